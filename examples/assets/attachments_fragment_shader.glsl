@@ -0,0 +1,15 @@
+#version 300 es
+in highp vec2 v_position;
+
+uniform lowp vec4 color;
+
+// each output lands in the framebuffer attachment with the same index
+layout(location = 0) out lowp vec4 albedo;
+layout(location = 1) out lowp vec4 normal;
+
+void main()
+{
+    albedo = color;
+    // a dome over the square, packed into 0..1 like a normal map
+    normal = vec4(normalize(vec3(v_position, 100.0)) * 0.5 + 0.5, 1.0);
+}
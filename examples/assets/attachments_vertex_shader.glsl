@@ -0,0 +1,13 @@
+#version 300 es
+in highp vec2 corner;
+
+uniform highp vec2 screen_size;
+uniform highp vec2 offset;
+
+out highp vec2 v_position;
+
+void main()
+{
+    gl_Position = vec4(((corner + offset) / screen_size * 2.0) - vec2(1.0, 1.0), 0.0, 1.0);
+    v_position = corner;
+}
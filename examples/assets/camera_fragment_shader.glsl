@@ -0,0 +1,9 @@
+#version 300 es
+uniform lowp vec4 color;
+
+out lowp vec4 frag_color;
+
+void main()
+{
+    frag_color = color;
+}
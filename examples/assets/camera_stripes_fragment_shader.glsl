@@ -0,0 +1,11 @@
+#version 300 es
+uniform lowp vec4 color;
+
+out lowp vec4 frag_color;
+
+void main()
+{
+    // stripes in screen space, so they stay the same width at any zoom
+    lowp float stripe = step(0.5, fract((gl_FragCoord.x + gl_FragCoord.y) / 16.0));
+    frag_color = vec4(color.rgb * (0.5 + 0.5 * stripe), color.a);
+}
@@ -0,0 +1,15 @@
+#version 300 es
+in highp vec2 corner;
+
+// shared by every program, uploaded once per frame
+layout(std140) uniform Camera
+{
+    highp mat4 view_projection;
+};
+
+uniform highp vec2 offset;
+
+void main()
+{
+    gl_Position = view_projection * vec4(corner + offset, 0.0, 1.0);
+}
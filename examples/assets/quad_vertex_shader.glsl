@@ -0,0 +1,14 @@
+#version 100
+attribute highp vec2 corner;
+
+uniform vec2 screen_size;
+uniform vec2 offset;
+uniform lowp vec4 color;
+
+varying lowp vec4 v_color;
+
+void main()
+{
+    gl_Position = vec4(((corner + offset) / screen_size * 2.0) - vec2(1.0, 1.0), 0.0, 1.0);
+    v_color = color;
+}
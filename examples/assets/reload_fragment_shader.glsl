@@ -0,0 +1,11 @@
+#version 100
+varying highp vec2 v_position;
+
+uniform highp float time;
+
+// edit and save this file while the example is running
+void main()
+{
+    highp vec2 uv = v_position * 0.5 + 0.5;
+    gl_FragColor = vec4(uv, 0.5 + 0.5 * sin(time), 1.0);
+}
@@ -0,0 +1,10 @@
+#version 100
+attribute highp vec2 position;
+
+varying highp vec2 v_position;
+
+void main()
+{
+    gl_Position = vec4(position, 0.0, 1.0);
+    v_position = position;
+}
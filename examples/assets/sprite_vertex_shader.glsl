@@ -0,0 +1,16 @@
+#version 100
+attribute highp vec2 position;
+attribute highp vec2 uv;
+attribute lowp vec4 color;
+
+uniform vec2 screen_size;
+
+varying highp vec2 v_uv;
+varying lowp vec4 v_color;
+
+void main()
+{
+    gl_Position = vec4((position / screen_size * 2.0) - vec2(1.0, 1.0), 0.0, 1.0);
+    v_uv = uv;
+    v_color = color;
+}
\ No newline at end of file
@@ -0,0 +1,164 @@
+extern crate embla;
+extern crate failure;
+
+use embla::math::Vec2;
+use embla::rendering::{
+    BufferUsage, Program, Renderer, Texture, TextureFormat, Uniform, Vertex, VertexAttributeType,
+    VertexBuffer,
+};
+use embla::window::WindowSettings;
+use failure::Error;
+
+const ATTACHMENTS_VERTEX_SHADER: &'static str =
+    include_str!("assets/attachments_vertex_shader.glsl");
+const ATTACHMENTS_FRAGMENT_SHADER: &'static str =
+    include_str!("assets/attachments_fragment_shader.glsl");
+const VERTEX_SHADER: &'static str = include_str!("assets/vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/fragment_shader.glsl");
+
+// both outputs of a single draw, the color on the left and the normals on the right
+const HALF_SIZE: u32 = 320;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Attachments Example".to_string())
+                    .size(Vec2::new(HALF_SIZE * 2, HALF_SIZE))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut quad_program, mut texture_program, vertex_buffer, framebuffer) = {
+            let renderer = window.renderer();
+            let half = HALF_SIZE as f32;
+
+            let mut quad_program = renderer
+                .create_program::<Corner>(ATTACHMENTS_VERTEX_SHADER, ATTACHMENTS_FRAGMENT_SHADER)
+                .unwrap();
+            quad_program.set_uniform("screen_size", Uniform::Vec2((half, half)));
+            quad_program.set_uniform("offset", Uniform::Vec2((half / 2.0, half / 2.0)));
+            let mut texture_program = renderer
+                .create_program::<TexturedVertex>(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            texture_program.set_uniform("screen_size", Uniform::Vec2((half * 2.0, half)));
+            texture_program.set_uniform("texture_size", Uniform::Vec2((half, half)));
+
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Stream).unwrap();
+            let framebuffer = renderer
+                .create_framebuffer_with_attachments(
+                    (HALF_SIZE, HALF_SIZE),
+                    &[TextureFormat::Rgba8, TextureFormat::Rgba8],
+                    None,
+                )
+                .unwrap();
+            (quad_program, texture_program, vertex_buffer, framebuffer)
+        };
+
+        let mut angle = 0.0f32;
+
+        move |dt, _input| {
+            angle += dt as f32 * 0.5;
+            let (red, green) = (angle.sin() * 0.5 + 0.5, angle.cos() * 0.5 + 0.5);
+            quad_program.set_uniform("color", Uniform::Vec4((red, green, 0.8, 1.0)));
+
+            let renderer = window.renderer();
+            renderer.bind_framebuffer(Some(&framebuffer));
+            // clears every attachment
+            renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+            let corners = rotated_square(angle, HALF_SIZE as f32 * 0.3);
+            renderer.render_vertices(&vertex_buffer, &quad_program, &corners)?;
+            renderer.bind_framebuffer(None);
+
+            renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+            let textures = framebuffer.textures();
+            draw_half(
+                &renderer,
+                &vertex_buffer,
+                &mut texture_program,
+                &textures[0],
+                0.0,
+            )?;
+            draw_half(
+                &renderer,
+                &vertex_buffer,
+                &mut texture_program,
+                &textures[1],
+                HALF_SIZE as f32,
+            )?;
+
+            Ok(())
+        }
+    });
+}
+
+fn rotated_square(angle: f32, radius: f32) -> Vec<Corner> {
+    let corner = |i: usize| {
+        let a = angle + i as f32 * std::f32::consts::PI / 2.0;
+        Corner {
+            corner: (a.cos() * radius, a.sin() * radius),
+        }
+    };
+    vec![
+        corner(0),
+        corner(1),
+        corner(2),
+        corner(0),
+        corner(2),
+        corner(3),
+    ]
+}
+
+fn draw_half(
+    renderer: &Renderer,
+    vertex_buffer: &VertexBuffer,
+    program: &mut Program<TexturedVertex>,
+    texture: &Texture,
+    x: f32,
+) -> Result<(), Error> {
+    let size = HALF_SIZE as f32;
+    let vertex = |px: f32, py: f32| TexturedVertex {
+        position: (x + px, py),
+        tex_coord: (px, py),
+    };
+    program.set_uniform("texture", Uniform::Texture(texture.clone()));
+    renderer.render_vertices(
+        vertex_buffer,
+        program,
+        &vec![
+            vertex(0.0, 0.0),
+            vertex(size, 0.0),
+            vertex(0.0, size),
+            vertex(size, 0.0),
+            vertex(size, size),
+            vertex(0.0, size),
+        ],
+    )
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
+
+#[repr(C)]
+pub struct TexturedVertex {
+    pub position: (f32, f32),
+    pub tex_coord: (f32, f32),
+}
+
+impl Vertex for TexturedVertex {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![
+            ("position".into(), 2, VertexAttributeType::Float),
+            ("tex_coord".into(), 2, VertexAttributeType::Float),
+        ]
+    }
+}
@@ -0,0 +1,33 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+// a square follows the mouse, on the web the canvas sits in a scrolled page with
+// a margin, border and padding and the square should stay under the pointer
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Canvas Offset Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        move |_dt, input| {
+            let renderer = window.renderer();
+            renderer.clear(Some((0.1, 0.1, 0.1, 1.0)));
+
+            if input.mouse_inside() {
+                let position = input.mouse_position();
+                renderer.set_scissor(Some((position.x - 8, position.y - 8, 16, 16)));
+                renderer.clear(Some((0.9, 0.6, 0.2, 1.0)));
+                renderer.set_scissor(None);
+            }
+
+            Ok(())
+        }
+    });
+}
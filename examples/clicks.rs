@@ -0,0 +1,51 @@
+extern crate embla;
+
+use embla::input::{Key, MouseButton};
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+// the same code runs in a native SDL2 window and in a browser canvas
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Clicks Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let buttons = [MouseButton::Left, MouseButton::Middle, MouseButton::Right];
+        let keys = [
+            Key::Space,
+            Key::Return,
+            Key::Escape,
+            Key::Left,
+            Key::Right,
+            Key::Up,
+            Key::Down,
+        ];
+
+        move |_dt, input| {
+            let position = input.mouse_position();
+            for button in buttons.iter() {
+                if input.mouse_button_is_pressed(button) {
+                    println!("{:?} down at ({}, {})", button, position.x, position.y);
+                }
+                if input.mouse_button_is_released(button) {
+                    println!("{:?} up at ({}, {})", button, position.x, position.y);
+                }
+            }
+            for key in keys.iter() {
+                if input.key_is_pressed(key) {
+                    println!("{:?} pressed", key);
+                }
+            }
+
+            window.renderer().clear(Some((0.1, 0.1, 0.1, 1.0)));
+
+            Ok(())
+        }
+    });
+}
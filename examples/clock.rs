@@ -0,0 +1,36 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::time::{Clock, FixedTimestep};
+use embla::window::WindowSettings;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Clock Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut clock = Clock::new();
+        let mut timestep = FixedTimestep::new(1.0 / 60.0);
+        let mut angle = 0.0f64;
+
+        move |_dt, _input| {
+            let delta = clock.delta();
+            println!("{:8.3}s delta: {:6.2}ms", clock.now(), delta * 1000.0);
+
+            for _ in 0..timestep.advance(delta) {
+                angle += timestep.step();
+            }
+
+            let t = (angle.sin() * 0.5 + 0.5) as f32;
+            window.renderer().clear(Some((t, t, t, 1.0)));
+
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,101 @@
+extern crate embla;
+
+use embla::assets::Image;
+use embla::graphics::{default_sprite_program, quad};
+use embla::math::{Rect, Vec2};
+use embla::rendering::{BufferUsage, CompressedFormat, TextureFiltering, Uniform};
+use embla::window::WindowSettings;
+
+const SIZE: u32 = 64;
+// RGB565 colors of the checkerboard
+const COLORS: [u16; 2] = [0xF800, 0x07E0];
+
+// every 4x4 block is a single color, the index bits all pick color0
+fn dxt1_checkerboard() -> Vec<u8> {
+    let mut data = Vec::new();
+    for by in 0..SIZE / 4 {
+        for bx in 0..SIZE / 4 {
+            let color = COLORS[((bx + by) % 2) as usize];
+            data.extend(&[
+                color as u8,
+                (color >> 8) as u8,
+                color as u8,
+                (color >> 8) as u8,
+            ]);
+            data.extend(&[0, 0, 0, 0]);
+        }
+    }
+    data
+}
+
+// the same checkerboard for GPUs without DXT support
+fn rgba_checkerboard() -> Image {
+    let mut data = Vec::new();
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let color = COLORS[((x / 4 + y / 4) % 2) as usize];
+            let (r, g) = ((color >> 11) as u8 * 8, ((color >> 5) & 0x3F) as u8 * 4);
+            data.extend(&[r, g, 0, 255]);
+        }
+    }
+    Image {
+        data,
+        width: SIZE,
+        height: SIZE,
+    }
+}
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Compressed Texture Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (program, vertex_buffer) = {
+            let renderer = window.renderer();
+            let mut program = default_sprite_program(&renderer).unwrap();
+
+            let filtering = Some(TextureFiltering::Nearest);
+            let texture = if renderer.compressed_format_supported(CompressedFormat::Dxt1) {
+                println!("using a DXT1 texture");
+                renderer
+                    .create_compressed_texture(
+                        CompressedFormat::Dxt1,
+                        (SIZE, SIZE),
+                        &dxt1_checkerboard(),
+                        filtering,
+                        None,
+                    )
+                    .unwrap()
+            } else {
+                println!("DXT1 is not supported, using an RGBA8 texture");
+                renderer
+                    .create_texture_from_image(&rgba_checkerboard(), filtering, None)
+                    .unwrap()
+            };
+            program.set_uniform("texture", Uniform::Texture(texture));
+
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+            (program, vertex_buffer)
+        };
+
+        let sprite = quad(
+            Rect::new(Vec2::new(128.0, 48.0), Vec2::new(512.0, 432.0)),
+            Rect::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)),
+            None,
+        );
+
+        move |_dt, _input| {
+            let renderer = window.renderer();
+            renderer.clear(Some((0.1, 0.1, 0.1, 1.0)));
+            renderer.render_vertices(&vertex_buffer, &program, &sprite)?;
+
+            Ok(())
+        }
+    });
+}
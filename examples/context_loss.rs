@@ -0,0 +1,98 @@
+extern crate embla;
+extern crate failure;
+
+use failure::Error;
+
+use embla::assets::image_from_png;
+use embla::graphics::{default_sprite_program, quad, SpriteVertex};
+use embla::input::{InputEvent, Key};
+use embla::math::{Rect, Vec2};
+use embla::rendering::{BufferUsage, Program, Renderer, TextureFiltering, Uniform, VertexBuffer};
+use embla::window::WindowSettings;
+
+const EMBLA_LOGO: &'static [u8] = include_bytes!("assets/embla.png");
+
+// everything on the GPU, created at startup and again after a context loss
+struct Resources {
+    program: Program<SpriteVertex>,
+    vertex_buffer: VertexBuffer,
+    size: Vec2<f32>,
+}
+
+fn create_resources(renderer: &Renderer) -> Result<Resources, Error> {
+    let mut program = default_sprite_program(renderer)?;
+    let image = image_from_png(EMBLA_LOGO)?;
+    let texture =
+        renderer.create_texture_from_image(&image, Some(TextureFiltering::Linear), None)?;
+    program.set_uniform("texture", Uniform::Texture(texture));
+    Ok(Resources {
+        program,
+        vertex_buffer: renderer.create_vertex_buffer(BufferUsage::Static)?,
+        size: Vec2::new(image.width as f32, image.height as f32),
+    })
+}
+
+// press L to lose the WebGL context and R to restore it, the logo comes back
+// once the resources have been created again
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Context Loss Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut resources = Some(create_resources(&window.renderer()).unwrap());
+        let mut angle = 0.0f32;
+
+        move |dt, input| {
+            for event in input.poll_events() {
+                match event {
+                    InputEvent::ContextLost => {
+                        println!("context lost");
+                        // the old resources are invalid, drop them without using them
+                        resources = None;
+                    }
+                    InputEvent::ContextRestored => {
+                        println!("context restored");
+                        resources = Some(create_resources(&window.renderer())?);
+                    }
+                    _ => {}
+                }
+            }
+            if input.key_is_pressed(&Key::L) {
+                window.simulate_context_loss();
+            }
+            if input.key_is_pressed(&Key::R) {
+                window.simulate_context_restore();
+            }
+
+            // pause while there is nothing to draw with
+            if input.context_lost() {
+                return Ok(());
+            }
+            let resources = match resources {
+                Some(ref resources) => resources,
+                None => return Ok(()),
+            };
+            angle += dt as f32;
+
+            let renderer = window.renderer();
+            renderer.clear(Some((0.1, 0.1, 0.1, 1.0)));
+            let center = Vec2::new(320.0 + angle.cos() * 100.0, 240.0 + angle.sin() * 100.0);
+            let half = resources.size / 2.0;
+            let sprite = quad(
+                Rect::new(center - half, center + half),
+                // images start with the top row, while y points up on screen
+                Rect::new(Vec2::new(0.0, 1.0), Vec2::new(1.0, 0.0)),
+                None,
+            );
+            renderer.render_vertices(&resources.vertex_buffer, &resources.program, &sprite)?;
+
+            Ok(())
+        }
+    });
+}
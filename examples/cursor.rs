@@ -0,0 +1,47 @@
+extern crate embla;
+
+use embla::input::MouseButton;
+use embla::math::Vec2;
+use embla::window::{CursorStyle, WindowSettings};
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Cursor Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut cursor_visible = true;
+        let mut hovering = false;
+
+        move |_dt, input| {
+            // clicking toggles the system cursor, like switching to a crosshair
+            // drawn by the game
+            if input.mouse_button_is_pressed(&MouseButton::Left) {
+                cursor_visible = !cursor_visible;
+                window.set_cursor_visible(cursor_visible)?;
+            }
+
+            // pretend the left half of the window is a button
+            let position = input.mouse_position();
+            let over_button = position.x < 320;
+            if over_button != hovering {
+                hovering = over_button;
+                window.set_cursor_style(if hovering {
+                    CursorStyle::Pointer
+                } else {
+                    CursorStyle::Crosshair
+                })?;
+            }
+
+            let shade = if hovering { 0.4 } else { 0.2 };
+            window.renderer().clear(Some((shade, shade, shade, 1.0)));
+
+            Ok(())
+        }
+    });
+}
@@ -1,7 +1,7 @@
 extern crate embla;
 extern crate failure;
 
-use std::rc::Rc;
+use std::sync::Arc;
 
 use failure::Error;
 
@@ -9,7 +9,7 @@ use embla::assets::image_from_png;
 use embla::graphics::{TextureAtlas, TextureImage};
 use embla::math::Vec2;
 use embla::rand;
-use embla::rendering::{Texture, TextureFiltering, Uniform, Vertex, VertexAttributeType};
+use embla::rendering::{BufferUsage, TextureFiltering, Uniform, Vertex, VertexAttributeType};
 use embla::window::WindowSettings;
 
 const EMBLA_LOGO: &'static [u8] = include_bytes!("assets/embla.png");
@@ -28,13 +28,11 @@ fn main() {
             )
             .unwrap();
 
-        let mut atlas = TextureAtlas::new((4096, 4096));
         let texture_size = (4096, 4096);
-        let (program, atlas_texture, vertex_buffer) = {
+        let (program, mut atlas, vertex_buffer) = {
             let renderer = window.renderer();
 
-            let texture = renderer
-                .create_texture(texture_size, Some(TextureFiltering::Nearest))
+            let atlas = TextureAtlas::new(&renderer, texture_size, Some(TextureFiltering::Nearest))
                 .unwrap();
             let mut program = renderer
                 .create_program::<TexturedVertex>(VERTEX_SHADER, FRAGMENT_SHADER)
@@ -49,14 +47,14 @@ fn main() {
                 "texture_size",
                 Uniform::Vec2((texture_size.0 as f32, texture_size.1 as f32)),
             );
-            program.set_uniform("texture", Uniform::Texture(texture.clone()));
+            program.set_uniform("texture", Uniform::Texture(atlas.texture().clone()));
 
-            let vertex_buffer = renderer.create_vertex_buffer().unwrap();
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Dynamic).unwrap();
 
-            (program, texture, vertex_buffer)
+            (program, atlas, vertex_buffer)
         };
 
-        let logo = TextureImage::new(Rc::new(image_from_png(EMBLA_LOGO).unwrap()));
+        let logo = TextureImage::new(Arc::new(image_from_png(EMBLA_LOGO).unwrap()));
 
         let mut logos: Vec<(Vec2<f32>, Vec2<f32>)> = (0..100)
             .map(|_| {
@@ -83,7 +81,7 @@ fn main() {
                 }
                 *p += *v * dt as f32;
 
-                draw_texture(&mut vertices, &mut atlas, &atlas_texture, &logo, *p).unwrap();
+                draw_texture(&mut vertices, &mut atlas, &logo, *p).unwrap();
             }
 
             let renderer = window.renderer();
@@ -115,19 +113,17 @@ impl Vertex for TexturedVertex {
 fn draw_texture(
     vertices: &mut Vec<TexturedVertex>,
     atlas: &mut TextureAtlas,
-    atlas_texture: &Texture,
     texture: &TextureImage,
     position: Vec2<f32>,
 ) -> Result<(), Error> {
-    let tex_region = match atlas.get_texture_block(texture) {
-        Some(region) => region,
-        None => {
-            let region = atlas.add_texture(texture)?;
-            atlas_texture.set_region(texture.image(), (region[0], region[1]));
-            region
-        }
-    };
-    let size = (tex_region[2] - tex_region[0], tex_region[3] - tex_region[1]);
+    let region = atlas.add_texture_image(texture)?;
+    let size = region.size;
+    let tex_region = [
+        region.position.0,
+        region.position.1,
+        region.position.0 + size.0,
+        region.position.1 + size.1,
+    ];
 
     let rect = (
         size.0 as f32 / -2.0,
@@ -0,0 +1,33 @@
+extern crate embla;
+
+use embla::input::InputEvent;
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+// handles input as a queue of events instead of querying the input state
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Events Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        move |_dt, input| {
+            for event in input.poll_events() {
+                match event {
+                    // reported every frame the mouse moves, too noisy to print
+                    InputEvent::MouseMove(..) | InputEvent::MouseDelta(..) => {}
+                    event => println!("{:?}", event),
+                }
+            }
+
+            window.renderer().clear(Some((0.1, 0.1, 0.1, 1.0)));
+
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,39 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Focus Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut counter = 0u32;
+
+        move |_dt, input| {
+            if input.focused() {
+                counter += 1;
+                if counter % 60 == 0 {
+                    println!("counter: {}", counter);
+                }
+            }
+
+            // fade to gray while paused
+            let renderer = window.renderer();
+            if input.focused() {
+                let t = (counter % 120) as f32 / 120.0;
+                renderer.clear(Some((t, 0.3, 1.0 - t, 1.0)));
+            } else {
+                renderer.clear(Some((0.2, 0.2, 0.2, 1.0)));
+            }
+
+            Ok(())
+        }
+    });
+}
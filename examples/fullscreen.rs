@@ -0,0 +1,45 @@
+extern crate embla;
+
+use embla::input::Key;
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Fullscreen Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut was_fullscreen = false;
+
+        move |_dt, input| {
+            // F11 is taken by the browser, so toggle with F instead
+            if input.key_is_pressed(&Key::F) {
+                if input.fullscreen() {
+                    window.exit_fullscreen()?;
+                } else {
+                    window.request_fullscreen()?;
+                }
+            }
+
+            if input.fullscreen() != was_fullscreen {
+                was_fullscreen = input.fullscreen();
+                println!("fullscreen: {}", was_fullscreen);
+            }
+
+            let renderer = window.renderer();
+            if input.fullscreen() {
+                renderer.clear(Some((0.1, 0.4, 0.2, 1.0)));
+            } else {
+                renderer.clear(Some((0.2, 0.2, 0.2, 1.0)));
+            }
+
+            Ok(())
+        }
+    });
+}
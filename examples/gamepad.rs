@@ -0,0 +1,108 @@
+extern crate embla;
+
+use embla::assets::Image;
+use embla::graphics::SpriteBatch;
+use embla::graphics::SpriteVertex;
+use embla::math::{Rect, Vec2};
+use embla::rendering::{TextureFiltering, Uniform};
+use embla::window::WindowSettings;
+
+const VERTEX_SHADER: &'static str = r#"#version 100
+attribute highp vec2 position;
+attribute highp vec2 uv;
+attribute lowp vec4 color;
+
+uniform vec2 screen_size;
+
+varying highp vec2 v_uv;
+varying lowp vec4 v_color;
+
+void main()
+{
+    gl_Position = vec4((position / screen_size * 2.0) - vec2(1.0, 1.0), 0.0, 1.0);
+    v_uv = uv;
+    v_color = color;
+}
+"#;
+
+const FRAGMENT_SHADER: &'static str = r#"#version 100
+varying highp vec2 v_uv;
+varying lowp vec4 v_color;
+
+uniform sampler2D texture;
+
+void main()
+{
+    gl_FragColor = texture2D(texture, v_uv) * v_color;
+}
+"#;
+
+const SPEED: f32 = 300.0;
+const DEAD_ZONE: f32 = 0.15;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Gamepad Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, texture, mut batch) = {
+            let renderer = window.renderer();
+            let mut program = renderer
+                .create_program::<SpriteVertex>(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            let screen_size = renderer.screen_size();
+            program.set_uniform(
+                "screen_size",
+                Uniform::Vec2((screen_size.0 as f32, screen_size.1 as f32)),
+            );
+
+            let white = Image {
+                data: vec![255; 4],
+                width: 1,
+                height: 1,
+            };
+            let texture = renderer
+                .create_texture_from_image(&white, Some(TextureFiltering::Nearest), None)
+                .unwrap();
+            let batch = SpriteBatch::new(&renderer, 16).unwrap();
+
+            (program, texture, batch)
+        };
+
+        let mut position = Vec2::new(320.0, 240.0);
+
+        move |dt, input| {
+            // the left stick is the first two axes on standard gamepads, up is negative
+            if let Some(gamepad) = input.gamepad(0) {
+                let stick = Vec2::new(gamepad.axis(0, DEAD_ZONE), -gamepad.axis(1, DEAD_ZONE));
+                position += stick * SPEED * dt as f32;
+            }
+            let color = match input.gamepad(0) {
+                Some(gamepad) if gamepad.button(0) > 0.5 => (1.0, 0.4, 0.4, 1.0),
+                Some(_) => (1.0, 1.0, 1.0, 1.0),
+                None => (0.4, 0.4, 0.4, 1.0),
+            };
+
+            let renderer = window.renderer();
+            renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+
+            let mut pass = batch.begin(&renderer, &mut program);
+            pass.draw(
+                &texture,
+                Rect::new(
+                    position - Vec2::new(16.0, 16.0),
+                    position + Vec2::new(16.0, 16.0),
+                ),
+                Rect::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0)),
+                color,
+            )?;
+            pass.end()
+        }
+    });
+}
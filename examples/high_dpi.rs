@@ -0,0 +1,82 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::rendering::{BufferUsage, Uniform, Vertex, VertexAttributeType};
+use embla::window::WindowSettings;
+
+const QUAD_VERTEX_SHADER: &'static str = include_str!("assets/quad_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/color_fragment_shader.glsl");
+
+// lines one pixel wide with one pixel gaps, on a high DPI screen they only stay
+// sharp when every pixel of the drawable maps to one physical pixel
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla High DPI Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string())
+                    .high_dpi(true),
+            )
+            .unwrap();
+
+        println!(
+            "{}x{} pixels for a {}x{} window, scale factor {}",
+            window.size().x,
+            window.size().y,
+            window.logical_size().x,
+            window.logical_size().y,
+            window.scale_factor()
+        );
+
+        let (mut program, vertex_buffer) = {
+            let renderer = window.renderer();
+            let mut program = renderer
+                .create_program::<Corner>(QUAD_VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            program.set_uniform("offset", Uniform::Vec2((0.0, 0.0)));
+            program.set_uniform("color", Uniform::Vec4((1.0, 1.0, 1.0, 1.0)));
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+            (program, vertex_buffer)
+        };
+
+        move |_dt, _input| {
+            // sizes are in drawable pixels, so the lines follow the physical pixels
+            let size = window.size();
+            let mut lines = Vec::new();
+            for x in (0..size.x / 2).filter(|x| x % 2 == 0) {
+                let corner = |dx: f32, y: f32| Corner {
+                    corner: (x as f32 + dx, y * size.y as f32),
+                };
+                lines.extend(vec![
+                    corner(0.0, 0.0),
+                    corner(1.0, 0.0),
+                    corner(0.0, 1.0),
+                    corner(1.0, 0.0),
+                    corner(1.0, 1.0),
+                    corner(0.0, 1.0),
+                ]);
+            }
+
+            program.set_uniform("screen_size", Uniform::Vec2((size.x as f32, size.y as f32)));
+            let renderer = window.renderer();
+            renderer.set_viewport(0, 0, size.x, size.y);
+            renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+            renderer.render_vertices(&vertex_buffer, &program, &lines)?;
+
+            Ok(())
+        }
+    });
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
@@ -0,0 +1,46 @@
+extern crate embla;
+
+use embla::input::MouseButton;
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Hover Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut hovered = true;
+
+        move |_dt, input| {
+            if input.mouse_inside() != hovered {
+                hovered = input.mouse_inside();
+                println!(
+                    "{}",
+                    if hovered {
+                        "mouse entered"
+                    } else {
+                        "mouse left"
+                    }
+                );
+            }
+
+            // leaving the window ends a drag
+            let renderer = window.renderer();
+            if input.mouse_button_is_down(&MouseButton::Left) {
+                renderer.clear(Some((0.9, 0.6, 0.2, 1.0)));
+            } else if hovered {
+                renderer.clear(Some((0.3, 0.5, 0.9, 1.0)));
+            } else {
+                renderer.clear(Some((0.2, 0.2, 0.2, 1.0)));
+            }
+
+            Ok(())
+        }
+    });
+}
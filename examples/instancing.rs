@@ -0,0 +1,171 @@
+extern crate embla;
+
+use embla::input::Key;
+use embla::math::Vec2;
+use embla::rand;
+use embla::rendering::{BufferUsage, Uniform, Vertex, VertexAttributeType};
+use embla::time::Clock;
+use embla::window::WindowSettings;
+
+const INSTANCED_VERTEX_SHADER: &'static str = include_str!("assets/instanced_vertex_shader.glsl");
+const QUAD_VERTEX_SHADER: &'static str = include_str!("assets/quad_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/color_fragment_shader.glsl");
+
+const QUAD_COUNT: usize = 10_000;
+const QUAD_SIZE: f32 = 4.0;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Instancing Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (instanced_program, mut quad_program, vertex_buffer) = {
+            let renderer = window.renderer();
+            let screen_size = renderer.screen_size();
+            let screen_size = (screen_size.0 as f32, screen_size.1 as f32);
+
+            let mut instanced_program = renderer
+                .create_program::<Corner>(INSTANCED_VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            instanced_program.set_uniform("screen_size", Uniform::Vec2(screen_size));
+            let mut quad_program = renderer
+                .create_program::<Corner>(QUAD_VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            quad_program.set_uniform("screen_size", Uniform::Vec2(screen_size));
+
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Stream).unwrap();
+            (instanced_program, quad_program, vertex_buffer)
+        };
+
+        let corners = vec![
+            Corner { corner: (0.0, 0.0) },
+            Corner {
+                corner: (QUAD_SIZE, 0.0),
+            },
+            Corner {
+                corner: (0.0, QUAD_SIZE),
+            },
+            Corner {
+                corner: (QUAD_SIZE, 0.0),
+            },
+            Corner {
+                corner: (QUAD_SIZE, QUAD_SIZE),
+            },
+            Corner {
+                corner: (0.0, QUAD_SIZE),
+            },
+        ];
+        let mut quads: Vec<(Quad, Vec2<f32>)> = (0..QUAD_COUNT)
+            .map(|_| {
+                (
+                    Quad {
+                        offset: (rand() * 640.0, rand() * 480.0),
+                        color: (rand(), rand(), rand(), 1.0),
+                    },
+                    Vec2::new(rand() - 0.5, rand() - 0.5) * 100.0,
+                )
+            })
+            .collect();
+
+        // not every GPU has timer queries, only the CPU time is printed without them
+        let mut gpu_timer = window.renderer().create_gpu_timer().ok();
+        let mut instanced = true;
+        let mut clock = Clock::new();
+        let mut frame_time = 0.0;
+        let mut frames = 0;
+
+        move |dt, input| {
+            if input.key_is_pressed(&Key::Space) {
+                instanced = !instanced;
+                println!("instanced: {}", instanced);
+            }
+
+            for &mut (ref mut quad, velocity) in quads.iter_mut() {
+                quad.offset.0 = (quad.offset.0 + velocity.x * dt as f32 + 640.0) % 640.0;
+                quad.offset.1 = (quad.offset.1 + velocity.y * dt as f32 + 480.0) % 480.0;
+            }
+
+            clock.delta();
+            let renderer = window.renderer();
+            if let Some(ref mut timer) = gpu_timer {
+                timer.begin();
+            }
+            renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+            if instanced {
+                let instances: Vec<Quad> = quads.iter().map(|&(quad, _)| quad).collect();
+                renderer.render_instanced(
+                    &vertex_buffer,
+                    &instanced_program,
+                    &corners,
+                    &instances,
+                )?;
+            } else {
+                for &(quad, _) in quads.iter() {
+                    quad_program.set_uniform("offset", Uniform::Vec2(quad.offset));
+                    quad_program.set_uniform("color", Uniform::Vec4(quad.color));
+                    renderer.render_vertices(&vertex_buffer, &quad_program, &corners)?;
+                }
+            }
+            frame_time += clock.delta();
+            if let Some(ref mut timer) = gpu_timer {
+                timer.end();
+            }
+
+            frames += 1;
+            if frames == 60 {
+                let gpu_time = match gpu_timer.as_ref().and_then(|timer| timer.elapsed_ms()) {
+                    Some(ms) => format!(", {:.2}ms on the GPU", ms),
+                    None => String::new(),
+                };
+                println!(
+                    "{} quads {}: {:.2}ms to submit{}",
+                    QUAD_COUNT,
+                    if instanced {
+                        "instanced"
+                    } else {
+                        "drawn one by one"
+                    },
+                    frame_time / frames as f64 * 1000.0,
+                    gpu_time
+                );
+                frames = 0;
+                frame_time = 0.0;
+            }
+
+            Ok(())
+        }
+    });
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct Quad {
+    pub offset: (f32, f32),
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Vertex for Quad {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![
+            ("offset".into(), 2, VertexAttributeType::Float),
+            ("color".into(), 4, VertexAttributeType::Float),
+        ]
+    }
+}
@@ -0,0 +1,65 @@
+extern crate embla;
+
+use embla::assets::load_image;
+use embla::graphics::{default_sprite_program, quad};
+use embla::math::{Rect, Vec2};
+use embla::rendering::{BufferUsage, TextureFiltering, Uniform};
+use embla::window::WindowSettings;
+
+// relative to the working directory natively and to the page on the web, run it
+// from the repository root or serve the examples directory next to the page
+const EMBLA_LOGO: &'static str = "examples/assets/embla.png";
+
+// loads the logo at runtime instead of including it, drawing starts once it has arrived
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Image Loading Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, vertex_buffer) = {
+            let renderer = window.renderer();
+            let program = default_sprite_program(&renderer).unwrap();
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+            (program, vertex_buffer)
+        };
+
+        let mut logo = load_image(EMBLA_LOGO);
+        let mut sprite = None;
+
+        move |_dt, _input| {
+            let renderer = window.renderer();
+
+            if let Some(image) = logo.take() {
+                let image = image?;
+                let size = Vec2::new(image.width as f32, image.height as f32);
+                let texture = renderer.create_texture_from_image(
+                    &image,
+                    Some(TextureFiltering::Linear),
+                    None,
+                )?;
+                program.set_uniform("texture", Uniform::Texture(texture));
+
+                let center = Vec2::new(320.0, 240.0);
+                sprite = Some(quad(
+                    Rect::new(center - size / 2.0, center + size / 2.0),
+                    // images start with the top row, while y points up on screen
+                    Rect::new(Vec2::new(0.0, 1.0), Vec2::new(1.0, 0.0)),
+                    None,
+                ));
+            }
+
+            renderer.clear(Some((0.1, 0.1, 0.1, 1.0)));
+            if let Some(ref sprite) = sprite {
+                renderer.render_vertices(&vertex_buffer, &program, sprite)?;
+            }
+
+            Ok(())
+        }
+    });
+}
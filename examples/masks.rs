@@ -0,0 +1,129 @@
+extern crate embla;
+
+use std::f32::consts::PI;
+
+use embla::graphics::StencilMask;
+use embla::math::Vec2;
+use embla::rendering::{BufferUsage, Uniform, Vertex, VertexAttributeType};
+use embla::window::WindowSettings;
+
+const QUAD_VERTEX_SHADER: &'static str = include_str!("assets/quad_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/color_fragment_shader.glsl");
+
+// stripes clipped to a rounded panel, and a circle following the mouse clipped
+// to the panel as well
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Masks Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, vertex_buffer) = {
+            let renderer = window.renderer();
+            let mut program = renderer
+                .create_program::<Corner>(QUAD_VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            program.set_uniform("screen_size", Uniform::Vec2((640.0, 480.0)));
+            program.set_uniform("offset", Uniform::Vec2((0.0, 0.0)));
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Stream).unwrap();
+            (program, vertex_buffer)
+        };
+
+        let panel = rounded_rectangle(120.0, 90.0, 400.0, 300.0, 48.0);
+        let mut stripes = Vec::new();
+        for i in 0..20 {
+            stripes.extend(rectangle(i as f32 * 32.0, 0.0, 16.0, 480.0));
+        }
+        let background = rectangle(0.0, 0.0, 640.0, 480.0);
+        let mut mask = StencilMask::new();
+
+        move |_dt, input| {
+            let renderer = window.renderer();
+            renderer.clear_all(Some((0.1, 0.1, 0.1, 1.0)));
+
+            mask.begin_mask(&renderer)?;
+            renderer.render_vertices(&vertex_buffer, &program, &panel)?;
+            mask.end_mask(&renderer);
+
+            program.set_uniform("color", Uniform::Vec4((0.2, 0.4, 0.8, 1.0)));
+            renderer.render_vertices(&vertex_buffer, &program, &stripes)?;
+
+            // the circle only counts where it overlaps the panel
+            let mouse = input.mouse_position();
+            let center = (mouse.x as f32, 480.0 - mouse.y as f32);
+            mask.begin_mask(&renderer)?;
+            renderer.render_vertices(&vertex_buffer, &program, &circle(center, 80.0))?;
+            mask.end_mask(&renderer);
+
+            program.set_uniform("color", Uniform::Vec4((0.9, 0.7, 0.2, 1.0)));
+            renderer.render_vertices(&vertex_buffer, &program, &background)?;
+
+            mask.pop_mask(&renderer);
+            mask.pop_mask(&renderer);
+
+            Ok(())
+        }
+    });
+}
+
+fn rectangle(x: f32, y: f32, width: f32, height: f32) -> Vec<Corner> {
+    let corner = |dx: f32, dy: f32| Corner {
+        corner: (x + dx * width, y + dy * height),
+    };
+    vec![
+        corner(0.0, 0.0),
+        corner(1.0, 0.0),
+        corner(0.0, 1.0),
+        corner(1.0, 0.0),
+        corner(1.0, 1.0),
+        corner(0.0, 1.0),
+    ]
+}
+
+// triangles from the center to points around the circle
+fn circle(center: (f32, f32), radius: f32) -> Vec<Corner> {
+    let point = |i: usize| {
+        let a = i as f32 * 2.0 * PI / 32.0;
+        Corner {
+            corner: (center.0 + a.cos() * radius, center.1 + a.sin() * radius),
+        }
+    };
+    let mut vertices = Vec::new();
+    for i in 0..32 {
+        vertices.push(Corner { corner: center });
+        vertices.push(point(i));
+        vertices.push(point(i + 1));
+    }
+    vertices
+}
+
+// a cross of two rectangles with a circle in each corner
+fn rounded_rectangle(x: f32, y: f32, width: f32, height: f32, radius: f32) -> Vec<Corner> {
+    let mut vertices = rectangle(x + radius, y, width - 2.0 * radius, height);
+    vertices.extend(rectangle(x, y + radius, width, height - 2.0 * radius));
+    for &(cx, cy) in &[
+        (x + radius, y + radius),
+        (x + width - radius, y + radius),
+        (x + radius, y + height - radius),
+        (x + width - radius, y + height - radius),
+    ] {
+        vertices.extend(circle((cx, cy), radius));
+    }
+    vertices
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
@@ -0,0 +1,59 @@
+extern crate embla;
+
+use std::f32::consts::PI;
+
+use embla::input::{Key, MouseButton};
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+// radians turned per pixel of mouse movement
+const SENSITIVITY: f32 = 0.004;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Mouse Look Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut yaw = 0.0f32;
+        let mut pitch = 0.0f32;
+
+        move |_dt, input| {
+            if !input.pointer_locked() {
+                if input.mouse_button_is_pressed(&MouseButton::Left) {
+                    window.request_pointer_lock();
+                }
+            } else {
+                if input.key_is_pressed(&Key::Escape) {
+                    window.exit_pointer_lock();
+                }
+
+                let delta = input.mouse_delta();
+                yaw = (yaw + delta.x as f32 * SENSITIVITY) % (2.0 * PI);
+                pitch = (pitch - delta.y as f32 * SENSITIVITY)
+                    .max(-PI / 2.0)
+                    .min(PI / 2.0);
+            }
+
+            // visualize the camera direction with the clear color
+            let forward = (
+                yaw.sin() * pitch.cos(),
+                pitch.sin(),
+                yaw.cos() * pitch.cos(),
+            );
+            window.renderer().clear(Some((
+                forward.0 * 0.5 + 0.5,
+                forward.1 * 0.5 + 0.5,
+                forward.2 * 0.5 + 0.5,
+                1.0,
+            )));
+
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,186 @@
+extern crate embla;
+extern crate failure;
+
+use embla::input::Key;
+use embla::math::Vec2;
+use embla::rendering::{
+    BufferUsage, Framebuffer, Program, Renderer, Uniform, Vertex, VertexAttributeType, VertexBuffer,
+};
+use embla::window::WindowSettings;
+use failure::Error;
+
+const QUAD_VERTEX_SHADER: &'static str = include_str!("assets/quad_vertex_shader.glsl");
+const COLOR_FRAGMENT_SHADER: &'static str = include_str!("assets/color_fragment_shader.glsl");
+const VERTEX_SHADER: &'static str = include_str!("assets/vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/fragment_shader.glsl");
+
+// the left half of the window shows the plain framebuffer, the right half the
+// multisampled one
+const HALF_SIZE: u32 = 320;
+const SAMPLES: u32 = 4;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla MSAA Example".to_string())
+                    .size(Vec2::new(HALF_SIZE * 2, HALF_SIZE))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (quad_program, mut texture_program, vertex_buffer, plain, multisampled) = {
+            let renderer = window.renderer();
+            let half = HALF_SIZE as f32;
+
+            let mut quad_program = renderer
+                .create_program::<Corner>(QUAD_VERTEX_SHADER, COLOR_FRAGMENT_SHADER)
+                .unwrap();
+            quad_program.set_uniform("screen_size", Uniform::Vec2((half, half)));
+            quad_program.set_uniform("offset", Uniform::Vec2((half / 2.0, half / 2.0)));
+            quad_program.set_uniform("color", Uniform::Vec4((1.0, 1.0, 1.0, 1.0)));
+            let mut texture_program = renderer
+                .create_program::<TexturedVertex>(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            texture_program.set_uniform("screen_size", Uniform::Vec2((half * 2.0, half)));
+            texture_program.set_uniform("texture_size", Uniform::Vec2((half, half)));
+
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Stream).unwrap();
+            let plain = renderer
+                .create_framebuffer((HALF_SIZE, HALF_SIZE), None)
+                .unwrap();
+            let multisampled = renderer
+                .create_multisampled_framebuffer((HALF_SIZE, HALF_SIZE), SAMPLES, None)
+                .unwrap();
+            (
+                quad_program,
+                texture_program,
+                vertex_buffer,
+                plain,
+                multisampled,
+            )
+        };
+
+        let mut angle = 0.3f32;
+        let mut measure = true;
+        println!("press space to count the edge pixels on both sides");
+
+        move |dt, input| {
+            angle += dt as f32 * 0.2;
+            measure |= input.key_is_pressed(&Key::Space);
+
+            let renderer = window.renderer();
+            let corners = rotated_square(angle, HALF_SIZE as f32 * 0.3);
+            for framebuffer in [&plain, &multisampled].iter() {
+                renderer.bind_framebuffer(Some(framebuffer));
+                renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+                renderer.render_vertices(&vertex_buffer, &quad_program, &corners)?;
+            }
+            renderer.resolve_framebuffer(&multisampled)?;
+            renderer.bind_framebuffer(None);
+
+            renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+            draw_half(&renderer, &vertex_buffer, &mut texture_program, &plain, 0.0)?;
+            draw_half(
+                &renderer,
+                &vertex_buffer,
+                &mut texture_program,
+                &multisampled,
+                HALF_SIZE as f32,
+            )?;
+
+            if measure {
+                measure = false;
+                let image = renderer.read_pixels(0, 0, HALF_SIZE * 2, HALF_SIZE)?;
+                let (mut left, mut right) = (0, 0);
+                for (i, pixel) in image.data.chunks(4).enumerate() {
+                    // partially covered pixels get shades between black and white
+                    if pixel[0] > 0 && pixel[0] < 255 {
+                        if (i as u32 % (HALF_SIZE * 2)) < HALF_SIZE {
+                            left += 1;
+                        } else {
+                            right += 1;
+                        }
+                    }
+                }
+                println!(
+                    "blended edge pixels: {} without multisampling, {} with {}x",
+                    left, right, SAMPLES
+                );
+            }
+
+            Ok(())
+        }
+    });
+}
+
+fn rotated_square(angle: f32, radius: f32) -> Vec<Corner> {
+    let corner = |i: usize| {
+        let a = angle + i as f32 * std::f32::consts::PI / 2.0;
+        Corner {
+            corner: (a.cos() * radius, a.sin() * radius),
+        }
+    };
+    vec![
+        corner(0),
+        corner(1),
+        corner(2),
+        corner(0),
+        corner(2),
+        corner(3),
+    ]
+}
+
+fn draw_half(
+    renderer: &Renderer,
+    vertex_buffer: &VertexBuffer,
+    program: &mut Program<TexturedVertex>,
+    framebuffer: &Framebuffer,
+    x: f32,
+) -> Result<(), Error> {
+    let size = HALF_SIZE as f32;
+    let vertex = |px: f32, py: f32| TexturedVertex {
+        position: (x + px, py),
+        tex_coord: (px, py),
+    };
+    program.set_uniform("texture", Uniform::Texture(framebuffer.texture().clone()));
+    renderer.render_vertices(
+        vertex_buffer,
+        program,
+        &vec![
+            vertex(0.0, 0.0),
+            vertex(size, 0.0),
+            vertex(0.0, size),
+            vertex(size, 0.0),
+            vertex(size, size),
+            vertex(0.0, size),
+        ],
+    )
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
+
+#[repr(C)]
+pub struct TexturedVertex {
+    pub position: (f32, f32),
+    pub tex_coord: (f32, f32),
+}
+
+impl Vertex for TexturedVertex {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![
+            ("position".into(), 2, VertexAttributeType::Float),
+            ("tex_coord".into(), 2, VertexAttributeType::Float),
+        ]
+    }
+}
@@ -0,0 +1,94 @@
+extern crate embla;
+
+use embla::input::MouseButton;
+use embla::math::Vec2;
+use embla::rendering::{BufferUsage, Uniform, Vertex, VertexAttributeType};
+use embla::window::WindowSettings;
+
+const QUAD_VERTEX_SHADER: &'static str = include_str!("assets/quad_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/color_fragment_shader.glsl");
+
+const TILE_SIZE: f32 = 32.0;
+const TILES: usize = 40;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Pan Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, vertex_buffer) = {
+            let renderer = window.renderer();
+            let mut program = renderer
+                .create_program::<Corner>(QUAD_VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            program.set_uniform("screen_size", Uniform::Vec2((640.0, 480.0)));
+            program.set_uniform("color", Uniform::Vec4((0.8, 0.8, 0.8, 1.0)));
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+            (program, vertex_buffer)
+        };
+
+        // a checkerboard bigger than the window
+        let mut board = Vec::new();
+        for y in 0..TILES {
+            for x in 0..TILES {
+                if (x + y) % 2 == 0 {
+                    let (x, y) = (x as f32 * TILE_SIZE, y as f32 * TILE_SIZE);
+                    let corner = |dx: f32, dy: f32| Corner {
+                        corner: (x + dx * TILE_SIZE, y + dy * TILE_SIZE),
+                    };
+                    board.extend(vec![
+                        corner(0.0, 0.0),
+                        corner(1.0, 0.0),
+                        corner(0.0, 1.0),
+                        corner(1.0, 0.0),
+                        corner(1.0, 1.0),
+                        corner(0.0, 1.0),
+                    ]);
+                }
+            }
+        }
+
+        let mut pan = Vec2::new(-320.0, -320.0);
+        let mut last_position = None;
+
+        move |_dt, input| {
+            // the button state stays right even when it is released off the canvas
+            if input.mouse_button_is_down(&MouseButton::Left) {
+                let position = input.mouse_position();
+                if let Some(last) = last_position {
+                    let delta: Vec2<i32> = position - last;
+                    // screen y points down, the shader's y points up
+                    pan.x += delta.x as f32;
+                    pan.y -= delta.y as f32;
+                }
+                last_position = Some(position);
+            } else {
+                last_position = None;
+            }
+
+            program.set_uniform("offset", Uniform::Vec2((pan.x, pan.y)));
+            let renderer = window.renderer();
+            renderer.clear(Some((0.1, 0.1, 0.1, 1.0)));
+            renderer.render_vertices(&vertex_buffer, &program, &board)?;
+
+            Ok(())
+        }
+    });
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
@@ -0,0 +1,29 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::time;
+
+// radians per second
+const SPEED: f64 = 1.5;
+
+fn main() {
+    // spin the corners of a unit quad for five seconds, printing them every
+    // half second
+    let start = time::now();
+    let mut last_print = start;
+    time::run_loop(move |now| {
+        let angle = (now - start) * SPEED;
+        let (sin, cos) = (angle.sin() as f32, angle.cos() as f32);
+        let corners: Vec<Vec2<f32>> = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)]
+            .iter()
+            .map(|&(x, y)| Vec2::new(x * cos - y * sin, x * sin + y * cos))
+            .collect();
+
+        if now - last_print >= 0.5 {
+            last_print = now;
+            println!("{:.2}s: {:?}", now - start, corners);
+        }
+
+        now - start < 5.0
+    });
+}
@@ -0,0 +1,108 @@
+extern crate embla;
+
+use embla::assets::Image;
+use embla::math::Vec2;
+use embla::rendering::{
+    BufferUsage, TextureFiltering, TextureWrap, Uniform, Vertex, VertexAttributeType,
+};
+use embla::window::WindowSettings;
+
+const VERTEX_SHADER: &'static str = include_str!("assets/vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/fragment_shader.glsl");
+
+// the same texture twice, with its own linear filtering on the left and through a
+// nearest sampler on the right
+const HALF_SIZE: u32 = 320;
+const TEXTURE_SIZE: u32 = 8;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Samplers Example".to_string())
+                    .size(Vec2::new(HALF_SIZE * 2, HALF_SIZE))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, vertex_buffer, texture, nearest) = {
+            let renderer = window.renderer();
+            let half = HALF_SIZE as f32;
+
+            let mut program = renderer
+                .create_program::<TexturedVertex>(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            program.set_uniform("screen_size", Uniform::Vec2((half * 2.0, half)));
+            program.set_uniform("texture_size", Uniform::Vec2((half, half)));
+
+            let image = Image::checkerboard(
+                TEXTURE_SIZE,
+                TEXTURE_SIZE,
+                1,
+                [255, 255, 255, 255],
+                [40, 40, 40, 255],
+            );
+            let texture = renderer
+                .create_texture_from_image(
+                    &image,
+                    Some(TextureFiltering::Linear),
+                    Some(TextureWrap::ClampToEdge),
+                )
+                .unwrap();
+            let nearest = renderer
+                .create_sampler(TextureFiltering::Nearest, TextureWrap::ClampToEdge)
+                .unwrap();
+
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+            (program, vertex_buffer, texture, nearest)
+        };
+
+        let half = |x: f32| {
+            let size = HALF_SIZE as f32;
+            let vertex = |px: f32, py: f32| TexturedVertex {
+                position: (x + px, py),
+                tex_coord: (px, py),
+            };
+            vec![
+                vertex(0.0, 0.0),
+                vertex(size, 0.0),
+                vertex(0.0, size),
+                vertex(size, 0.0),
+                vertex(size, size),
+                vertex(0.0, size),
+            ]
+        };
+        let (left, right) = (half(0.0), half(HALF_SIZE as f32));
+
+        move |_dt, _input| {
+            let renderer = window.renderer();
+            renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+
+            program.set_uniform("texture", Uniform::Texture(texture.clone()));
+            renderer.render_vertices(&vertex_buffer, &program, &left)?;
+            program.set_uniform(
+                "texture",
+                Uniform::SampledTexture(texture.clone(), nearest.clone()),
+            );
+            renderer.render_vertices(&vertex_buffer, &program, &right)?;
+
+            Ok(())
+        }
+    });
+}
+
+#[repr(C)]
+pub struct TexturedVertex {
+    pub position: (f32, f32),
+    pub tex_coord: (f32, f32),
+}
+
+impl Vertex for TexturedVertex {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![
+            ("position".into(), 2, VertexAttributeType::Float),
+            ("tex_coord".into(), 2, VertexAttributeType::Float),
+        ]
+    }
+}
@@ -0,0 +1,34 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Scrolling Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut zoom = 1.0f32;
+
+        move |_dt, input| {
+            let scroll = input.mouse_wheel();
+            if scroll.y != 0.0 {
+                // every 100 pixels scrolled up doubles the zoom
+                zoom = (zoom * (-scroll.y / 100.0).exp2()).max(0.125).min(8.0);
+                println!("zoom: {:.3}", zoom);
+            }
+
+            let brightness = zoom.log2() / 6.0 + 0.5;
+            let renderer = window.renderer();
+            renderer.clear(Some((brightness, brightness, brightness, 1.0)));
+
+            Ok(())
+        }
+    });
+}
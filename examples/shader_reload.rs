@@ -0,0 +1,84 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::rendering::{BufferUsage, DebugSeverity, Uniform, Vertex, VertexAttributeType};
+use embla::time::Clock;
+use embla::window::WindowSettings;
+
+// shader files are only watched natively, there is no file system on the web
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    println!("shader reloading is not supported on the web");
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Shader Reload Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string())
+                    .gl_debug(true),
+            )
+            .unwrap();
+
+        let assets = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/assets");
+        let (mut program, vertex_buffer) = {
+            let renderer = window.renderer();
+            // driver warnings help while editing the shaders, not every driver has them
+            if let Err(e) = renderer.enable_debug_output(DebugSeverity::Low) {
+                println!("{}", e);
+            }
+            let program = renderer
+                .create_program_from_files::<Position, _>(
+                    format!("{}/reload_vertex_shader.glsl", assets),
+                    format!("{}/reload_fragment_shader.glsl", assets),
+                )
+                .unwrap();
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+            (program, vertex_buffer)
+        };
+
+        // a single triangle covering the whole window
+        let vertices = vec![
+            Position {
+                position: (-1.0, -1.0),
+            },
+            Position {
+                position: (3.0, -1.0),
+            },
+            Position {
+                position: (-1.0, 3.0),
+            },
+        ];
+        let clock = Clock::new();
+
+        move |_dt, _input| {
+            let renderer = window.renderer();
+            match renderer.reload_program(&mut program) {
+                Ok(true) => println!("reloaded shaders"),
+                Ok(false) => {}
+                Err(e) => println!("{}", e),
+            }
+
+            program.set_uniform("time", Uniform::Float(clock.now() as f32));
+            renderer.clear(None);
+            renderer.render_vertices(&vertex_buffer, &program, &vertices)?;
+
+            Ok(())
+        }
+    });
+}
+
+#[repr(C)]
+pub struct Position {
+    pub position: (f32, f32),
+}
+
+impl Vertex for Position {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("position".into(), 2, VertexAttributeType::Float)]
+    }
+}
@@ -0,0 +1,64 @@
+extern crate embla;
+
+use embla::audio::Sound;
+use embla::input::{Key, MouseButton};
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+const CLICK: &'static [u8] = include_bytes!("assets/click.wav");
+const MUSIC: &'static [u8] = include_bytes!("assets/loop.wav");
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Sound Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let click = Sound::from_wav_bytes(CLICK).unwrap();
+        let music = Sound::from_wav_bytes(MUSIC).unwrap();
+
+        let mixer = context.mixer(2);
+        let mut music_volume = 0.5;
+        let mut playing_music = None;
+
+        move |_dt, input| {
+            // clicking plays a short sound, louder towards the right of the window
+            if input.mouse_button_is_pressed(&MouseButton::Left) {
+                let volume = 0.2 + input.mouse_position().x as f32 / 640.0;
+                mixer.play(&click, volume, false);
+            }
+
+            // space toggles the looping music, up and down change its volume
+            if input.key_is_pressed(&Key::Space) {
+                playing_music = match playing_music.take() {
+                    Some(playback) => {
+                        mixer.stop(playback);
+                        None
+                    }
+                    None => Some(mixer.play(&music, music_volume, true)),
+                };
+            }
+            if input.key_is_pressed(&Key::Up) || input.key_is_pressed(&Key::Down) {
+                let change = if input.key_is_pressed(&Key::Up) {
+                    0.1
+                } else {
+                    -0.1
+                };
+                music_volume = (music_volume + change).max(0.0).min(1.0);
+                if let Some(playback) = playing_music {
+                    mixer.set_volume(playback, music_volume);
+                }
+            }
+
+            let brightness = if playing_music.is_some() { 0.3 } else { 0.1 };
+            window.renderer().clear(Some((0.1, 0.1, brightness, 1.0)));
+
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,129 @@
+extern crate embla;
+
+use embla::input::{Input, Key};
+use embla::math::Vec2;
+use embla::rendering::{BufferUsage, Uniform, Vertex, VertexAttributeType, Viewport};
+use embla::window::WindowSettings;
+
+const QUAD_VERTEX_SHADER: &'static str = include_str!("assets/quad_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/color_fragment_shader.glsl");
+
+const SPEED: f32 = 200.0;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Split Screen Example".to_string())
+                    .size(Vec2::new(800, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, vertex_buffer) = {
+            let renderer = window.renderer();
+            let program = renderer
+                .create_program::<Corner>(QUAD_VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Stream).unwrap();
+            (program, vertex_buffer)
+        };
+
+        // a checkerboard floor so that the cameras can be seen moving
+        let mut floor = Vec::new();
+        for i in 0..20 {
+            for j in 0..20 {
+                if (i + j) % 2 == 0 {
+                    floor.extend(rectangle(i as f32 * 64.0, j as f32 * 64.0, 64.0, 64.0));
+                }
+            }
+        }
+        // player 1 moves with WASD, player 2 with the arrow keys
+        let mut players = [(300.0f32, 300.0f32), (900.0f32, 900.0f32)];
+        let controls = [
+            [Key::W, Key::A, Key::S, Key::D],
+            [Key::Up, Key::Left, Key::Down, Key::Right],
+        ];
+        let colors = [(0.9, 0.3, 0.2, 1.0), (0.2, 0.5, 0.9, 1.0)];
+
+        move |dt, input| {
+            for (player, keys) in players.iter_mut().zip(controls.iter()) {
+                let (dx, dy) = direction(input, keys);
+                player.0 += dx * SPEED * dt as f32;
+                player.1 += dy * SPEED * dt as f32;
+            }
+
+            let renderer = window.renderer();
+            renderer.clear_all(Some((0.0, 0.0, 0.0, 1.0)));
+
+            let (width, height) = renderer.screen_size();
+            let screen = Viewport::new(0, 0, width as u32, height as u32);
+            // leave a gap between the views so that the border is visible
+            for (i, view) in screen.split_columns(2).into_iter().enumerate() {
+                let view = Viewport::new(view.x + 2, view.y, view.width - 4, view.height);
+                renderer.render_view(view, |renderer| {
+                    renderer.clear_all(Some((0.15, 0.15, 0.15, 1.0)));
+
+                    // each camera is centered on its own player
+                    let (width, height) = renderer.screen_size();
+                    let (x, y) = players[i];
+                    let camera = (width as f32 / 2.0 - x, height as f32 / 2.0 - y);
+                    program
+                        .set_uniform("screen_size", Uniform::Vec2((width as f32, height as f32)));
+                    program.set_uniform("offset", Uniform::Vec2(camera));
+
+                    program.set_uniform("color", Uniform::Vec4((0.3, 0.3, 0.3, 1.0)));
+                    renderer.render_vertices(&vertex_buffer, &program, &floor)?;
+                    for (&(x, y), &color) in players.iter().zip(colors.iter()) {
+                        program.set_uniform("color", Uniform::Vec4(color));
+                        let square = rectangle(x - 16.0, y - 16.0, 32.0, 32.0);
+                        renderer.render_vertices(&vertex_buffer, &program, &square)?;
+                    }
+                    Ok(())
+                })?;
+            }
+
+            Ok(())
+        }
+    });
+}
+
+fn direction(input: &Input, keys: &[Key; 4]) -> (f32, f32) {
+    let axis = |negative: &Key, positive: &Key| {
+        let mut value = 0.0;
+        if input.key_is_down(negative) {
+            value -= 1.0;
+        }
+        if input.key_is_down(positive) {
+            value += 1.0;
+        }
+        value
+    };
+    (axis(&keys[1], &keys[3]), axis(&keys[2], &keys[0]))
+}
+
+fn rectangle(x: f32, y: f32, width: f32, height: f32) -> Vec<Corner> {
+    let corner = |dx: f32, dy: f32| Corner {
+        corner: (x + dx * width, y + dy * height),
+    };
+    vec![
+        corner(0.0, 0.0),
+        corner(1.0, 0.0),
+        corner(0.0, 1.0),
+        corner(1.0, 0.0),
+        corner(1.0, 1.0),
+        corner(0.0, 1.0),
+    ]
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
@@ -0,0 +1,54 @@
+extern crate embla;
+
+use embla::assets::image_from_png;
+use embla::graphics::{default_sprite_program, quad};
+use embla::math::{Rect, Vec2};
+use embla::rendering::{BufferUsage, TextureFiltering, Uniform};
+use embla::window::WindowSettings;
+
+const EMBLA_LOGO: &'static [u8] = include_bytes!("assets/embla.png");
+
+// a single sprite with the built-in program and vertex type, no shaders needed
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Sprite Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (program, vertex_buffer, size) = {
+            let renderer = window.renderer();
+            let mut program = default_sprite_program(&renderer).unwrap();
+
+            let image = image_from_png(EMBLA_LOGO).unwrap();
+            let size = Vec2::new(image.width as f32, image.height as f32);
+            let texture = renderer
+                .create_texture_from_image(&image, Some(TextureFiltering::Linear), None)
+                .unwrap();
+            program.set_uniform("texture", Uniform::Texture(texture));
+
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+            (program, vertex_buffer, size)
+        };
+
+        let center = Vec2::new(320.0, 240.0);
+        let sprite = quad(
+            Rect::new(center - size / 2.0, center + size / 2.0),
+            // images start with the top row, while y points up on screen
+            Rect::new(Vec2::new(0.0, 1.0), Vec2::new(1.0, 0.0)),
+            None,
+        );
+
+        move |_dt, _input| {
+            let renderer = window.renderer();
+            renderer.clear(Some((0.1, 0.1, 0.1, 1.0)));
+            renderer.render_vertices(&vertex_buffer, &program, &sprite)?;
+
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,107 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::rendering::{BufferUsage, StencilFunc, StencilOp, Uniform, Vertex, VertexAttributeType};
+use embla::window::WindowSettings;
+
+const QUAD_VERTEX_SHADER: &'static str = include_str!("assets/quad_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/color_fragment_shader.glsl");
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Stencil Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, vertex_buffer) = {
+            let renderer = window.renderer();
+            let mut program = renderer
+                .create_program::<Corner>(QUAD_VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            program.set_uniform("screen_size", Uniform::Vec2((640.0, 480.0)));
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Stream).unwrap();
+            (program, vertex_buffer)
+        };
+
+        // vertical stripes that only show up through the mask
+        let mut stripes = Vec::new();
+        for i in 0..20 {
+            let x = i as f32 * 32.0;
+            stripes.extend(rectangle(x, 0.0, 16.0, 480.0));
+        }
+        let mut angle = 0.0f32;
+
+        move |dt, _input| {
+            angle += dt as f32;
+            let renderer = window.renderer();
+            renderer.clear_all(Some((0.1, 0.1, 0.1, 1.0)));
+
+            // write 1 wherever the rotating mask covers, without touching the colors
+            let mask = rotated_square(angle, 150.0);
+            renderer.set_stencil_test(true);
+            renderer.set_color_write(false);
+            renderer.set_stencil_func(StencilFunc::Always, 1, 0xFF);
+            renderer.set_stencil_op(StencilOp::Keep, StencilOp::Keep, StencilOp::Replace);
+            program.set_uniform("offset", Uniform::Vec2((320.0, 240.0)));
+            renderer.render_vertices(&vertex_buffer, &program, &mask)?;
+
+            // then draw the stripes only where the mask was drawn
+            renderer.set_color_write(true);
+            renderer.set_stencil_func(StencilFunc::Equal, 1, 0xFF);
+            renderer.set_stencil_op(StencilOp::Keep, StencilOp::Keep, StencilOp::Keep);
+            program.set_uniform("offset", Uniform::Vec2((0.0, 0.0)));
+            program.set_uniform("color", Uniform::Vec4((0.9, 0.7, 0.2, 1.0)));
+            renderer.render_vertices(&vertex_buffer, &program, &stripes)?;
+            renderer.set_stencil_test(false);
+
+            Ok(())
+        }
+    });
+}
+
+fn rectangle(x: f32, y: f32, width: f32, height: f32) -> Vec<Corner> {
+    let corner = |dx: f32, dy: f32| Corner {
+        corner: (x + dx * width, y + dy * height),
+    };
+    vec![
+        corner(0.0, 0.0),
+        corner(1.0, 0.0),
+        corner(0.0, 1.0),
+        corner(1.0, 0.0),
+        corner(1.0, 1.0),
+        corner(0.0, 1.0),
+    ]
+}
+
+fn rotated_square(angle: f32, radius: f32) -> Vec<Corner> {
+    let corner = |i: usize| {
+        let a = angle + i as f32 * std::f32::consts::PI / 2.0;
+        Corner {
+            corner: (a.cos() * radius, a.sin() * radius),
+        }
+    };
+    vec![
+        corner(0),
+        corner(1),
+        corner(2),
+        corner(0),
+        corner(2),
+        corner(3),
+    ]
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
@@ -0,0 +1,40 @@
+extern crate embla;
+
+use embla::input::Key;
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Text Input Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut buffer = String::new();
+
+        move |_dt, input| {
+            // text input and key codes arrive side by side, keys handle editing and
+            // holding backspace keeps deleting
+            let mut changed = false;
+            if input.key_is_repeated(&Key::Backspace) {
+                changed = buffer.pop().is_some();
+            }
+            if !input.text_input().is_empty() {
+                buffer.push_str(input.text_input());
+                changed = true;
+            }
+            if changed {
+                println!("{}", buffer);
+            }
+
+            window.renderer().clear(Some((0.0, 0.0, 0.0, 1.0)));
+
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,87 @@
+extern crate embla;
+
+use embla::assets::Image;
+use embla::graphics::{SpriteBatch, SpriteVertex};
+use embla::math::{Rect, Vec2};
+use embla::rendering::{TextureFiltering, Uniform};
+use embla::window::WindowSettings;
+
+const VERTEX_SHADER: &'static str = include_str!("assets/sprite_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/sprite_fragment_shader.glsl");
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Touch Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, texture, mut batch, screen_size) = {
+            let renderer = window.renderer();
+            let mut program = renderer
+                .create_program::<SpriteVertex>(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            let (width, height) = renderer.screen_size();
+            let screen_size = Vec2::new(width as f32, height as f32);
+            program.set_uniform("screen_size", Uniform::Vec2((screen_size.x, screen_size.y)));
+
+            let white = Image {
+                data: vec![255; 4],
+                width: 1,
+                height: 1,
+            };
+            let texture = renderer
+                .create_texture_from_image(&white, Some(TextureFiltering::Nearest), None)
+                .unwrap();
+            let batch = SpriteBatch::new(&renderer, 16).unwrap();
+
+            (program, texture, batch, screen_size)
+        };
+
+        // the right quarter of the screen acts as a button
+        let button = Rect::new(
+            Vec2::new(screen_size.x * 0.75, 0.0),
+            Vec2::new(screen_size.x, screen_size.y),
+        );
+        let mut position = screen_size / 2.0;
+
+        move |_dt, input| {
+            let mut button_pressed = false;
+            for (_, touch) in input.touches() {
+                // touches have a top left origin, the shader expects bottom left
+                let touch = Vec2::new(touch.x as f32, screen_size.y - touch.y as f32);
+                if touch.x >= button.min.x {
+                    button_pressed = true;
+                } else {
+                    position = touch;
+                }
+            }
+
+            let renderer = window.renderer();
+            renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+
+            let full = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 1.0));
+            let sprite_color = if button_pressed {
+                (1.0, 0.4, 0.4, 1.0)
+            } else {
+                (1.0, 1.0, 1.0, 1.0)
+            };
+            let mut pass = batch.begin(&renderer, &mut program);
+            pass.draw(&texture, button.clone(), full.clone(), (0.2, 0.2, 0.2, 1.0))?;
+            pass.draw(
+                &texture,
+                Rect::new(
+                    position - Vec2::new(32.0, 32.0),
+                    position + Vec2::new(32.0, 32.0),
+                ),
+                full,
+                sprite_color,
+            )?;
+            pass.end()
+        }
+    });
+}
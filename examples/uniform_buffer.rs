@@ -0,0 +1,115 @@
+extern crate embla;
+
+use embla::camera::Camera2D;
+use embla::math::Vec2;
+use embla::rendering::{BufferUsage, Uniform, Vertex, VertexAttributeType};
+use embla::time::Clock;
+use embla::window::WindowSettings;
+
+const VERTEX_SHADER: &'static str = include_str!("assets/camera_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/camera_fragment_shader.glsl");
+const STRIPES_FRAGMENT_SHADER: &'static str =
+    include_str!("assets/camera_stripes_fragment_shader.glsl");
+
+const CAMERA_BINDING: u32 = 0;
+const QUAD_SIZE: f32 = 40.0;
+
+// matches the std140 layout of the Camera block in the vertex shader
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct CameraBlock {
+    view_projection: [f32; 16],
+}
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Uniform Buffer Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let (mut program, mut stripes_program, camera_buffer, vertex_buffer) = {
+            let renderer = window.renderer();
+            let mut program = renderer
+                .create_program::<Corner>(VERTEX_SHADER, FRAGMENT_SHADER)
+                .unwrap();
+            program
+                .set_uniform_block_binding("Camera", CAMERA_BINDING)
+                .unwrap();
+            program.set_uniform("color", Uniform::Vec4((0.2, 0.6, 0.9, 1.0)));
+            let mut stripes_program = renderer
+                .create_program::<Corner>(VERTEX_SHADER, STRIPES_FRAGMENT_SHADER)
+                .unwrap();
+            stripes_program
+                .set_uniform_block_binding("Camera", CAMERA_BINDING)
+                .unwrap();
+            stripes_program.set_uniform("color", Uniform::Vec4((0.9, 0.5, 0.2, 1.0)));
+
+            let camera_buffer = renderer.create_uniform_buffer(BufferUsage::Stream).unwrap();
+            let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+            (program, stripes_program, camera_buffer, vertex_buffer)
+        };
+
+        let corner = |x: f32, y: f32| Corner {
+            corner: (x * QUAD_SIZE, y * QUAD_SIZE),
+        };
+        let quad = vec![
+            corner(-0.5, -0.5),
+            corner(0.5, -0.5),
+            corner(-0.5, 0.5),
+            corner(0.5, -0.5),
+            corner(0.5, 0.5),
+            corner(-0.5, 0.5),
+        ];
+        let clock = Clock::new();
+
+        move |_dt, _input| {
+            let time = clock.now() as f32;
+            let camera = Camera2D {
+                position: Vec2::new(time.cos() * 50.0, time.sin() * 50.0),
+                zoom: 1.5 + (time * 0.5).sin() * 0.5,
+                rotation: time * 0.2,
+            };
+
+            let renderer = window.renderer();
+            let (width, height) = renderer.screen_size();
+            // one upload for both programs
+            camera_buffer.set_data(&CameraBlock {
+                view_projection: camera.ortho_matrix(width as f32, height as f32),
+            });
+            camera_buffer.bind(CAMERA_BINDING)?;
+
+            renderer.clear(Some((0.1, 0.1, 0.1, 1.0)));
+            for y in -3..4 {
+                for x in -4..5 {
+                    let offset =
+                        Uniform::Vec2((x as f32 * QUAD_SIZE * 2.0, y as f32 * QUAD_SIZE * 2.0));
+                    if (x + y) % 2 == 0 {
+                        program.set_uniform("offset", offset);
+                        renderer.render_vertices(&vertex_buffer, &program, &quad)?;
+                    } else {
+                        stripes_program.set_uniform("offset", offset);
+                        renderer.render_vertices(&vertex_buffer, &stripes_program, &quad)?;
+                    }
+                }
+            }
+
+            Ok(())
+        }
+    });
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
@@ -0,0 +1,34 @@
+extern crate embla;
+
+use embla::math::Vec2;
+use embla::window::WindowSettings;
+
+fn main() {
+    embla::init(|mut context| {
+        let window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Window Title Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+
+        let mut frames = 0;
+        let mut elapsed = 0.0;
+
+        move |dt, _input| {
+            frames += 1;
+            elapsed += dt;
+            if elapsed >= 1.0 {
+                window.set_title(&format!("FPS: {:.1}", frames as f64 / elapsed))?;
+                frames = 0;
+                elapsed = 0.0;
+            }
+
+            window.renderer().clear(Some((0.1, 0.1, 0.1, 1.0)));
+
+            Ok(())
+        }
+    });
+}
@@ -0,0 +1,108 @@
+extern crate embla;
+
+use embla::input::MouseButton;
+use embla::math::Vec2;
+use embla::rendering::{BufferUsage, Program, Uniform, Vertex, VertexAttributeType, VertexBuffer};
+use embla::window::{Window, WindowSettings};
+
+const QUAD_VERTEX_SHADER: &'static str = include_str!("assets/quad_vertex_shader.glsl");
+const FRAGMENT_SHADER: &'static str = include_str!("assets/color_fragment_shader.glsl");
+
+const COLORS: [(f32, f32, f32, f32); 4] = [
+    (0.9, 0.3, 0.3, 1.0),
+    (0.3, 0.9, 0.3, 1.0),
+    (0.3, 0.3, 0.9, 1.0),
+    (0.9, 0.9, 0.3, 1.0),
+];
+
+// every window has its own GL context, programs and buffers only work with the
+// renderer of the window they were created with
+fn quad_program(window: &Window, size: (f32, f32)) -> (Program<Corner>, VertexBuffer) {
+    let renderer = window.renderer();
+    let mut program = renderer
+        .create_program::<Corner>(QUAD_VERTEX_SHADER, FRAGMENT_SHADER)
+        .unwrap();
+    program.set_uniform("screen_size", Uniform::Vec2(size));
+    let vertex_buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+    (program, vertex_buffer)
+}
+
+fn quad(size: f32) -> Vec<Corner> {
+    let corner = |x: f32, y: f32| Corner {
+        corner: (x * size, y * size),
+    };
+    vec![
+        corner(0.0, 0.0),
+        corner(1.0, 0.0),
+        corner(0.0, 1.0),
+        corner(1.0, 0.0),
+        corner(1.0, 1.0),
+        corner(0.0, 1.0),
+    ]
+}
+
+// the main window shows a square moving around, the preview shows its color up
+// close. Clicking anywhere picks the next color.
+fn main() {
+    embla::init(|mut context| {
+        let main_window = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Windows Example".to_string())
+                    .size(Vec2::new(640, 480))
+                    .canvas_id("window".to_string()),
+            )
+            .unwrap();
+        let preview = context
+            .window(
+                WindowSettings::new()
+                    .title("Embla Windows Example Preview".to_string())
+                    .size(Vec2::new(240, 240))
+                    .canvas_id("preview".to_string()),
+            )
+            .unwrap();
+
+        let (mut main_program, main_buffer) = quad_program(&main_window, (640.0, 480.0));
+        let (mut preview_program, preview_buffer) = quad_program(&preview, (240.0, 240.0));
+        let (square, swatch) = (quad(64.0), quad(160.0));
+        preview_program.set_uniform("offset", Uniform::Vec2((40.0, 40.0)));
+
+        let mut time = 0.0;
+        let mut color = 0;
+
+        move |dt, input| {
+            time += dt;
+            if input.mouse_button_is_pressed(&MouseButton::Left) {
+                color = (color + 1) % COLORS.len();
+            }
+
+            let offset = (
+                288.0 + (time * 1.3).cos() as f32 * 200.0,
+                208.0 + (time * 2.1).sin() as f32 * 150.0,
+            );
+            main_program.set_uniform("offset", Uniform::Vec2(offset));
+            main_program.set_uniform("color", Uniform::Vec4(COLORS[color]));
+            let renderer = main_window.renderer();
+            renderer.clear(Some((0.1, 0.1, 0.1, 1.0)));
+            renderer.render_vertices(&main_buffer, &main_program, &square)?;
+
+            preview_program.set_uniform("color", Uniform::Vec4(COLORS[color]));
+            let renderer = preview.renderer();
+            renderer.clear(Some((0.3, 0.3, 0.3, 1.0)));
+            renderer.render_vertices(&preview_buffer, &preview_program, &swatch)?;
+
+            Ok(())
+        }
+    });
+}
+
+#[repr(C)]
+pub struct Corner {
+    pub corner: (f32, f32),
+}
+
+impl Vertex for Corner {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("corner".into(), 2, VertexAttributeType::Float)]
+    }
+}
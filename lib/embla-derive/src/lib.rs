@@ -0,0 +1,163 @@
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Data, DeriveInput, Error, Fields, Ident, LitInt, LitStr, Token};
+
+/// Implements `embla::rendering::Vertex` for a `#[repr(C)]` struct from
+/// `#[attribute(name = "position", ty = Float)]` annotations on its fields.
+///
+/// The component count defaults to the size of the field divided by the size of `ty`
+/// and can be given with `count = 2`. Every field needs an annotation and the fields
+/// have to be tightly packed, which is checked when the attributes are requested.
+#[proc_macro_derive(Vertex, attributes(attribute))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+struct Attribute {
+    name: LitStr,
+    ty: Ident,
+    count: Option<LitInt>,
+}
+
+enum Arg {
+    Name(LitStr),
+    Ty(Ident),
+    Count(LitInt),
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Arg> {
+        let key: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        if key == "name" {
+            Ok(Arg::Name(input.parse()?))
+        } else if key == "ty" {
+            Ok(Arg::Ty(input.parse()?))
+        } else if key == "count" {
+            Ok(Arg::Count(input.parse()?))
+        } else {
+            Err(Error::new(key.span(), "expected `name`, `ty` or `count`"))
+        }
+    }
+}
+
+fn parse_attribute(attr: &syn::Attribute) -> syn::Result<Attribute> {
+    let args = attr.parse_args_with(Punctuated::<Arg, Token![,]>::parse_terminated)?;
+    let (mut name, mut ty, mut count) = (None, None, None);
+    for arg in args {
+        match arg {
+            Arg::Name(v) => name = Some(v),
+            Arg::Ty(v) => ty = Some(v),
+            Arg::Count(v) => count = Some(v),
+        }
+    }
+    Ok(Attribute {
+        name: name.ok_or_else(|| Error::new_spanned(attr, "missing `name = \"...\"`"))?,
+        ty: ty.ok_or_else(|| Error::new_spanned(attr, "missing `ty = ...`"))?,
+        count,
+    })
+}
+
+fn is_repr_c(input: &DeriveInput) -> bool {
+    input
+        .attrs
+        .iter()
+        .filter(|a| a.path.is_ident("repr"))
+        .any(|a| {
+            a.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)
+                .map(|reprs| reprs.iter().any(|r| r == "C"))
+                .unwrap_or(false)
+        })
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match input.data {
+        Data::Struct(ref s) => match s.fields {
+            Fields::Named(ref fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    input,
+                    "Vertex can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "Vertex can only be derived for structs",
+            ))
+        }
+    };
+    // without it the compiler is free to reorder the fields
+    if !is_repr_c(input) {
+        return Err(Error::new_spanned(
+            input,
+            "deriving Vertex requires #[repr(C)]",
+        ));
+    }
+
+    let mut attributes = Vec::new();
+    for field in fields {
+        let attr = field
+            .attrs
+            .iter()
+            .find(|a| a.path.is_ident("attribute"))
+            .ok_or_else(|| Error::new_spanned(field, "vertex fields need an #[attribute(...)]"))?;
+        let Attribute { name, ty, count } = parse_attribute(attr)?;
+        let field_name = field.ident.as_ref().unwrap();
+        let field_ty = &field.ty;
+        let count = match count {
+            Some(count) => quote!(#count),
+            None => quote! {
+                ::std::mem::size_of::<#field_ty>()
+                    / ::embla::rendering::VertexAttributeType::#ty.size()
+            },
+        };
+        attributes.push(quote! {
+            {
+                let ty = ::embla::rendering::VertexAttributeType::#ty;
+                let count: usize = #count;
+                let vertex = ::std::mem::MaybeUninit::<Self>::uninit();
+                let base = vertex.as_ptr();
+                let field = unsafe { ::std::ptr::addr_of!((*base).#field_name) };
+                let field_offset = field as usize - base as usize;
+                assert_eq!(
+                    field_offset,
+                    offset,
+                    "vertex attribute `{}` is not tightly packed after the previous one",
+                    #name
+                );
+                offset += count * ty.size();
+                (#name.to_string(), count, ty)
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::embla::rendering::Vertex for #ident #ty_generics #where_clause {
+            fn stride() -> usize {
+                ::std::mem::size_of::<Self>()
+            }
+
+            #[allow(unused_assignments)]
+            fn attributes() -> Vec<(String, usize, ::embla::rendering::VertexAttributeType)> {
+                let mut offset = 0;
+                vec![#(#attributes),*]
+            }
+        }
+    })
+}
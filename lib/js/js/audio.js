@@ -3,6 +3,15 @@ window.create_audio_output = function (channels, callback) {
     var scriptNode = audioCtx.createScriptProcessor(4096, 0, channels);
 
     scriptNode.connect(audioCtx.destination);
+    // browsers keep audio suspended until the page has been interacted with
+    var resume = function () {
+        if (audioCtx.state === "suspended") {
+            audioCtx.resume();
+        }
+    };
+    document.addEventListener("mousedown", resume);
+    document.addEventListener("keydown", resume);
+    document.addEventListener("touchstart", resume);
     scriptNode.onaudioprocess = function (e) {
         for (var channel = 0; channel < e.outputBuffer.numberOfChannels; channel++) {
             callback.call(channel, audioCtx.sampleRate, e.outputBuffer.getChannelData(channel));
@@ -10,10 +19,16 @@ window.create_audio_output = function (channels, callback) {
     }
 
     return {
+        context: audioCtx,
+        resume: resume,
         callback: callback
     }
 }
 
 window.destroy_audio_output = function (device) {
+    document.removeEventListener("mousedown", device.resume);
+    document.removeEventListener("keydown", device.resume);
+    document.removeEventListener("touchstart", device.resume);
+    device.context.close();
     device.callback.free();
 }
\ No newline at end of file
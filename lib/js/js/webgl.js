@@ -1,24 +1,120 @@
 var gl_global;
+// state last set on the current context, redundant calls are skipped
+var gl_bound;
 
 window.gl_set_current_context = function (context) {
+    if (!context.embla_bound) {
+        context.embla_bound = { program: undefined, active_texture: undefined, blend_func: [], blend_equation: undefined, enabled: {}, samplers: [], screen_viewport: undefined, limits: {} };
+    }
     gl_global = context;
+    gl_bound = context.embla_bound;
+}
+
+window.gl_forget_bound_state = function (context) {
+    context.embla_bound = undefined;
+    if (gl_global === context) {
+        gl_set_current_context(context);
+    }
+}
+
+window.gl_reset_bound_state = function () {
+    gl_global.embla_bound = undefined;
+    gl_set_current_context(gl_global);
 }
 
+window.gl_get_error = function () {
+    return gl_global.getError();
+}
+window.gl_get_parameter_i = function (pname) {
+    return gl_global.getParameter(pname);
+}
+window.gl_get_limit = function (pname) {
+    if (gl_bound.limits[pname] === undefined) {
+        gl_bound.limits[pname] = gl_global.getParameter(pname);
+    }
+    return gl_bound.limits[pname];
+}
+window.gl_get_parameter_f = function (pname) {
+    return gl_global.getParameter(pname);
+}
+window.gl_get_parameter_fv = function (pname, values) {
+    values.set(gl_global.getParameter(pname));
+}
+window.gl_get_parameter_iv = function (pname, values) {
+    values.set(gl_global.getParameter(pname));
+}
+window.gl_get_parameter_string = function (pname) {
+    return gl_global.getParameter(pname);
+}
+window.gl_get_parameter_b = function (pname) {
+    return !!gl_global.getParameter(pname);
+}
+window.gl_get_supported_extensions = function () {
+    return (gl_global.getSupportedExtensions() || []).join(" ");
+}
+window.gl_get_extension = function (name) {
+    return gl_global.getExtension(name) != null;
+}
+window.gl_is_enabled = function (capability) {
+    return gl_global.isEnabled(capability);
+}
 window.gl_enable = function (capability) {
-    gl_global.enable(capability);
+    if (gl_bound.enabled[capability] !== true) {
+        gl_global.enable(capability);
+        gl_bound.enabled[capability] = true;
+    }
+}
+window.gl_disable = function (capability) {
+    if (gl_bound.enabled[capability] !== false) {
+        gl_global.disable(capability);
+        gl_bound.enabled[capability] = false;
+    }
 }
 window.gl_blend_func = function (sfactor, dfactor) {
-    gl_global.blendFunc(sfactor, dfactor);
+    if (gl_bound.blend_func[0] !== sfactor || gl_bound.blend_func[1] !== dfactor) {
+        gl_global.blendFunc(sfactor, dfactor);
+        gl_bound.blend_func = [sfactor, dfactor];
+    }
+}
+window.gl_blend_equation = function (mode) {
+    if (gl_bound.blend_equation !== mode) {
+        gl_global.blendEquation(mode);
+        gl_bound.blend_equation = mode;
+    }
 }
 window.gl_draw_arrays = function (mode, first, count) {
     gl_global.drawArrays(mode, first, count);
 }
+window.gl_draw_arrays_instanced = function (mode, first, count, instance_count) {
+    gl_global.drawArraysInstanced(mode, first, count, instance_count);
+}
+window.gl_draw_elements = function (mode, count, type, offset) {
+    gl_global.drawElements(mode, count, type, offset);
+}
 window.gl_clear_color = function (r, g, b, a) {
     gl_global.clearColor(r, g, b, a);
 }
 window.gl_clear = function (mask) {
     gl_global.clear(mask);
 }
+window.gl_clear_stencil = function (s) {
+    gl_global.clearStencil(s);
+}
+window.gl_clear_depth = function (depth) {
+    gl_global.clearDepth(depth);
+}
+window.gl_color_mask = function (red, green, blue, alpha) {
+    gl_global.colorMask(red, green, blue, alpha);
+}
+window.gl_stencil_func = function (func, reference, mask) {
+    gl_global.stencilFunc(func, reference, mask);
+}
+window.gl_stencil_op = function (fail, zfail, zpass) {
+    gl_global.stencilOp(fail, zfail, zpass);
+}
+window.gl_stencil_mask = function (mask) {
+    gl_global.stencilMask(mask);
+}
 
 window.gl_drawing_buffer_width = function () {
     return gl_global.drawingBufferWidth;
@@ -26,6 +122,95 @@ window.gl_drawing_buffer_width = function () {
 window.gl_drawing_buffer_height = function () {
     return gl_global.drawingBufferHeight;
 }
+window.gl_viewport = function (x, y, width, height) {
+    gl_global.viewport(x, y, width, height);
+}
+window.gl_get_viewport = function (rect) {
+    rect.set(gl_global.getParameter(gl_global.VIEWPORT));
+}
+// kept in the bound state so that each context restores its own viewport
+window.gl_save_screen_viewport = function () {
+    if (!gl_bound.screen_viewport) {
+        gl_bound.screen_viewport = gl_global.getParameter(gl_global.VIEWPORT);
+    }
+}
+window.gl_restore_screen_viewport = function () {
+    var rect = gl_bound.screen_viewport ||
+        [0, 0, gl_global.drawingBufferWidth, gl_global.drawingBufferHeight];
+    gl_bound.screen_viewport = undefined;
+    gl_global.viewport(rect[0], rect[1], rect[2], rect[3]);
+}
+window.gl_line_width = function (width) {
+    gl_global.lineWidth(width);
+}
+window.gl_scissor = function (x, y, width, height) {
+    gl_global.scissor(x, y, width, height);
+}
+window.gl_read_pixels = function (x, y, width, height, format, type, pixels) {
+    gl_global.readPixels(x, y, width, height, format, type, pixels);
+}
+
+window.gl_create_framebuffer = function () {
+    return gl_global.createFramebuffer();
+}
+window.gl_delete_framebuffer = function (framebuffer) {
+    gl_global.deleteFramebuffer(framebuffer);
+}
+window.gl_bind_framebuffer = function (target, framebuffer) {
+    gl_global.bindFramebuffer(target, framebuffer);
+}
+window.gl_get_framebuffer_binding = function () {
+    return gl_global.getParameter(gl_global.FRAMEBUFFER_BINDING);
+}
+window.gl_framebuffer_texture_2d = function (target, attachment, textarget, texture, level) {
+    gl_global.framebufferTexture2D(target, attachment, textarget, texture, level);
+}
+window.gl_framebuffer_renderbuffer = function (target, attachment, renderbuffertarget, renderbuffer) {
+    gl_global.framebufferRenderbuffer(target, attachment, renderbuffertarget, renderbuffer);
+}
+window.gl_draw_buffers = function (buffers) {
+    gl_global.drawBuffers(Array.from(buffers));
+}
+window.gl_create_query = function () {
+    return gl_global.createQuery();
+}
+window.gl_delete_query = function (query) {
+    gl_global.deleteQuery(query);
+}
+window.gl_begin_query = function (target, query) {
+    gl_global.beginQuery(target, query);
+}
+window.gl_end_query = function (target) {
+    gl_global.endQuery(target);
+}
+window.gl_get_query_parameter_b = function (query, pname) {
+    return !!gl_global.getQueryParameter(query, pname);
+}
+window.gl_get_query_parameter_f = function (query, pname) {
+    return gl_global.getQueryParameter(query, pname);
+}
+window.gl_check_framebuffer_status = function (target) {
+    return gl_global.checkFramebufferStatus(target);
+}
+window.gl_blit_framebuffer = function (src_x0, src_y0, src_x1, src_y1, dst_x0, dst_y0, dst_x1, dst_y1, mask, filter) {
+    gl_global.blitFramebuffer(src_x0, src_y0, src_x1, src_y1, dst_x0, dst_y0, dst_x1, dst_y1, mask, filter);
+}
+
+window.gl_create_renderbuffer = function () {
+    return gl_global.createRenderbuffer();
+}
+window.gl_delete_renderbuffer = function (renderbuffer) {
+    gl_global.deleteRenderbuffer(renderbuffer);
+}
+window.gl_bind_renderbuffer = function (target, renderbuffer) {
+    gl_global.bindRenderbuffer(target, renderbuffer);
+}
+window.gl_renderbuffer_storage = function (target, internalformat, width, height) {
+    gl_global.renderbufferStorage(target, internalformat, width, height);
+}
+window.gl_renderbuffer_storage_multisample = function (target, samples, internalformat, width, height) {
+    gl_global.renderbufferStorageMultisample(target, samples, internalformat, width, height);
+}
 
 window.gl_create_texture = function () {
     return gl_global.createTexture();
@@ -36,18 +221,55 @@ window.gl_delete_texture = function (texture) {
 window.gl_bind_texture = function (target, texture) {
     gl_global.bindTexture(target, texture);
 };
+window.gl_create_sampler = function () {
+    return gl_global.createSampler();
+}
+window.gl_delete_sampler = function (sampler) {
+    gl_global.deleteSampler(sampler);
+    // deleting unbinds it from every unit
+    for (var unit = 0; unit < gl_bound.samplers.length; unit++) {
+        if (gl_bound.samplers[unit] === sampler) {
+            gl_bound.samplers[unit] = null;
+        }
+    }
+}
+window.gl_sampler_parameter_i = function (sampler, pname, param) {
+    gl_global.samplerParameteri(sampler, pname, param);
+}
+window.gl_bind_sampler = function (unit, sampler) {
+    if ((gl_bound.samplers[unit] || null) !== sampler) {
+        gl_global.bindSampler(unit, sampler);
+        gl_bound.samplers[unit] = sampler;
+    }
+}
 window.gl_active_texture = function (texture) {
-    gl_global.activeTexture(texture);
+    if (gl_bound.active_texture !== texture) {
+        gl_global.activeTexture(texture);
+        gl_bound.active_texture = texture;
+    }
 }
 window.gl_tex_parameter_i = function (target, pname, param) {
     gl_global.texParameteri(target, pname, param);
 }
+window.gl_tex_parameter_f = function (target, pname, param) {
+    gl_global.texParameterf(target, pname, param);
+}
+window.gl_generate_mipmap = function (target) {
+    gl_global.generateMipmap(target);
+}
+window.gl_pixel_store_i = function (pname, param) {
+    gl_global.pixelStorei(pname, param);
+}
 
 function tex_format_type_size(format) {
     switch (format) {
         case WebGLRenderingContext.ALPHA:
+        case WebGL2RenderingContext.RED:
             return 1;
             break;
+        case WebGL2RenderingContext.RG:
+            return 2;
+            break;
         case WebGLRenderingContext.RGB:
             return 3;
             break;
@@ -89,6 +311,9 @@ window.gl_tex_image_2d_u8 = function (target, level, internalFormat, width, heig
     }
     gl_global.texImage2D(target, level, internalFormat, width, height, border, format, data_type, pixels);
 }
+window.gl_compressed_tex_image_2d = function (target, level, internalFormat, width, height, border, data) {
+    gl_global.compressedTexImage2D(target, level, internalFormat, width, height, border, data);
+}
 window.gl_tex_image_2d_u16 = function (target, level, internalFormat, width, height, border, format, data_type, pixels) {
     if (data_type == WebGLRenderingContext.UNSIGNED_BYTE) {
         throw "invalid data type for Uint16Array data"
@@ -99,11 +324,7 @@ window.gl_tex_sub_image_2d_u8 = function (target, level, xoffset, yoffset, width
     if (data_type != WebGLRenderingContext.UNSIGNED_BYTE) {
         throw "invalid data type for Uint8Array data"
     }
-    if (data_type == WebGLRenderingContext.UNSIGNED_BYTE && format == WebGLRenderingContext.RGBA) {
-        gl_global.texSubImage2D(target, level, xoffset, yoffset, width, height, format, data_type, pixels);
-    } else {
-        throw "Only format of RGBA and data type of UNSIGNED_BYTE supported"
-    }
+    gl_global.texSubImage2D(target, level, xoffset, yoffset, width, height, format, data_type, pixels);
 }
 window.gl_tex_sub_image_2d_u16 = function (target, level, xoffset, yoffset, width, height, format, data_type, pixels) {
     if (data_type == WebGLRenderingContext.UNSIGNED_BYTE) {
@@ -145,15 +366,24 @@ window.gl_create_program = function () {
 }
 window.gl_delete_program = function (program) {
     gl_global.deleteProgram(program);
+    if (gl_bound.program === program) {
+        gl_bound.program = undefined;
+    }
 }
 window.gl_attach_shader = function (program, shader) {
     gl_global.attachShader(program, shader);
 }
+window.gl_detach_shader = function (program, shader) {
+    gl_global.detachShader(program, shader);
+}
 window.gl_link_program = function (program) {
     gl_global.linkProgram(program);
 }
 window.gl_use_program = function (program) {
-    gl_global.useProgram(program);
+    if (gl_bound.program !== program) {
+        gl_global.useProgram(program);
+        gl_bound.program = program;
+    }
 }
 window.gl_get_program_parameter = function (program, pname) {
     return gl_global.getProgramParameter(program, pname);
@@ -178,13 +408,43 @@ window.gl_bind_buffer = function (target, buffer) {
 window.gl_buffer_data = function (target, data, usage) {
     gl_global.bufferData(target, data, usage);
 }
+window.gl_buffer_data_size = function (target, size, usage) {
+    gl_global.bufferData(target, size, usage);
+}
+window.gl_buffer_sub_data = function (target, offset, data) {
+    gl_global.bufferSubData(target, offset, data);
+}
+window.gl_bind_buffer_base = function (target, index, buffer) {
+    gl_global.bindBufferBase(target, index, buffer);
+}
+window.gl_get_uniform_block_index = function (program, name) {
+    return gl_global.getUniformBlockIndex(program, name);
+}
+window.gl_uniform_block_binding = function (program, index, binding) {
+    gl_global.uniformBlockBinding(program, index, binding);
+}
 
 window.gl_get_uniform_location = function (program, name) {
     return gl_global.getUniformLocation(program, name);
 };
+window.gl_uniform1f = function (location, v0) {
+    gl_global.uniform1f(location, v0);
+}
 window.gl_uniform2f = function (location, v0, v1) {
     gl_global.uniform2f(location, v0, v1);
 }
+window.gl_uniform3f = function (location, v0, v1, v2) {
+    gl_global.uniform3f(location, v0, v1, v2);
+}
+window.gl_uniform4f = function (location, v0, v1, v2, v3) {
+    gl_global.uniform4f(location, v0, v1, v2, v3);
+}
+window.gl_uniform_matrix3fv = function (location, transpose, value) {
+    gl_global.uniformMatrix3fv(location, transpose, value);
+}
+window.gl_uniform_matrix4fv = function (location, transpose, value) {
+    gl_global.uniformMatrix4fv(location, transpose, value);
+}
 window.gl_uniform1i = function (location, v0) {
     gl_global.uniform1i(location, v0);
 }
@@ -195,6 +455,12 @@ window.gl_get_attrib_location = function (program, name) {
 window.gl_enable_vertex_attrib_array = function (index) {
     gl_global.enableVertexAttribArray(index);
 }
+window.gl_vertex_attrib_divisor = function (index, divisor) {
+    gl_global.vertexAttribDivisor(index, divisor);
+}
 window.gl_vertex_attrib_pointer = function (index, size, type, normalized, stride, offset) {
     gl_global.vertexAttribPointer(index, size, type, normalized, stride, offset);
+}
+window.gl_vertex_attrib_i_pointer = function (index, size, type, stride, offset) {
+    gl_global.vertexAttribIPointer(index, size, type, stride, offset);
 }
\ No newline at end of file
@@ -1,22 +1,279 @@
+// keyCodes of the named physical keys on a US layout
+const PHYSICAL_KEY_CODES = {
+    "Backspace": 8, "Tab": 9, "Enter": 13, "NumpadEnter": 13,
+    "ShiftLeft": 16, "ShiftRight": 16, "ControlLeft": 17, "ControlRight": 17,
+    "AltLeft": 18, "AltRight": 18, "Pause": 19, "CapsLock": 20, "Escape": 27,
+    "Space": 32, "PageUp": 33, "PageDown": 34, "End": 35, "Home": 36,
+    "ArrowLeft": 37, "ArrowUp": 38, "ArrowRight": 39, "ArrowDown": 40,
+    "Insert": 45, "Delete": 46, "MetaLeft": 91, "MetaRight": 92, "ContextMenu": 93,
+    "NumpadMultiply": 106, "NumpadAdd": 107, "NumpadSubtract": 109,
+    "NumpadDecimal": 110, "NumpadDivide": 111, "NumLock": 144, "ScrollLock": 145,
+    "Semicolon": 186, "Equal": 187, "Comma": 188, "Minus": 189, "Period": 190,
+    "Slash": 191, "Backquote": 192, "BracketLeft": 219, "Backslash": 220,
+    "BracketRight": 221, "Quote": 222,
+};
+
+// translates the physical key of a keyboard event into the keyCode it would have
+// on a US layout, keyCode itself depends on the active layout
+function physical_key_code(event) {
+    var code = event.code;
+    if (!code) {
+        return event.keyCode;
+    }
+    if (/^Key[A-Z]$/.test(code)) {
+        return code.charCodeAt(3);
+    }
+    if (/^Digit[0-9]$/.test(code)) {
+        return code.charCodeAt(5);
+    }
+    if (/^Numpad[0-9]$/.test(code)) {
+        return 96 + (code.charCodeAt(6) - 48);
+    }
+    if (/^F([1-9]|1[0-2])$/.test(code)) {
+        return 111 + parseInt(code.substring(1));
+    }
+    if (code in PHYSICAL_KEY_CODES) {
+        return PHYSICAL_KEY_CODES[code];
+    }
+    return event.keyCode;
+}
+
+// pixels scrolled per wheel line, matches the native backend
+const WHEEL_LINE_HEIGHT = 16.0;
+
+// events of the whole page go to a single window, the last one clicked, or the
+// input of pages with several canvases would get every key once per canvas
+var active_window = null;
+
 class CanvasWindow {
     constructor(canvas_id, input_handler) {
         this.canvas = document.getElementById(canvas_id);
         this.input_handler = input_handler;
+        var self = this;
+        var is_active = function () {
+            return active_window === self;
+        };
+        // events every window gets, whether it is active or not
+        var is_alive = function () {
+            return !self.deleted;
+        };
+        if (active_window === null) {
+            active_window = this;
+        }
+        // with high DPI the canvas is backed by a physical pixel per device pixel
+        // and positions are reported in those pixels as well
+        this.high_dpi = false;
+        var pixel_ratio = function () {
+            return self.high_dpi ? (window.devicePixelRatio || 1) : 1;
+        };
+        var scaled = function (v) {
+            return Math.round(v * pixel_ratio());
+        };
+        // client coordinates to drawing buffer pixels from the top left of the
+        // canvas content, whatever the scroll position, margins, borders, padding
+        // or CSS scaling of the canvas
+        var canvas_position = function (client_x, client_y) {
+            var canvas = self.canvas;
+            var rect = canvas.getBoundingClientRect();
+            var style = window.getComputedStyle(canvas);
+            var left = rect.left + parseFloat(style.borderLeftWidth) + parseFloat(style.paddingLeft);
+            var top = rect.top + parseFloat(style.borderTopWidth) + parseFloat(style.paddingTop);
+            var width = rect.right - parseFloat(style.borderRightWidth) - parseFloat(style.paddingRight) - left;
+            var height = rect.bottom - parseFloat(style.borderBottomWidth) - parseFloat(style.paddingBottom) - top;
+            if (width <= 0 || height <= 0) {
+                return [scaled(client_x - left), scaled(client_y - top)];
+            }
+            return [
+                Math.round((client_x - left) * canvas.width / width),
+                Math.round((client_y - top) * canvas.height / height)
+            ];
+        };
+        var activate = function () {
+            if (active_window === self) {
+                return;
+            }
+            // the key up events now go to this window, release what the previous
+            // one holds so it doesn't stay down there
+            if (active_window !== null) {
+                active_window.input_handler.focus(false);
+            }
+            active_window = self;
+            input_handler.focus(document.hasFocus() && !document.hidden);
+        };
+        this.canvas.addEventListener("mousedown", activate);
+        this.canvas.addEventListener("touchstart", activate);
 
         this.canvas.addEventListener("mousemove", function (event) {
-            input_handler.mouse_move(event.offsetX, event.offsetY);
+            var position = canvas_position(event.clientX, event.clientY);
+            input_handler.mouse_move_buttons(position[0], position[1], event.buttons);
+            input_handler.mouse_delta(scaled(event.movementX), scaled(event.movementY));
+        });
+        // only report changes of this canvas, not of the other ones on the page
+        var lock_canvas = this.canvas;
+        var locked = false;
+        document.addEventListener("pointerlockchange", function () {
+            if ((document.pointerLockElement === lock_canvas) != locked) {
+                locked = !locked;
+                input_handler.pointer_lock_change(locked);
+            }
+        });
+        // the resize observer picks up the new canvas size
+        var fullscreen = false;
+        document.addEventListener("fullscreenchange", function () {
+            if ((document.fullscreenElement === lock_canvas) != fullscreen) {
+                fullscreen = !fullscreen;
+                input_handler.fullscreen_change(fullscreen);
+            }
+        });
+        // without preventDefault the browser never restores the context
+        this.canvas.addEventListener("webglcontextlost", function (event) {
+            event.preventDefault();
+            input_handler.context_lost(true);
+        });
+        this.canvas.addEventListener("webglcontextrestored", function () {
+            // the restored context starts out with the default state again
+            gl_forget_bound_state(self.canvas.getContext('webgl2'));
+            input_handler.context_lost(false);
+        });
+        this.canvas.addEventListener("mouseenter", function () {
+            input_handler.mouse_enter();
+        });
+        this.canvas.addEventListener("mouseleave", function () {
+            input_handler.mouse_leave();
         });
         this.canvas.addEventListener("mousedown", function (event) {
-            input_handler.mouse_down(event.button, event.offsetX, event.offsetY);
+            var position = canvas_position(event.clientX, event.clientY);
+            input_handler.mouse_down_mod(event.button, position[0], position[1],
+                event.shiftKey, event.ctrlKey, event.altKey, event.metaKey);
         });
         this.canvas.addEventListener("mouseup", function (event) {
-            input_handler.mouse_up(event.button, event.offsetX, event.offsetY);
+            var position = canvas_position(event.clientX, event.clientY);
+            input_handler.mouse_up_mod(event.button, position[0], position[1],
+                event.shiftKey, event.ctrlKey, event.altKey, event.metaKey);
+        });
+        var canvas = this.canvas;
+        this.canvas.addEventListener("wheel", function (event) {
+            // report deltas in pixels regardless of the browser's delta mode
+            var scale = 1.0;
+            if (event.deltaMode == WheelEvent.DOM_DELTA_LINE) {
+                scale = WHEEL_LINE_HEIGHT;
+            } else if (event.deltaMode == WheelEvent.DOM_DELTA_PAGE) {
+                scale = canvas.clientHeight;
+            }
+            input_handler.mouse_wheel(event.deltaX * scale, event.deltaY * scale);
+            event.preventDefault();
+        });
+
+        // keep the drawing buffer in sync with the size the canvas is displayed at
+        var last_size = null;
+        var check_size = function () {
+            // the client size includes the padding, which is not drawn to
+            var style = window.getComputedStyle(canvas);
+            var width = scaled(canvas.clientWidth - parseFloat(style.paddingLeft) - parseFloat(style.paddingRight));
+            var height = scaled(canvas.clientHeight - parseFloat(style.paddingTop) - parseFloat(style.paddingBottom));
+            if (last_size != null && last_size[0] == width && last_size[1] == height) {
+                return;
+            }
+            last_size = [width, height];
+            canvas.width = width;
+            canvas.height = height;
+            input_handler.resize(width, height);
+        };
+        // browser zoom changes devicePixelRatio and sends a resize
+        window.addEventListener("resize", check_size);
+        this.check_size = check_size;
+        if (typeof ResizeObserver !== "undefined") {
+            new ResizeObserver(check_size).observe(canvas);
+        }
+        check_size();
+
+        window.addEventListener("focus", function () {
+            if (is_active()) {
+                input_handler.focus(true);
+            }
+        });
+        window.addEventListener("blur", function () {
+            if (is_alive()) {
+                input_handler.focus(false);
+            }
+        });
+        // switching tabs doesn't always blur the window
+        document.addEventListener("visibilitychange", function () {
+            if (document.hidden ? is_alive() : is_active()) {
+                input_handler.focus(!document.hidden);
+            }
+        });
+
+        // changedTouches only holds the touches that started, moved or ended
+        var touch_listener = function (callback) {
+            return function (event) {
+                for (var i = 0; i < event.changedTouches.length; i++) {
+                    var touch = event.changedTouches[i];
+                    var position = canvas_position(touch.clientX, touch.clientY);
+                    callback.call(input_handler, touch.identifier, position[0], position[1]);
+                }
+                // keep the browser from scrolling and emulating mouse events
+                event.preventDefault();
+            };
+        };
+        var listener_options = { passive: false };
+        this.canvas.addEventListener("touchstart", touch_listener(input_handler.touch_start), listener_options);
+        this.canvas.addEventListener("touchmove", touch_listener(input_handler.touch_move), listener_options);
+        this.canvas.addEventListener("touchend", touch_listener(input_handler.touch_end), listener_options);
+        this.canvas.addEventListener("touchcancel", touch_listener(input_handler.touch_end), listener_options);
+
+        // text goes through a hidden textarea so dead keys and IME composition
+        // produce the composed characters
+        var text_area = document.createElement("textarea");
+        text_area.setAttribute("autocapitalize", "off");
+        text_area.style.cssText = "position: fixed; left: 0; top: 0; width: 1px; height: 1px; opacity: 0; pointer-events: none;";
+        document.body.appendChild(text_area);
+        text_area.addEventListener("input", function (event) {
+            if (event.isComposing) {
+                return;
+            }
+            if (text_area.value.length > 0) {
+                input_handler.text_input(text_area.value);
+            }
+            text_area.value = "";
+        });
+        text_area.addEventListener("compositionend", function (event) {
+            if (event.data) {
+                input_handler.text_input(event.data);
+            }
+            text_area.value = "";
+        });
+        var focus_text_area = function () {
+            // focus after the event so the canvas click doesn't take it back
+            setTimeout(function () { text_area.focus(); }, 0);
+        };
+        this.canvas.addEventListener("mousedown", focus_text_area);
+        this.canvas.addEventListener("touchstart", focus_text_area);
+        this.text_area = text_area;
+        text_area.focus();
+
+        window.addEventListener("gamepadconnected", function (event) {
+            if (is_alive()) {
+                input_handler.gamepad_connected(event.gamepad.index);
+            }
         });
+        window.addEventListener("gamepaddisconnected", function (event) {
+            if (is_alive()) {
+                input_handler.gamepad_disconnected(event.gamepad.index);
+            }
+        });
+
         window.addEventListener("keydown", function (event) {
-            input_handler.key_down(event.keyCode);
+            if (is_active()) {
+                input_handler.key_down_repeat(physical_key_code(event), event.repeat,
+                    event.shiftKey, event.ctrlKey, event.altKey, event.metaKey);
+            }
         });
         window.addEventListener("keyup", function (event) {
-            input_handler.key_up(event.keyCode);
+            if (is_active()) {
+                input_handler.key_up_mod(physical_key_code(event),
+                    event.shiftKey, event.ctrlKey, event.altKey, event.metaKey);
+            }
         });
     }
 }
@@ -25,10 +282,148 @@ window.create_canvas_window = function (canvas_id, input_handler) {
     return new CanvasWindow(canvas_id, input_handler);
 }
 
+window.performance_now = function () {
+    return performance.now();
+}
+
+// the callback is owned by the loop and freed once it is stopped, but not while
+// it is still running
+window.start_frame_loop = function (callback) {
+    var frame_loop = { callback: callback, request: null, running: false, stopped: false };
+    function frame(timestamp) {
+        frame_loop.running = true;
+        callback.call(timestamp);
+        frame_loop.running = false;
+        if (frame_loop.stopped) {
+            callback.free();
+        } else {
+            frame_loop.request = window.requestAnimationFrame(frame);
+        }
+    }
+    frame_loop.request = window.requestAnimationFrame(frame);
+    return frame_loop;
+}
+
+window.stop_frame_loop = function (frame_loop) {
+    if (frame_loop.stopped) {
+        return;
+    }
+    frame_loop.stopped = true;
+    if (!frame_loop.running) {
+        window.cancelAnimationFrame(frame_loop.request);
+        frame_loop.callback.free();
+    }
+}
+
+window.set_window_title = function (title) {
+    document.title = title;
+}
+
+window.set_canvas_cursor = function (window, cursor) {
+    window.canvas.style.cursor = cursor;
+}
+
+window.set_canvas_high_dpi = function (window, enabled) {
+    // the displayed size follows the drawing buffer size unless CSS sets it, keep
+    // it from growing with the pixel ratio
+    var canvas = window.canvas;
+    if (enabled && !canvas.style.width && !canvas.style.height) {
+        canvas.style.width = canvas.clientWidth + "px";
+        canvas.style.height = canvas.clientHeight + "px";
+    }
+    window.high_dpi = enabled;
+    window.check_size();
+}
+
+window.get_canvas_size = function (window, size) {
+    size.set([window.canvas.width, window.canvas.height]);
+}
+
+window.get_canvas_css_size = function (window, size) {
+    size.set([window.canvas.clientWidth, window.canvas.clientHeight]);
+}
+
+window.request_fullscreen = function (window) {
+    window.canvas.requestFullscreen();
+}
+
+window.exit_fullscreen = function () {
+    if (document.fullscreenElement) {
+        document.exitFullscreen();
+    }
+}
+
+window.request_pointer_lock = function (window) {
+    window.canvas.requestPointerLock();
+}
+
+window.exit_pointer_lock = function () {
+    document.exitPointerLock();
+}
+
+window.set_canvas_context_lost = function (window, lost) {
+    var extension = window.canvas.getContext('webgl2').getExtension("WEBGL_lose_context");
+    if (extension === null) {
+        return;
+    }
+    if (lost) {
+        extension.loseContext();
+    } else {
+        extension.restoreContext();
+    }
+}
+
 window.delete_canvas_window = function (window) {
+    // the listeners stay registered, they check for this before touching the
+    // freed input handler
+    window.deleted = true;
+    if (active_window === window) {
+        active_window = null;
+    }
+    window.text_area.remove();
     window.input_handler.free();
 }
 
 window.get_window_context = function (window) {
-    return window.canvas.getContext('webgl');
-}
\ No newline at end of file
+    // WebGL 2 for sized texture formats, UNSIGNED_INT element indices are core there
+    return window.canvas.getContext('webgl2', { depth: true, stencil: true });
+}
+
+function get_gamepad(index) {
+    var gamepads = navigator.getGamepads ? navigator.getGamepads() : [];
+    return index < gamepads.length ? gamepads[index] : null;
+}
+window.get_gamepad_count = function () {
+    return navigator.getGamepads ? navigator.getGamepads().length : 0;
+}
+window.get_gamepad_connected = function (index) {
+    var gamepad = get_gamepad(index);
+    return gamepad != null && gamepad.connected;
+}
+window.get_gamepad_button_count = function (index) {
+    return get_gamepad(index).buttons.length;
+}
+window.get_gamepad_button = function (index, button) {
+    return get_gamepad(index).buttons[button].value;
+}
+window.get_gamepad_axis_count = function (index) {
+    return get_gamepad(index).axes.length;
+}
+window.get_gamepad_axis = function (index, axis) {
+    return get_gamepad(index).axes[axis];
+}
+
+window.fetch_bytes = function (url, handler) {
+    fetch(url).then(function (response) {
+        if (!response.ok) {
+            throw new Error(response.status + " " + response.statusText);
+        }
+        return response.arrayBuffer();
+    }).then(function (buffer) {
+        handler.on_load(new Uint8Array(buffer));
+        handler.free();
+    }, function (error) {
+        handler.on_error(String(error));
+        handler.free();
+    });
+}
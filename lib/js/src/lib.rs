@@ -18,16 +18,6 @@ pub fn bootstrap() {
     window::bootstrap();
 }
 
-#[wasm_bindgen]
-pub struct MainLoopCallback(pub Box<FnMut() + 'static>);
-
-#[wasm_bindgen]
-impl MainLoopCallback {
-    pub fn call(&mut self) {
-        (*self.0)();
-    }
-}
-
 #[wasm_bindgen]
 extern "C" {
     pub type console;
@@ -36,6 +26,5 @@ extern "C" {
     pub fn log(s: &str);
 
     pub fn eval(s: &str);
-    pub fn set_main_loop(cb: MainLoopCallback);
     pub fn rand() -> f32;
 }
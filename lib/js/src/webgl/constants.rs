@@ -1,4 +1,12 @@
-use super::types::{GLboolean, GLenum};
+use super::types::{GLboolean, GLenum, GLuint};
+
+pub const NO_ERROR: GLenum = 0;
+pub const INVALID_ENUM: GLenum = 0x0500;
+pub const INVALID_VALUE: GLenum = 0x0501;
+pub const INVALID_OPERATION: GLenum = 0x0502;
+pub const OUT_OF_MEMORY: GLenum = 0x0505;
+pub const INVALID_FRAMEBUFFER_OPERATION: GLenum = 0x0506;
+pub const CONTEXT_LOST_WEBGL: GLenum = 0x9242;
 
 pub const FALSE: GLboolean = false;
 pub const TRUE: GLboolean = true;
@@ -21,8 +29,34 @@ pub const POLYGON_OFFSET_FILL: GLenum = 0x8037;
 pub const SAMPLE_ALPHA_TO_COVERAGE: GLenum = 0x809E;
 pub const SAMPLE_COVERAGE: GLenum = 0x80A0;
 pub const SCISSOR_TEST: GLenum = 0x0C11;
+pub const SCISSOR_BOX: GLenum = 0x0C10;
+pub const COLOR_WRITEMASK: GLenum = 0x0C23;
+pub const BLEND_DST_RGB: GLenum = 0x80C8;
+pub const BLEND_SRC_RGB: GLenum = 0x80C9;
 pub const STENCIL_TEST: GLenum = 0x0B90;
 
+pub const ALIASED_POINT_SIZE_RANGE: GLenum = 0x846D;
+pub const ALIASED_LINE_WIDTH_RANGE: GLenum = 0x846E;
+
+pub const NEVER: GLenum = 0x0200;
+pub const LESS: GLenum = 0x0201;
+pub const EQUAL: GLenum = 0x0202;
+pub const LEQUAL: GLenum = 0x0203;
+pub const GREATER: GLenum = 0x0204;
+pub const NOTEQUAL: GLenum = 0x0205;
+pub const GEQUAL: GLenum = 0x0206;
+pub const ALWAYS: GLenum = 0x0207;
+
+pub const KEEP: GLenum = 0x1E00;
+pub const REPLACE: GLenum = 0x1E01;
+pub const INCR: GLenum = 0x1E02;
+pub const DECR: GLenum = 0x1E03;
+pub const INVERT: GLenum = 0x150A;
+pub const INCR_WRAP: GLenum = 0x8507;
+pub const DECR_WRAP: GLenum = 0x8508;
+
+pub const ZERO: GLenum = 0;
+pub const ONE: GLenum = 1;
 pub const SRC_COLOR: GLenum = 0x0300;
 pub const ONE_MINUS_SRC_COLOR: GLenum = 0x0301;
 pub const SRC_ALPHA: GLenum = 0x0302;
@@ -32,6 +66,9 @@ pub const ONE_MINUS_DST_ALPHA: GLenum = 0x0305;
 pub const DST_COLOR: GLenum = 0x0306;
 pub const ONE_MINUS_DST_COLOR: GLenum = 0x0307;
 pub const SRC_ALPHA_SATURATE: GLenum = 0x0308;
+pub const FUNC_ADD: GLenum = 0x8006;
+pub const FUNC_SUBTRACT: GLenum = 0x800A;
+pub const FUNC_REVERSE_SUBTRACT: GLenum = 0x800B;
 pub const CONSTANT_COLOR: GLenum = 0x8001;
 pub const ONE_MINUS_CONSTANT_COLOR: GLenum = 0x8002;
 pub const CONSTANT_ALPHA: GLenum = 0x8003;
@@ -52,6 +89,9 @@ pub const MAX_VARYING_VECTORS: GLenum = 0x8DFC;
 pub const MAX_COMBINED_TEXTURE_IMAGE_UNITS: GLenum = 0x8B4D;
 pub const MAX_VERTEX_TEXTURE_IMAGE_UNITS: GLenum = 0x8B4C;
 pub const MAX_TEXTURE_IMAGE_UNITS: GLenum = 0x8872;
+pub const MAX_TEXTURE_SIZE: GLenum = 0x0D33;
+pub const RENDERER: GLenum = 0x1F01;
+pub const VERSION: GLenum = 0x1F02;
 pub const MAX_FRAGMENT_UNIFORM_VECTORS: GLenum = 0x8DFD;
 pub const SHADER_TYPE: GLenum = 0x8B4F;
 pub const SHADING_LANGUAGE_VERSION: GLenum = 0x8B8C;
@@ -71,8 +111,26 @@ pub const RGB: GLenum = 0x1907;
 pub const RGBA: GLenum = 0x1908;
 pub const LUMINANCE: GLenum = 0x1909;
 pub const LUMINANCE_ALPHA: GLenum = 0x190A;
+pub const RED: GLenum = 0x1903;
+pub const RG: GLenum = 0x8227;
+pub const R8: GLenum = 0x8229;
+pub const RG8: GLenum = 0x822B;
+pub const RGB8: GLenum = 0x8051;
+pub const RGBA8: GLenum = 0x8058;
+pub const SRGB8_ALPHA8: GLenum = 0x8C43;
+// from WEBGL_compressed_texture_s3tc, _etc and _astc
+pub const COMPRESSED_RGB_S3TC_DXT1: GLenum = 0x83F0;
+pub const COMPRESSED_RGBA_S3TC_DXT5: GLenum = 0x83F3;
+pub const COMPRESSED_RGB8_ETC2: GLenum = 0x9274;
+pub const COMPRESSED_RGBA8_ETC2_EAC: GLenum = 0x9278;
+pub const COMPRESSED_RGBA_ASTC_4X4: GLenum = 0x93B0;
+
+pub const UNPACK_ALIGNMENT: GLenum = 0x0CF5;
+pub const PACK_ALIGNMENT: GLenum = 0x0D05;
 
 pub const NEAREST: GLenum = 0x2600;
+pub const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+pub const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
 pub const LINEAR: GLenum = 0x2601;
 pub const NEAREST_MIPMAP_NEAREST: GLenum = 0x2700;
 pub const LINEAR_MIPMAP_NEAREST: GLenum = 0x2701;
@@ -82,6 +140,7 @@ pub const TEXTURE_MAG_FILTER: GLenum = 0x2800;
 pub const TEXTURE_MIN_FILTER: GLenum = 0x2801;
 pub const TEXTURE_WRAP_S: GLenum = 0x2802;
 pub const TEXTURE_WRAP_T: GLenum = 0x2803;
+pub const TEXTURE_MAX_LEVEL: GLenum = 0x813D;
 pub const TEXTURE_2D: GLenum = 0x0DE1;
 pub const TEXTURE: GLenum = 0x1702;
 pub const TEXTURE_CUBE_MAP: GLenum = 0x8513;
@@ -97,7 +156,7 @@ pub const TEXTURE0: GLenum = 0x84C0;
 pub const ACTIVE_TEXTURE: GLenum = 0x84E0;
 pub const REPEAT: GLenum = 0x2901;
 pub const CLAMP_TO_EDGE: GLenum = 0x812F;
-pub const MIRRORED_REPEAT: GLenum = 0x837;
+pub const MIRRORED_REPEAT: GLenum = 0x8370;
 
 pub const STATIC_DRAW: GLenum = 0x88E4;
 pub const STREAM_DRAW: GLenum = 0x88E0;
@@ -106,3 +165,27 @@ pub const ARRAY_BUFFER: GLenum = 0x8892;
 pub const ELEMENT_ARRAY_BUFFER: GLenum = 0x8893;
 pub const BUFFER_SIZE: GLenum = 0x8764;
 pub const BUFFER_USAGE: GLenum = 0x8765;
+pub const UNIFORM_BUFFER: GLenum = 0x8A11;
+
+pub const QUERY_RESULT: GLenum = 0x8866;
+pub const QUERY_RESULT_AVAILABLE: GLenum = 0x8867;
+// from EXT_disjoint_timer_query_webgl2
+pub const TIME_ELAPSED: GLenum = 0x88BF;
+pub const GPU_DISJOINT: GLenum = 0x8FBB;
+pub const MAX_UNIFORM_BUFFER_BINDINGS: GLenum = 0x8A2F;
+pub const INVALID_INDEX: GLuint = 0xFFFFFFFF;
+
+pub const FRAMEBUFFER: GLenum = 0x8D40;
+pub const RENDERBUFFER: GLenum = 0x8D41;
+pub const FRAMEBUFFER_BINDING: GLenum = 0x8CA6;
+pub const READ_FRAMEBUFFER: GLenum = 0x8CA8;
+pub const DRAW_FRAMEBUFFER: GLenum = 0x8CA9;
+pub const MAX_SAMPLES: GLenum = 0x8D57;
+pub const MAX_COLOR_ATTACHMENTS: GLenum = 0x8CDF;
+pub const MAX_DRAW_BUFFERS: GLenum = 0x8824;
+pub const COLOR_ATTACHMENT0: GLenum = 0x8CE0;
+pub const DEPTH_ATTACHMENT: GLenum = 0x8D00;
+pub const DEPTH_COMPONENT16: GLenum = 0x81A5;
+pub const DEPTH_STENCIL_ATTACHMENT: GLenum = 0x821A;
+pub const DEPTH24_STENCIL8: GLenum = 0x88F0;
+pub const FRAMEBUFFER_COMPLETE: GLenum = 0x8CD5;
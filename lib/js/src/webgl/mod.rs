@@ -12,6 +12,10 @@ pub type Texture = JsValue;
 pub type Program = JsValue;
 pub type Shader = JsValue;
 pub type UniformLocation = JsValue;
+pub type Framebuffer = JsValue;
+pub type Renderbuffer = JsValue;
+pub type Query = JsValue;
+pub type Sampler = JsValue;
 pub type AttribIndex = GLuint;
 
 pub fn bootstrap() {
@@ -20,20 +24,133 @@ pub fn bootstrap() {
 }
 #[wasm_bindgen]
 extern "C" {
+    pub fn gl_get_error() -> GLenum;
+    pub fn gl_reset_bound_state();
+    pub fn gl_get_parameter_i(pname: GLenum) -> GLint;
+    /// `gl_get_parameter_i` for limits such as `MAX_TEXTURE_IMAGE_UNITS`, cached
+    /// with the bound state of the current context
+    pub fn gl_get_limit(pname: GLenum) -> GLint;
+    pub fn gl_get_parameter_f(pname: GLenum) -> GLfloat;
+    pub fn gl_get_parameter_string(pname: GLenum) -> String;
+    pub fn gl_get_parameter_b(pname: GLenum) -> bool;
+    pub fn gl_get_extension(name: &str) -> bool;
+    /// Names of the supported extensions separated by spaces
+    pub fn gl_get_supported_extensions() -> String;
+    pub fn gl_get_parameter_fv(pname: GLenum, values: &mut [GLfloat]);
+    /// Also takes parameters that are arrays of booleans, as 0 and 1
+    pub fn gl_get_parameter_iv(pname: GLenum, values: &mut [GLint]);
     pub fn gl_enable(capability: GLenum);
+    pub fn gl_disable(capability: GLenum);
+    pub fn gl_is_enabled(capability: GLenum) -> bool;
     pub fn gl_blend_func(sfactor: GLenum, dfactor: GLenum);
+    pub fn gl_blend_equation(mode: GLenum);
     pub fn gl_draw_arrays(mode: GLenum, first: GLint, count: GLsizei);
+    pub fn gl_draw_arrays_instanced(
+        mode: GLenum,
+        first: GLint,
+        count: GLsizei,
+        instance_count: GLsizei,
+    );
+    pub fn gl_draw_elements(mode: GLenum, count: GLsizei, index_type: GLenum, offset: GLintptr);
     pub fn gl_clear_color(r: GLclampf, g: GLclampf, b: GLclampf, a: GLclampf);
     pub fn gl_clear(mask: GLbitfield);
+    pub fn gl_clear_stencil(s: GLint);
+    pub fn gl_clear_depth(depth: GLclampf);
+    pub fn gl_color_mask(red: bool, green: bool, blue: bool, alpha: bool);
+    pub fn gl_stencil_func(func: GLenum, reference: GLint, mask: GLuint);
+    pub fn gl_stencil_op(fail: GLenum, zfail: GLenum, zpass: GLenum);
+    pub fn gl_stencil_mask(mask: GLuint);
 
     pub fn gl_drawing_buffer_width() -> i32;
     pub fn gl_drawing_buffer_height() -> i32;
+    pub fn gl_viewport(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
+    pub fn gl_get_viewport(rect: &mut [GLint]);
+    /// Keeps the viewport of the default framebuffer in the context's bound
+    /// state, once until it is restored
+    pub fn gl_save_screen_viewport();
+    pub fn gl_restore_screen_viewport();
+    pub fn gl_line_width(width: GLfloat);
+    pub fn gl_scissor(x: GLint, y: GLint, width: GLsizei, height: GLsizei);
+    pub fn gl_read_pixels(
+        x: GLint,
+        y: GLint,
+        width: GLsizei,
+        height: GLsizei,
+        format: GLenum,
+        pixel_type: GLenum,
+        pixels: &mut [u8],
+    );
+
+    pub fn gl_create_framebuffer() -> JsValue;
+    pub fn gl_delete_framebuffer(framebuffer: &JsValue);
+    pub fn gl_bind_framebuffer(target: GLenum, framebuffer: &JsValue);
+    pub fn gl_get_framebuffer_binding() -> JsValue;
+    pub fn gl_framebuffer_texture_2d(
+        target: GLenum,
+        attachment: GLenum,
+        textarget: GLenum,
+        texture: &JsValue,
+        level: GLint,
+    );
+    pub fn gl_framebuffer_renderbuffer(
+        target: GLenum,
+        attachment: GLenum,
+        renderbuffertarget: GLenum,
+        renderbuffer: &JsValue,
+    );
+    pub fn gl_check_framebuffer_status(target: GLenum) -> GLenum;
+    pub fn gl_draw_buffers(buffers: &[GLenum]);
+
+    pub fn gl_create_query() -> JsValue;
+    pub fn gl_delete_query(query: &JsValue);
+    pub fn gl_begin_query(target: GLenum, query: &JsValue);
+    pub fn gl_end_query(target: GLenum);
+    pub fn gl_get_query_parameter_b(query: &JsValue, pname: GLenum) -> bool;
+    pub fn gl_get_query_parameter_f(query: &JsValue, pname: GLenum) -> f64;
+    pub fn gl_blit_framebuffer(
+        src_x0: GLint,
+        src_y0: GLint,
+        src_x1: GLint,
+        src_y1: GLint,
+        dst_x0: GLint,
+        dst_y0: GLint,
+        dst_x1: GLint,
+        dst_y1: GLint,
+        mask: GLbitfield,
+        filter: GLenum,
+    );
+
+    pub fn gl_create_renderbuffer() -> JsValue;
+    pub fn gl_delete_renderbuffer(renderbuffer: &JsValue);
+    pub fn gl_bind_renderbuffer(target: GLenum, renderbuffer: &JsValue);
+    pub fn gl_renderbuffer_storage(
+        target: GLenum,
+        internalformat: GLenum,
+        width: GLsizei,
+        height: GLsizei,
+    );
+
+    pub fn gl_renderbuffer_storage_multisample(
+        target: GLenum,
+        samples: GLsizei,
+        internalformat: GLenum,
+        width: GLsizei,
+        height: GLsizei,
+    );
 
     pub fn gl_create_texture() -> JsValue;
     pub fn gl_delete_texture(texture: &JsValue);
     pub fn gl_bind_texture(target: GLenum, texture: &JsValue);
     pub fn gl_active_texture(texture: GLenum);
+    pub fn gl_create_sampler() -> JsValue;
+    pub fn gl_delete_sampler(sampler: &JsValue);
+    pub fn gl_sampler_parameter_i(sampler: &JsValue, pname: GLenum, param: GLint);
+    /// Skipped when `sampler` is already bound to `unit`, null unbinds
+    pub fn gl_bind_sampler(unit: GLuint, sampler: &JsValue);
     pub fn gl_tex_parameter_i(target: GLenum, pname: GLenum, param: i32);
+    pub fn gl_tex_parameter_f(target: GLenum, pname: GLenum, param: GLfloat);
+    pub fn gl_generate_mipmap(target: GLenum);
+    pub fn gl_pixel_store_i(pname: GLenum, param: GLint);
     pub fn gl_tex_image_2d_empty(
         target: GLenum,
         level: GLint,
@@ -55,6 +172,15 @@ extern "C" {
         data_type: GLenum,
         pixels: &[u8],
     );
+    pub fn gl_compressed_tex_image_2d(
+        target: GLenum,
+        level: GLint,
+        internal_format: GLenum,
+        width: GLsizei,
+        height: GLsizei,
+        border: GLint,
+        data: &[u8],
+    );
     pub fn gl_tex_image_2d_u16(
         target: GLenum,
         level: GLint,
@@ -99,22 +225,34 @@ extern "C" {
     pub fn gl_create_program() -> JsValue;
     pub fn gl_delete_program(program: &JsValue);
     pub fn gl_attach_shader(program: &JsValue, shader: &JsValue);
+    pub fn gl_detach_shader(program: &JsValue, shader: &JsValue);
     pub fn gl_link_program(program: &JsValue);
     pub fn gl_use_program(program: &JsValue);
     pub fn gl_get_program_parameter(program: &JsValue, pname: GLenum) -> GLint;
     pub fn gl_get_program_info_log(program: &JsValue) -> String;
 
     pub fn gl_get_uniform_location(program: &JsValue, name: &str) -> JsValue;
+    pub fn gl_uniform1f(location: &JsValue, v0: GLfloat);
     pub fn gl_uniform2f(location: &JsValue, v0: GLfloat, v1: GLfloat);
+    pub fn gl_uniform3f(location: &JsValue, v0: GLfloat, v1: GLfloat, v2: GLfloat);
+    pub fn gl_uniform4f(location: &JsValue, v0: GLfloat, v1: GLfloat, v2: GLfloat, v3: GLfloat);
+    pub fn gl_uniform_matrix3fv(location: &JsValue, transpose: GLboolean, value: &[GLfloat]);
+    pub fn gl_uniform_matrix4fv(location: &JsValue, transpose: GLboolean, value: &[GLfloat]);
     pub fn gl_uniform1i(location: &JsValue, v0: GLint);
 
     pub fn gl_create_buffer() -> JsValue;
     pub fn gl_delete_buffer(buffer: &JsValue);
     pub fn gl_bind_buffer(target: GLenum, buffer: &JsValue);
     pub fn gl_buffer_data(target: GLenum, data: &[u8], usage: GLenum);
+    pub fn gl_buffer_data_size(target: GLenum, size: GLsizeiptr, usage: GLenum);
+    pub fn gl_buffer_sub_data(target: GLenum, offset: GLintptr, data: &[u8]);
+    pub fn gl_bind_buffer_base(target: GLenum, index: GLuint, buffer: &JsValue);
+    pub fn gl_get_uniform_block_index(program: &JsValue, name: &str) -> GLuint;
+    pub fn gl_uniform_block_binding(program: &JsValue, index: GLuint, binding: GLuint);
 
     pub fn gl_get_attrib_location(program: &JsValue, name: &str) -> GLint;
     pub fn gl_enable_vertex_attrib_array(index: AttribIndex);
+    pub fn gl_vertex_attrib_divisor(index: AttribIndex, divisor: GLuint);
     pub fn gl_vertex_attrib_pointer(
         index: AttribIndex,
         size: GLint,
@@ -123,4 +261,11 @@ extern "C" {
         stride: GLsizei,
         offset: GLintptr,
     );
+    pub fn gl_vertex_attrib_i_pointer(
+        index: AttribIndex,
+        size: GLint,
+        attrib_type: GLenum,
+        stride: GLsizei,
+        offset: GLintptr,
+    );
 }
@@ -13,25 +13,192 @@ extern "C" {
     pub fn create_canvas_window(canvas_id: &str, input_handler: InputHandler) -> CanvasWindow;
 
     pub fn get_window_context(window: &CanvasWindow) -> GLContext;
+
+    pub fn get_gamepad_count() -> u32;
+    pub fn get_gamepad_connected(index: u32) -> bool;
+    pub fn get_gamepad_button_count(index: u32) -> u32;
+    pub fn get_gamepad_button(index: u32, button: u32) -> f32;
+    pub fn get_gamepad_axis_count(index: u32) -> u32;
+    pub fn get_gamepad_axis(index: u32, axis: u32) -> f32;
     pub fn gl_set_current_context(context: &GLContext);
+
+    pub fn set_window_title(title: &str);
+
+    /// Milliseconds since the page was loaded, with sub-millisecond precision
+    pub fn performance_now() -> f64;
+
+    fn start_frame_loop(callback: FrameCallback) -> JsValue;
+    fn stop_frame_loop(frame_loop: &JsValue);
+
+    /// Sets the CSS cursor of the window's canvas
+    pub fn set_canvas_cursor(window: &CanvasWindow, cursor: &str);
+
+    /// Backs the canvas with one pixel per device pixel instead of per CSS pixel
+    /// and reports sizes and positions in those pixels
+    pub fn set_canvas_high_dpi(window: &CanvasWindow, enabled: bool);
+    /// Size of the canvas drawing buffer in pixels
+    pub fn get_canvas_size(window: &CanvasWindow, size: &mut [u32]);
+    /// Size the canvas is displayed at in CSS pixels
+    pub fn get_canvas_css_size(window: &CanvasWindow, size: &mut [f64]);
+
+    pub fn request_fullscreen(window: &CanvasWindow);
+    pub fn exit_fullscreen();
+
+    pub fn request_pointer_lock(window: &CanvasWindow);
+    pub fn exit_pointer_lock();
+
+    /// Loses or restores the canvas's context through `WEBGL_lose_context`, for
+    /// testing how a game recovers
+    pub fn set_canvas_context_lost(window: &CanvasWindow, lost: bool);
+
+    /// Fetches `url` and calls `handler` once with the body or an error, the
+    /// handler is freed afterwards
+    pub fn fetch_bytes(url: &str, handler: FetchHandler);
+}
+
+type FrameFn = Box<FnMut(f64) + 'static>;
+
+/// Called by `requestAnimationFrame` with the frame timestamp in milliseconds
+#[wasm_bindgen]
+pub struct FrameCallback {
+    callback: FrameFn,
+}
+
+#[wasm_bindgen]
+impl FrameCallback {
+    pub fn call(&mut self, timestamp: f64) {
+        (*self.callback)(timestamp);
+    }
+}
+
+type FetchFn = Box<FnMut(Result<Vec<u8>, String>) + 'static>;
+
+#[wasm_bindgen]
+pub struct FetchHandler {
+    callback: FetchFn,
+}
+
+#[wasm_bindgen]
+impl FetchHandler {
+    pub fn on_load(&mut self, data: Vec<u8>) {
+        (*self.callback)(Ok(data));
+    }
+    pub fn on_error(&mut self, message: String) {
+        (*self.callback)(Err(message));
+    }
+}
+
+impl FetchHandler {
+    pub fn new<F: FnMut(Result<Vec<u8>, String>) + 'static>(f: F) -> FetchHandler {
+        FetchHandler {
+            callback: Box::new(f),
+        }
+    }
+}
+
+/// A loop started with `run_loop`, it keeps running when this is dropped
+#[derive(Clone)]
+pub struct AnimationLoop(JsValue);
+
+impl AnimationLoop {
+    /// Stops scheduling frames and frees the callback, the current frame is
+    /// finished first when called from inside the callback
+    pub fn stop(&self) {
+        stop_frame_loop(&self.0);
+    }
+}
+
+/// Calls `f` every animation frame with the frame timestamp in milliseconds,
+/// which shares its time origin with `performance_now`
+pub fn run_loop<F: FnMut(f64) + 'static>(f: F) -> AnimationLoop {
+    AnimationLoop(start_frame_loop(FrameCallback {
+        callback: Box::new(f),
+    }))
 }
 
 type MouseX = i32;
 type MouseY = i32;
 type MouseButton = i8;
 type Key = i32;
+type WheelDelta = f32;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    pub buttons: Vec<f32>,
+    pub axes: Vec<f32>,
+}
+
+/// Polls the current state of a gamepad from the browser
+pub fn gamepad_state(index: u32) -> Option<GamepadState> {
+    if !get_gamepad_connected(index) {
+        return None;
+    }
+    Some(GamepadState {
+        buttons: (0..get_gamepad_button_count(index))
+            .map(|button| get_gamepad_button(index, button))
+            .collect(),
+        axes: (0..get_gamepad_axis_count(index))
+            .map(|axis| get_gamepad_axis(index, axis))
+            .collect(),
+    })
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
 
 type MouseMoveCallback = Box<FnMut(MouseX, MouseY) + 'static>;
+type MouseButtons = u32;
+type MouseMoveButtonsCallback = Box<FnMut(MouseX, MouseY, MouseButtons) + 'static>;
+type MouseDeltaCallback = Box<FnMut(MouseX, MouseY) + 'static>;
+type PointerLockCallback = Box<FnMut(bool) + 'static>;
+type MouseHoverCallback = Box<FnMut() + 'static>;
 type MouseButtonCallback = Box<FnMut(MouseButton, MouseX, MouseY) + 'static>;
 type KeyboardCallback = Box<FnMut(Key) + 'static>;
+type MouseWheelCallback = Box<FnMut(WheelDelta, WheelDelta) + 'static>;
+type ResizeCallback = Box<FnMut(i32, i32) + 'static>;
+type FocusCallback = Box<FnMut(bool) + 'static>;
+type FullscreenCallback = Box<FnMut(bool) + 'static>;
+type ContextLostCallback = Box<FnMut(bool) + 'static>;
+type GamepadCallback = Box<FnMut(u32) + 'static>;
+type TextInputCallback = Box<FnMut(String) + 'static>;
+type TouchId = i32;
+type TouchCallback = Box<FnMut(TouchId, MouseX, MouseY) + 'static>;
+type MouseButtonModCallback = Box<FnMut(MouseButton, MouseX, MouseY, Modifiers) + 'static>;
+type KeyboardModCallback = Box<FnMut(Key, Modifiers) + 'static>;
 
 #[wasm_bindgen]
 pub struct InputHandler {
     mouse_move: Option<MouseMoveCallback>,
+    mouse_move_buttons: Option<MouseMoveButtonsCallback>,
+    mouse_delta: Option<MouseDeltaCallback>,
+    pointer_lock_change: Option<PointerLockCallback>,
+    mouse_enter: Option<MouseHoverCallback>,
+    mouse_leave: Option<MouseHoverCallback>,
     mouse_down: Option<MouseButtonCallback>,
     mouse_up: Option<MouseButtonCallback>,
+    mouse_wheel: Option<MouseWheelCallback>,
     key_down: Option<KeyboardCallback>,
     key_up: Option<KeyboardCallback>,
+    mouse_down_mod: Option<MouseButtonModCallback>,
+    mouse_up_mod: Option<MouseButtonModCallback>,
+    key_down_mod: Option<KeyboardModCallback>,
+    key_up_mod: Option<KeyboardModCallback>,
+    key_repeat: Option<KeyboardCallback>,
+    resize: Option<ResizeCallback>,
+    focus: Option<FocusCallback>,
+    fullscreen_change: Option<FullscreenCallback>,
+    context_lost: Option<ContextLostCallback>,
+    gamepad_connected: Option<GamepadCallback>,
+    gamepad_disconnected: Option<GamepadCallback>,
+    touch_start: Option<TouchCallback>,
+    touch_move: Option<TouchCallback>,
+    touch_end: Option<TouchCallback>,
+    text_input: Option<TextInputCallback>,
 }
 
 #[wasm_bindgen]
@@ -41,6 +208,26 @@ impl InputHandler {
             (*mouse_move)(x, y);
         }
     }
+    pub fn mouse_delta(&mut self, dx: MouseX, dy: MouseY) {
+        if let Some(ref mut mouse_delta) = self.mouse_delta {
+            (*mouse_delta)(dx, dy);
+        }
+    }
+    pub fn pointer_lock_change(&mut self, locked: bool) {
+        if let Some(ref mut pointer_lock_change) = self.pointer_lock_change {
+            (*pointer_lock_change)(locked);
+        }
+    }
+    pub fn mouse_enter(&mut self) {
+        if let Some(ref mut mouse_enter) = self.mouse_enter {
+            (*mouse_enter)();
+        }
+    }
+    pub fn mouse_leave(&mut self) {
+        if let Some(ref mut mouse_leave) = self.mouse_leave {
+            (*mouse_leave)();
+        }
+    }
     pub fn mouse_down(&mut self, button: MouseButton, x: MouseX, y: MouseY) {
         if let Some(ref mut mouse_down) = self.mouse_down {
             (*mouse_down)(button, x, y);
@@ -51,6 +238,11 @@ impl InputHandler {
             (*mouse_up)(button, x, y);
         }
     }
+    pub fn mouse_wheel(&mut self, dx: WheelDelta, dy: WheelDelta) {
+        if let Some(ref mut mouse_wheel) = self.mouse_wheel {
+            (*mouse_wheel)(dx, dy);
+        }
+    }
     pub fn key_down(&mut self, key: Key) {
         if let Some(ref mut key_down) = self.key_down {
             (*key_down)(key);
@@ -61,32 +253,276 @@ impl InputHandler {
             (*key_up)(key);
         }
     }
+
+    pub fn resize(&mut self, width: i32, height: i32) {
+        if let Some(ref mut resize) = self.resize {
+            (*resize)(width, height);
+        }
+    }
+
+    pub fn focus(&mut self, focused: bool) {
+        if let Some(ref mut focus) = self.focus {
+            (*focus)(focused);
+        }
+    }
+
+    pub fn fullscreen_change(&mut self, fullscreen: bool) {
+        if let Some(ref mut fullscreen_change) = self.fullscreen_change {
+            (*fullscreen_change)(fullscreen);
+        }
+    }
+
+    pub fn context_lost(&mut self, lost: bool) {
+        if let Some(ref mut context_lost) = self.context_lost {
+            (*context_lost)(lost);
+        }
+    }
+
+    pub fn text_input(&mut self, text: String) {
+        if let Some(ref mut text_input) = self.text_input {
+            (*text_input)(text);
+        }
+    }
+    pub fn touch_start(&mut self, id: TouchId, x: MouseX, y: MouseY) {
+        if let Some(ref mut touch_start) = self.touch_start {
+            (*touch_start)(id, x, y);
+        }
+    }
+    pub fn touch_move(&mut self, id: TouchId, x: MouseX, y: MouseY) {
+        if let Some(ref mut touch_move) = self.touch_move {
+            (*touch_move)(id, x, y);
+        }
+    }
+    pub fn touch_end(&mut self, id: TouchId, x: MouseX, y: MouseY) {
+        if let Some(ref mut touch_end) = self.touch_end {
+            (*touch_end)(id, x, y);
+        }
+    }
+    pub fn gamepad_connected(&mut self, index: u32) {
+        if let Some(ref mut gamepad_connected) = self.gamepad_connected {
+            (*gamepad_connected)(index);
+        }
+    }
+    pub fn gamepad_disconnected(&mut self, index: u32) {
+        if let Some(ref mut gamepad_disconnected) = self.gamepad_disconnected {
+            (*gamepad_disconnected)(index);
+        }
+    }
+
+    // `buttons` is the DOM bitmask of held buttons: 1 left, 2 right, 4 middle.
+    // Falls back to the plain callback when no button aware callback is set
+    pub fn mouse_move_buttons(&mut self, x: MouseX, y: MouseY, buttons: MouseButtons) {
+        match self.mouse_move_buttons {
+            Some(ref mut mouse_move) => (*mouse_move)(x, y, buttons),
+            None => self.mouse_move(x, y),
+        }
+    }
+
+    // the _mod variants fall back to the plain callbacks when no modifier aware
+    // callback is set
+    pub fn mouse_down_mod(
+        &mut self,
+        button: MouseButton,
+        x: MouseX,
+        y: MouseY,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+        meta: bool,
+    ) {
+        let modifiers = Modifiers {
+            shift,
+            ctrl,
+            alt,
+            meta,
+        };
+        match self.mouse_down_mod {
+            Some(ref mut mouse_down) => (*mouse_down)(button, x, y, modifiers),
+            None => self.mouse_down(button, x, y),
+        }
+    }
+    pub fn mouse_up_mod(
+        &mut self,
+        button: MouseButton,
+        x: MouseX,
+        y: MouseY,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+        meta: bool,
+    ) {
+        let modifiers = Modifiers {
+            shift,
+            ctrl,
+            alt,
+            meta,
+        };
+        match self.mouse_up_mod {
+            Some(ref mut mouse_up) => (*mouse_up)(button, x, y, modifiers),
+            None => self.mouse_up(button, x, y),
+        }
+    }
+    pub fn key_down_mod(&mut self, key: Key, shift: bool, ctrl: bool, alt: bool, meta: bool) {
+        let modifiers = Modifiers {
+            shift,
+            ctrl,
+            alt,
+            meta,
+        };
+        match self.key_down_mod {
+            Some(ref mut key_down) => (*key_down)(key, modifiers),
+            None => self.key_down(key),
+        }
+    }
+    /// Key down that knows whether it was sent by key repeat, repeats go to the
+    /// repeat callback and fall back to a regular key down
+    pub fn key_down_repeat(
+        &mut self,
+        key: Key,
+        repeat: bool,
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+        meta: bool,
+    ) {
+        match self.key_repeat {
+            Some(ref mut key_repeat) if repeat => (*key_repeat)(key),
+            _ => self.key_down_mod(key, shift, ctrl, alt, meta),
+        }
+    }
+    pub fn key_up_mod(&mut self, key: Key, shift: bool, ctrl: bool, alt: bool, meta: bool) {
+        let modifiers = Modifiers {
+            shift,
+            ctrl,
+            alt,
+            meta,
+        };
+        match self.key_up_mod {
+            Some(ref mut key_up) => (*key_up)(key, modifiers),
+            None => self.key_up(key),
+        }
+    }
 }
 
 impl InputHandler {
     pub fn new() -> InputHandler {
         InputHandler {
             mouse_move: None,
+            mouse_move_buttons: None,
+            mouse_delta: None,
+            pointer_lock_change: None,
+            mouse_enter: None,
+            mouse_leave: None,
             mouse_down: None,
             mouse_up: None,
+            mouse_wheel: None,
             key_down: None,
             key_up: None,
+            mouse_down_mod: None,
+            mouse_up_mod: None,
+            key_down_mod: None,
+            key_up_mod: None,
+            key_repeat: None,
+            resize: None,
+            focus: None,
+            fullscreen_change: None,
+            context_lost: None,
+            gamepad_connected: None,
+            gamepad_disconnected: None,
+            touch_start: None,
+            touch_move: None,
+            touch_end: None,
+            text_input: None,
         }
     }
 
     pub fn set_mouse_move<T: FnMut(MouseX, MouseY) + 'static>(&mut self, f: T) {
         self.mouse_move = Some(Box::new(f));
     }
+    pub fn set_mouse_move_buttons<T>(&mut self, f: T)
+    where
+        T: FnMut(MouseX, MouseY, MouseButtons) + 'static,
+    {
+        self.mouse_move_buttons = Some(Box::new(f));
+    }
+    pub fn set_mouse_delta<T: FnMut(MouseX, MouseY) + 'static>(&mut self, f: T) {
+        self.mouse_delta = Some(Box::new(f));
+    }
+    pub fn set_pointer_lock_change<T: FnMut(bool) + 'static>(&mut self, f: T) {
+        self.pointer_lock_change = Some(Box::new(f));
+    }
+    pub fn set_mouse_enter<T: FnMut() + 'static>(&mut self, f: T) {
+        self.mouse_enter = Some(Box::new(f));
+    }
+    pub fn set_mouse_leave<T: FnMut() + 'static>(&mut self, f: T) {
+        self.mouse_leave = Some(Box::new(f));
+    }
     pub fn set_mouse_down<T: FnMut(MouseButton, MouseX, MouseY) + 'static>(&mut self, f: T) {
         self.mouse_down = Some(Box::new(f));
     }
     pub fn set_mouse_up<T: FnMut(MouseButton, MouseX, MouseY) + 'static>(&mut self, f: T) {
         self.mouse_up = Some(Box::new(f));
     }
+    pub fn set_mouse_wheel<T: FnMut(WheelDelta, WheelDelta) + 'static>(&mut self, f: T) {
+        self.mouse_wheel = Some(Box::new(f));
+    }
     pub fn set_key_down<T: FnMut(Key) + 'static>(&mut self, f: T) {
         self.key_down = Some(Box::new(f));
     }
     pub fn set_key_up<T: FnMut(Key) + 'static>(&mut self, f: T) {
         self.key_up = Some(Box::new(f));
     }
+    pub fn set_mouse_down_mod<T>(&mut self, f: T)
+    where
+        T: FnMut(MouseButton, MouseX, MouseY, Modifiers) + 'static,
+    {
+        self.mouse_down_mod = Some(Box::new(f));
+    }
+    pub fn set_mouse_up_mod<T>(&mut self, f: T)
+    where
+        T: FnMut(MouseButton, MouseX, MouseY, Modifiers) + 'static,
+    {
+        self.mouse_up_mod = Some(Box::new(f));
+    }
+    pub fn set_key_down_mod<T: FnMut(Key, Modifiers) + 'static>(&mut self, f: T) {
+        self.key_down_mod = Some(Box::new(f));
+    }
+    pub fn set_key_up_mod<T: FnMut(Key, Modifiers) + 'static>(&mut self, f: T) {
+        self.key_up_mod = Some(Box::new(f));
+    }
+    pub fn set_key_repeat<T: FnMut(Key) + 'static>(&mut self, f: T) {
+        self.key_repeat = Some(Box::new(f));
+    }
+    pub fn set_fullscreen_change<T: FnMut(bool) + 'static>(&mut self, f: T) {
+        self.fullscreen_change = Some(Box::new(f));
+    }
+    /// Called with `true` when the canvas loses its WebGL context and with
+    /// `false` once it has been restored
+    pub fn set_context_lost<T: FnMut(bool) + 'static>(&mut self, f: T) {
+        self.context_lost = Some(Box::new(f));
+    }
+    pub fn set_focus<T: FnMut(bool) + 'static>(&mut self, f: T) {
+        self.focus = Some(Box::new(f));
+    }
+    pub fn set_resize<T: FnMut(i32, i32) + 'static>(&mut self, f: T) {
+        self.resize = Some(Box::new(f));
+    }
+    pub fn set_text_input<T: FnMut(String) + 'static>(&mut self, f: T) {
+        self.text_input = Some(Box::new(f));
+    }
+    pub fn set_touch_start<T: FnMut(TouchId, MouseX, MouseY) + 'static>(&mut self, f: T) {
+        self.touch_start = Some(Box::new(f));
+    }
+    pub fn set_touch_move<T: FnMut(TouchId, MouseX, MouseY) + 'static>(&mut self, f: T) {
+        self.touch_move = Some(Box::new(f));
+    }
+    pub fn set_touch_end<T: FnMut(TouchId, MouseX, MouseY) + 'static>(&mut self, f: T) {
+        self.touch_end = Some(Box::new(f));
+    }
+    pub fn set_gamepad_connected<T: FnMut(u32) + 'static>(&mut self, f: T) {
+        self.gamepad_connected = Some(Box::new(f));
+    }
+    pub fn set_gamepad_disconnected<T: FnMut(u32) + 'static>(&mut self, f: T) {
+        self.gamepad_disconnected = Some(Box::new(f));
+    }
 }
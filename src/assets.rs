@@ -1,16 +1,28 @@
 use failure::Error;
 use png;
+use png::HasParameters;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use platform::fetch;
+use rendering::{mip_size, CompressedFormat};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
 
 pub fn image_from_png(png_data: &[u8]) -> Result<Image, Error> {
-    let decoder = png::Decoder::new(png_data);
-    let (info, mut reader) = decoder.read_info()?;
-    let mut buf = vec![0; info.buffer_size()];
-    reader.next_frame(&mut buf)?;
-    Ok(Image {
-        data: buf,
-        width: info.width,
-        height: info.height,
-    })
+    Image::from_png_bytes(png_data)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResizeFilter {
+    Nearest,
+    Bilinear,
 }
 
 #[derive(Hash)]
@@ -19,3 +31,645 @@ pub struct Image {
     pub width: u32,
     pub height: u32,
 }
+
+impl Image {
+    pub fn solid(width: u32, height: u32, rgba: [u8; 4]) -> Image {
+        Image {
+            data: rgba.repeat((width * height) as usize),
+            width,
+            height,
+        }
+    }
+
+    /// Squares of `cell` by `cell` pixels alternating between the two colors,
+    /// starting with `color_a` in the first corner
+    pub fn checkerboard(
+        width: u32,
+        height: u32,
+        cell: u32,
+        color_a: [u8; 4],
+        color_b: [u8; 4],
+    ) -> Image {
+        let cell = cell.max(1);
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let color = if (x / cell + y / cell) % 2 == 0 {
+                    color_a
+                } else {
+                    color_b
+                };
+                data.extend_from_slice(&color);
+            }
+        }
+        Image {
+            data,
+            width,
+            height,
+        }
+    }
+
+    /// Decodes a PNG into 8 bit RGBA, expanding grayscale, palette and RGB images
+    pub fn from_png_bytes(png_data: &[u8]) -> Result<Image, Error> {
+        let mut decoder = png::Decoder::new(png_data);
+        decoder.set(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let (info, mut reader) = decoder.read_info()?;
+        let mut buf = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buf)?;
+
+        let data = match info.color_type {
+            png::ColorType::RGBA => buf,
+            png::ColorType::RGB => buf
+                .chunks(3)
+                .flat_map(|p| vec![p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buf
+                .chunks(2)
+                .flat_map(|p| vec![p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buf.iter().flat_map(|&v| vec![v, v, v, 255]).collect(),
+            png::ColorType::Indexed => {
+                return Err(format_err!("palette was not expanded while decoding png"))
+            }
+        };
+
+        Ok(Image {
+            data,
+            width: info.width,
+            height: info.height,
+        })
+    }
+
+    /// Copies the `w` by `h` RGBA rectangle at `(x, y)` into a new image
+    pub fn sub_region(&self, x: u32, y: u32, w: u32, h: u32) -> Result<Image, Error> {
+        let right = x.checked_add(w);
+        let bottom = y.checked_add(h);
+        if right.map_or(true, |right| right > self.width)
+            || bottom.map_or(true, |bottom| bottom > self.height)
+        {
+            return Err(format_err!(
+                "region {}x{} at ({}, {}) is outside of the {}x{} image",
+                w,
+                h,
+                x,
+                y,
+                self.width,
+                self.height
+            ));
+        }
+
+        let stride = (self.width * 4) as usize;
+        let row_len = (w * 4) as usize;
+        let mut data = Vec::with_capacity(row_len * h as usize);
+        for row in y..y + h {
+            let start = row as usize * stride + (x * 4) as usize;
+            data.extend_from_slice(&self.data[start..start + row_len]);
+        }
+
+        Ok(Image {
+            data,
+            width: w,
+            height: h,
+        })
+    }
+
+    /// Reverses the order of the rows, converting between top left and bottom left
+    /// origins
+    pub fn flip_vertical(&mut self) {
+        let row_len = (self.width * 4) as usize;
+        let rows = self.height as usize;
+        for row in 0..rows / 2 {
+            let (top, bottom) = self.data.split_at_mut((rows - row - 1) * row_len);
+            top[row * row_len..(row + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+        }
+    }
+
+    pub fn flipped_vertical(mut self) -> Image {
+        self.flip_vertical();
+        self
+    }
+
+    /// Multiplies the color channels by alpha, for drawing with
+    /// `BlendMode::PremultipliedAlpha` without dark fringes around transparent
+    /// edges. PNGs are decoded with straight alpha.
+    pub fn premultiply_alpha(&mut self) {
+        for pixel in self.data.chunks_mut(4) {
+            let alpha = u32::from(pixel[3]);
+            for channel in pixel[..3].iter_mut() {
+                *channel = ((u32::from(*channel) * alpha + 127) / 255) as u8;
+            }
+        }
+    }
+
+    pub fn premultiplied_alpha(mut self) -> Image {
+        self.premultiply_alpha();
+        self
+    }
+
+    /// Scales the image to `new_w` by `new_h`. Bilinear sampling clamps to the
+    /// edge pixels, so borders don't fade towards black.
+    pub fn resize(&self, new_w: u32, new_h: u32, filter: ResizeFilter) -> Image {
+        if self.width == 0 || self.height == 0 {
+            return Image {
+                data: vec![0; (new_w * new_h * 4) as usize],
+                width: new_w,
+                height: new_h,
+            };
+        }
+
+        let mut data = Vec::with_capacity((new_w * new_h * 4) as usize);
+        let pixel = |x: u32, y: u32| {
+            let i = ((y * self.width + x) * 4) as usize;
+            &self.data[i..i + 4]
+        };
+        // sample at pixel centers so that both images cover the same area
+        let source = |x: u32, new: u32, old: u32| (x as f32 + 0.5) * old as f32 / new as f32 - 0.5;
+
+        for y in 0..new_h {
+            for x in 0..new_w {
+                let sx = source(x, new_w, self.width);
+                let sy = source(y, new_h, self.height);
+                match filter {
+                    ResizeFilter::Nearest => {
+                        let nx = ((sx + 0.5) as u32).min(self.width - 1);
+                        let ny = ((sy + 0.5) as u32).min(self.height - 1);
+                        data.extend_from_slice(pixel(nx, ny));
+                    }
+                    ResizeFilter::Bilinear => {
+                        let sx = sx.max(0.0).min((self.width - 1) as f32);
+                        let sy = sy.max(0.0).min((self.height - 1) as f32);
+                        let (x0, y0) = (sx as u32, sy as u32);
+                        let (x1, y1) =
+                            ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                        let (fx, fy) = (sx - x0 as f32, sy - y0 as f32);
+                        for c in 0..4 {
+                            let top = f32::from(pixel(x0, y0)[c]) * (1.0 - fx)
+                                + f32::from(pixel(x1, y0)[c]) * fx;
+                            let bottom = f32::from(pixel(x0, y1)[c]) * (1.0 - fx)
+                                + f32::from(pixel(x1, y1)[c]) * fx;
+                            data.push((top * (1.0 - fy) + bottom * fy).round() as u8);
+                        }
+                    }
+                }
+            }
+        }
+
+        Image {
+            data,
+            width: new_w,
+            height: new_h,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_png_file<P: AsRef<Path>>(path: P) -> Result<Image, Error> {
+        use std::io::Read;
+
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+        Image::from_png_bytes(&data)
+    }
+}
+
+/// An asset loading in the background. It can be awaited by an executor, or
+/// `take` can be called every frame from the game loop until it is done.
+pub struct Load<T> {
+    state: Arc<Mutex<LoadState<T>>>,
+}
+
+struct LoadState<T> {
+    result: Option<Result<T, Error>>,
+    waker: Option<Waker>,
+}
+
+impl<T> Load<T> {
+    /// Returns the result once loading has finished, and `None` before that and
+    /// after it has been taken
+    pub fn take(&mut self) -> Option<Result<T, Error>> {
+        self.state.lock().unwrap().result.take()
+    }
+}
+
+impl<T> Future for Load<T> {
+    type Output = Result<T, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Loads a file without blocking, natively `url` is a path relative to the
+/// working directory and on the web a url relative to the page
+pub fn load_bytes(url: &str) -> Load<Vec<u8>> {
+    load(url, Ok)
+}
+
+/// Loads and decodes a PNG without blocking, see `load_bytes`
+pub fn load_image(url: &str) -> Load<Image> {
+    load(url, |data| Image::from_png_bytes(&data))
+}
+
+fn load<T: Send + 'static>(url: &str, decode: fn(Vec<u8>) -> Result<T, Error>) -> Load<T> {
+    let state = Arc::new(Mutex::new(LoadState {
+        result: None,
+        waker: None,
+    }));
+    let loaded_state = Arc::clone(&state);
+    fetch::fetch_bytes(url, move |data| {
+        let result = data.and_then(decode);
+        let waker = {
+            let mut state = loaded_state.lock().unwrap();
+            state.result = Some(result);
+            state.waker.take()
+        };
+        // outside of the lock, the executor may poll right away
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+    Load { state }
+}
+
+const KTX_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x31, 0x31, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const KTX_HEADER_SIZE: usize = 64;
+
+/// Pre-compressed texture data with its mip levels, level 0 first and each level
+/// half the size of the one before, see `Renderer::create_texture_from_compressed_image`
+pub struct CompressedImage {
+    pub format: CompressedFormat,
+    pub width: u32,
+    pub height: u32,
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    /// Parses a KTX 1 file holding a single 2D texture in one of the
+    /// `CompressedFormat`s, written in either byte order
+    pub fn from_ktx_bytes(data: &[u8]) -> Result<CompressedImage, Error> {
+        if data.len() < KTX_HEADER_SIZE || data[0..12] != KTX_IDENTIFIER {
+            return Err(format_err!("not a KTX 1 file"));
+        }
+
+        let swap = match read_u32_le(&data[12..]) {
+            0x0403_0201 => false,
+            0x0102_0304 => true,
+            _ => return Err(format_err!("invalid KTX endianness field")),
+        };
+        let read = |offset: usize| {
+            let value = read_u32_le(&data[offset..]);
+            if swap {
+                value.swap_bytes()
+            } else {
+                value
+            }
+        };
+        let header: Vec<u32> = (0..12).map(|i| read(16 + i * 4)).collect();
+        let (gl_type, internal_format) = (header[0], header[3]);
+        let (width, height, depth) = (header[5], header[6], header[7]);
+        let (array_elements, faces) = (header[8], header[9]);
+        let level_count = header[10].max(1);
+        let key_value_size = header[11] as usize;
+
+        if gl_type != 0 {
+            return Err(format_err!("KTX file is not compressed"));
+        }
+        let format = match internal_format {
+            0x83F0 => CompressedFormat::Dxt1,
+            0x83F3 => CompressedFormat::Dxt5,
+            0x9274 => CompressedFormat::Etc2Rgb8,
+            0x9278 => CompressedFormat::Etc2Rgba8,
+            0x93B0 => CompressedFormat::Astc4x4,
+            f => return Err(format_err!("unsupported KTX format 0x{:04X}", f)),
+        };
+        if width == 0 || height == 0 || depth > 1 || array_elements > 0 || faces != 1 {
+            return Err(format_err!("KTX file is not a single 2D texture"));
+        }
+        // each level halves the larger side until it is 1
+        let max_levels = 32 - width.max(height).leading_zeros();
+        if level_count > max_levels {
+            return Err(format_err!(
+                "KTX file has {} mip levels, a {}x{} texture has at most {}",
+                level_count,
+                width,
+                height,
+                max_levels
+            ));
+        }
+
+        // the header fields are untrusted, every offset is checked for overflow
+        let mut levels = Vec::new();
+        let mut offset = KTX_HEADER_SIZE.checked_add(key_value_size);
+        for level in 0..level_count as usize {
+            let start = match offset.and_then(|offset| offset.checked_add(4)) {
+                Some(start) if start <= data.len() => start,
+                _ => return Err(format_err!("KTX file is missing mip level {}", level)),
+            };
+            let size = read(start - 4) as usize;
+            let expected = format
+                .data_size(mip_size((width, height), level))
+                .ok_or_else(|| format_err!("KTX mip level {} is too large", level))?;
+            let end = match start.checked_add(size) {
+                Some(end) if size == expected && end <= data.len() => end,
+                _ => {
+                    return Err(format_err!(
+                        "KTX mip level {} has {} bytes instead of {}",
+                        level,
+                        size,
+                        expected
+                    ))
+                }
+            };
+            levels.push(data[start..end].to_vec());
+            // levels are padded to 4 bytes
+            offset = size
+                .checked_add(3)
+                .and_then(|padded| start.checked_add(padded / 4 * 4));
+        }
+
+        Ok(CompressedImage {
+            format,
+            width,
+            height,
+            levels,
+        })
+    }
+}
+
+fn read_u32_le(b: &[u8]) -> u32 {
+    u32::from(b[0]) | (u32::from(b[1]) << 8) | (u32::from(b[2]) << 16) | (u32::from(b[3]) << 24)
+}
+
+#[test]
+fn png_decoding() {
+    fn encode(color_type: png::ColorType, width: u32, height: u32, data: &[u8]) -> Vec<u8> {
+        let mut png_data = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut png_data, width, height);
+            encoder.set(color_type).set(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(data).unwrap();
+        }
+        png_data
+    }
+
+    let gray = encode(png::ColorType::Grayscale, 2, 1, &[0, 200]);
+    let image = Image::from_png_bytes(&gray).unwrap();
+    assert_eq!((image.width, image.height), (2, 1));
+    assert_eq!(image.data, vec![0, 0, 0, 255, 200, 200, 200, 255]);
+
+    let rgb = encode(png::ColorType::RGB, 1, 2, &[1, 2, 3, 4, 5, 6]);
+    let image = Image::from_png_bytes(&rgb).unwrap();
+    assert_eq!((image.width, image.height), (1, 2));
+    assert_eq!(image.data, vec![1, 2, 3, 255, 4, 5, 6, 255]);
+
+    let rgba = encode(png::ColorType::RGBA, 1, 1, &[9, 8, 7, 6]);
+    assert_eq!(Image::from_png_bytes(&rgba).unwrap().data, vec![9, 8, 7, 6]);
+}
+
+#[test]
+fn premultiplied_alpha() {
+    let image = Image {
+        data: vec![255, 0, 0, 128, 10, 20, 30, 255, 200, 100, 50, 0],
+        width: 3,
+        height: 1,
+    }
+    .premultiplied_alpha();
+    assert_eq!(
+        image.data,
+        vec![128, 0, 0, 128, 10, 20, 30, 255, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn generated_images() {
+    let magenta = Image::solid(3, 2, [255, 0, 255, 255]);
+    assert_eq!((magenta.width, magenta.height), (3, 2));
+    assert_eq!(magenta.data, [255, 0, 255, 255].repeat(6));
+
+    let (a, b) = ([0, 0, 0, 255], [255, 255, 255, 255]);
+    let board = Image::checkerboard(4, 4, 2, a, b);
+    let pixel = |x: u32, y: u32| board.data[((y * 4 + x) * 4) as usize..][..4].to_vec();
+    assert_eq!(pixel(0, 0), a);
+    assert_eq!(pixel(1, 1), a);
+    assert_eq!(pixel(2, 1), b);
+    assert_eq!(pixel(1, 2), b);
+    assert_eq!(pixel(3, 3), a);
+}
+
+#[test]
+fn image_resize() {
+    let solid = Image::solid(4, 4, [10, 20, 30, 255]);
+    for &filter in [ResizeFilter::Nearest, ResizeFilter::Bilinear].iter() {
+        let small = solid.resize(2, 2, filter);
+        assert_eq!((small.width, small.height), (2, 2));
+        assert_eq!(small.data, solid.data[..16].to_vec());
+        let large = solid.resize(7, 5, filter);
+        assert_eq!(large.data, solid.data[..4].repeat(35));
+    }
+
+    // black and white pixels blend in the middle and keep their values at the edges
+    let gradient = Image {
+        data: vec![0, 0, 0, 255, 255, 255, 255, 255],
+        width: 2,
+        height: 1,
+    };
+    let wide = gradient.resize(4, 1, ResizeFilter::Bilinear);
+    assert_eq!(
+        wide.data,
+        vec![0, 0, 0, 255, 64, 64, 64, 255, 191, 191, 191, 255, 255, 255, 255, 255]
+    );
+    let wide = gradient.resize(4, 1, ResizeFilter::Nearest);
+    assert_eq!(wide.data[4..8], [0, 0, 0, 255]);
+    assert_eq!(wide.data[8..12], [255, 255, 255, 255]);
+}
+
+#[test]
+fn image_sub_region() {
+    // 3x2 image where every pixel holds its own index
+    let image = Image {
+        data: (0..6).flat_map(|i| vec![i; 4]).collect(),
+        width: 3,
+        height: 2,
+    };
+
+    let region = image.sub_region(1, 0, 2, 2).unwrap();
+    assert_eq!((region.width, region.height), (2, 2));
+    assert_eq!(
+        region.data,
+        vec![1, 1, 1, 1, 2, 2, 2, 2, 4, 4, 4, 4, 5, 5, 5, 5]
+    );
+
+    let region = image.sub_region(0, 1, 1, 1).unwrap();
+    assert_eq!(region.data, vec![3, 3, 3, 3]);
+
+    assert!(image.sub_region(2, 0, 2, 1).is_err());
+    assert!(image.sub_region(0, 1, 1, 2).is_err());
+    assert!(image.sub_region(1, 0, u32::max_value(), 1).is_err());
+    assert!(image.sub_region(0, u32::max_value(), 1, 2).is_err());
+}
+
+#[test]
+fn image_flip_vertical() {
+    // 2x2 gradient
+    let original = vec![
+        0, 0, 0, 255, 64, 64, 64, 255, 128, 128, 128, 255, 192, 192, 192, 255,
+    ];
+    let mut image = Image {
+        data: original.clone(),
+        width: 2,
+        height: 2,
+    };
+    image.flip_vertical();
+    assert_eq!(&image.data[..8], &original[8..]);
+    image.flip_vertical();
+    assert_eq!(image.data, original);
+
+    // the middle row of an odd height image stays in place
+    let image = Image {
+        data: vec![1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3],
+        width: 1,
+        height: 3,
+    };
+    assert_eq!(
+        image.flipped_vertical().data,
+        vec![3, 3, 3, 3, 2, 2, 2, 2, 1, 1, 1, 1]
+    );
+
+    let mut empty = Image {
+        data: Vec::new(),
+        width: 4,
+        height: 0,
+    };
+    empty.flip_vertical();
+    assert!(empty.data.is_empty());
+}
+
+#[cfg(test)]
+fn ktx(internal_format: u32, size: (u32, u32), levels: &[Vec<u8>], big_endian: bool) -> Vec<u8> {
+    let u32_bytes = |v: u32| {
+        let b = [v as u8, (v >> 8) as u8, (v >> 16) as u8, (v >> 24) as u8];
+        if big_endian {
+            vec![b[3], b[2], b[1], b[0]]
+        } else {
+            b.to_vec()
+        }
+    };
+    let mut data = KTX_IDENTIFIER.to_vec();
+    for &v in &[
+        0x0403_0201,
+        0,
+        1,
+        0,
+        internal_format,
+        0x1908,
+        size.0,
+        size.1,
+        0,
+        0,
+        1,
+    ] {
+        data.extend(u32_bytes(v));
+    }
+    data.extend(u32_bytes(levels.len() as u32));
+    // a key/value pair to skip
+    data.extend(u32_bytes(8));
+    data.extend(u32_bytes(4));
+    data.extend(b"a\0b\0");
+    for level in levels {
+        data.extend(u32_bytes(level.len() as u32));
+        data.extend(level);
+    }
+    data
+}
+
+#[test]
+fn ktx_parsing() {
+    let levels = vec![vec![1; 32], vec![2; 8], vec![3; 8], vec![4; 8]];
+    for &big_endian in &[false, true] {
+        let image =
+            CompressedImage::from_ktx_bytes(&ktx(0x83F0, (8, 8), &levels, big_endian)).unwrap();
+        assert_eq!(image.format, CompressedFormat::Dxt1);
+        assert_eq!((image.width, image.height), (8, 8));
+        assert_eq!(image.levels, levels);
+    }
+
+    let image = CompressedImage::from_ktx_bytes(&ktx(0x9278, (4, 4), &[vec![0; 16]], false));
+    assert_eq!(image.unwrap().format, CompressedFormat::Etc2Rgba8);
+
+    // uncompressed RGBA8, a truncated level and a level of the wrong size
+    assert!(CompressedImage::from_ktx_bytes(&ktx(0x8058, (4, 4), &[vec![0; 64]], false)).is_err());
+    let mut truncated = ktx(0x83F0, (8, 8), &levels, false);
+    truncated.truncate(truncated.len() - 4);
+    assert!(CompressedImage::from_ktx_bytes(&truncated).is_err());
+    assert!(CompressedImage::from_ktx_bytes(&ktx(0x83F0, (8, 8), &[vec![0; 8]], false)).is_err());
+    assert!(CompressedImage::from_ktx_bytes(b"not a ktx file").is_err());
+
+    // malformed header fields
+    let set_field = |data: &mut Vec<u8>, field: usize, value: u32| {
+        let offset = 16 + field * 4;
+        data[offset..offset + 4].copy_from_slice(&[
+            value as u8,
+            (value >> 8) as u8,
+            (value >> 16) as u8,
+            (value >> 24) as u8,
+        ]);
+    };
+    for &(field, value) in &[
+        // numberOfMipmapLevels
+        (10, 0x0FFF_FFFF),
+        (10, 5),
+        (10, u32::max_value()),
+        // bytesOfKeyValueData
+        (11, u32::max_value()),
+        (11, u32::max_value() - 3),
+        (11, 0x1000),
+    ] {
+        let mut data = ktx(0x83F0, (8, 8), &levels, false);
+        set_field(&mut data, field, value);
+        assert!(
+            CompressedImage::from_ktx_bytes(&data).is_err(),
+            "field {} set to {:#x}",
+            field,
+            value
+        );
+    }
+    let one_by_one = vec![vec![0; 8]; 33];
+    assert!(CompressedImage::from_ktx_bytes(&ktx(0x83F0, (1, 1), &one_by_one, false)).is_err());
+    assert!(
+        CompressedImage::from_ktx_bytes(&ktx(0x83F0, (1, 1), &one_by_one[..2], false)).is_err()
+    );
+    assert!(CompressedImage::from_ktx_bytes(&ktx(0x83F0, (1, 1), &one_by_one[..1], false)).is_ok());
+    // an imageSize of the whole address space, after the 8 bytes of key/value data
+    let mut huge_level = ktx(0x83F0, (4, 4), &[vec![0; 8]], false);
+    let size_offset = KTX_HEADER_SIZE + 8;
+    huge_level[size_offset..size_offset + 4].copy_from_slice(&[0xFF; 4]);
+    assert!(CompressedImage::from_ktx_bytes(&huge_level).is_err());
+}
+
+#[cfg(test)]
+fn wait<T>(mut load: Load<T>) -> Result<T, Error> {
+    loop {
+        if let Some(result) = load.take() {
+            return result;
+        }
+        ::std::thread::sleep(::std::time::Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn background_loading() {
+    let image = wait(load_image("examples/assets/embla.png")).unwrap();
+    assert_eq!(image.data.len(), (image.width * image.height * 4) as usize);
+    assert!(wait(load_bytes("examples/assets/missing.png")).is_err());
+    // bytes that aren't a PNG fail when decoding
+    assert!(wait(load_image("examples/assets/loop.wav")).is_err());
+}
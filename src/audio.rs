@@ -1,3 +1,7 @@
+use std::sync::{Arc, Mutex};
+
+use failure::Error;
+
 use super::PlatformContext;
 use platform::audio as audio_impl;
 
@@ -12,3 +16,329 @@ impl AudioDevice {
         AudioDevice(audio_impl::AudioDevice::new(&context.0, channels, cb))
     }
 }
+
+/// Decoded audio kept in memory, cloning only clones a reference to the samples.
+/// Only uncompressed WAV files can be decoded, OGG and other compressed formats
+/// are not supported.
+#[derive(Clone)]
+pub struct Sound {
+    channels: Arc<Vec<Vec<f32>>>,
+    sample_rate: u32,
+}
+
+impl Sound {
+    /// Decodes an uncompressed WAV file with 8, 16, 24 or 32 bit samples
+    pub fn from_wav_bytes(data: &[u8]) -> Result<Sound, Error> {
+        if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+            return Err(format_err!("not a RIFF WAVE file"));
+        }
+
+        let mut format = None;
+        let mut samples = None;
+        let mut offset = 12;
+        while offset + 8 <= data.len() {
+            let id = &data[offset..offset + 4];
+            let len = read_u32(&data[offset + 4..]) as usize;
+            let start = offset + 8;
+            let end = start
+                .checked_add(len)
+                .filter(|&end| end <= data.len())
+                .ok_or_else(|| format_err!("truncated WAV chunk"))?;
+            match id {
+                b"fmt " if len >= 16 => format = Some(&data[start..end]),
+                b"data" => samples = Some(&data[start..end]),
+                _ => {}
+            }
+            // chunks are padded to an even length
+            offset = end + (len & 1);
+        }
+
+        let format = format.ok_or_else(|| format_err!("WAV file has no fmt chunk"))?;
+        let samples = samples.ok_or_else(|| format_err!("WAV file has no data chunk"))?;
+
+        let mut tag = read_u16(&format[0..]);
+        let channel_count = read_u16(&format[2..]) as usize;
+        let sample_rate = read_u32(&format[4..]);
+        let bits = read_u16(&format[14..]);
+        // WAVE_FORMAT_EXTENSIBLE keeps the real format at the start of the sub format guid
+        if tag == 0xFFFE && format.len() >= 26 {
+            tag = read_u16(&format[24..]);
+        }
+        if channel_count == 0 || sample_rate == 0 {
+            return Err(format_err!("WAV file has no channels"));
+        }
+
+        let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+            (1, 8) => |b| (f32::from(b[0]) - 128.0) / 128.0,
+            (1, 16) => |b| f32::from(read_u16(b) as i16) / 32768.0,
+            (1, 24) => |b| (((read_u32(&[0, b[0], b[1], b[2]]) as i32) >> 8) as f32) / 8_388_608.0,
+            (1, 32) => |b| (read_u32(b) as i32) as f32 / 2_147_483_648.0,
+            (3, 32) => |b| f32::from_bits(read_u32(b)),
+            _ => {
+                return Err(format_err!(
+                    "unsupported WAV format {} with {} bit samples",
+                    tag,
+                    bits
+                ))
+            }
+        };
+
+        let frame_size = channel_count * (bits as usize / 8);
+        let mut channels = vec![Vec::with_capacity(samples.len() / frame_size); channel_count];
+        for frame in samples.chunks(frame_size).filter(|f| f.len() == frame_size) {
+            for (channel, sample) in frame.chunks(bits as usize / 8).enumerate() {
+                channels[channel].push(decode(sample));
+            }
+        }
+
+        Ok(Sound {
+            channels: Arc::new(channels),
+            sample_rate,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f32 {
+        self.frames() as f32 / self.sample_rate as f32
+    }
+
+    fn frames(&self) -> usize {
+        self.channels[0].len()
+    }
+}
+
+fn read_u16(b: &[u8]) -> u16 {
+    u16::from(b[0]) | (u16::from(b[1]) << 8)
+}
+
+fn read_u32(b: &[u8]) -> u32 {
+    u32::from(read_u16(b)) | (u32::from(read_u16(&b[2..])) << 16)
+}
+
+/// Identifies a sound started with `Mixer::play`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Playback(u64);
+
+struct Voice {
+    id: Playback,
+    sound: Sound,
+    volume: f32,
+    looping: bool,
+    // in frames of the sound, fractional when the sample rates differ
+    position: f64,
+}
+
+struct Voices {
+    channels: u8,
+    next_id: u64,
+    playing: Vec<Voice>,
+}
+
+impl Voices {
+    fn mix(&mut self, channel: u8, sample_rate: f32, out: &mut [f32]) {
+        for v in out.iter_mut() {
+            *v = 0.0;
+        }
+        for voice in self.playing.iter_mut() {
+            let frames = voice.sound.frames();
+            if frames == 0 {
+                continue;
+            }
+            let samples = &voice.sound.channels[channel as usize % voice.sound.channels.len()];
+            let step = f64::from(voice.sound.sample_rate) / f64::from(sample_rate);
+            for (i, v) in out.iter_mut().enumerate() {
+                let mut position = voice.position + i as f64 * step;
+                if position >= frames as f64 {
+                    if !voice.looping {
+                        break;
+                    }
+                    position %= frames as f64;
+                }
+                *v += samples[position as usize] * voice.volume;
+            }
+        }
+        // loud sounds playing together would wrap around or crackle past full scale
+        for v in out.iter_mut() {
+            *v = v.max(-1.0).min(1.0);
+        }
+
+        // every channel is mixed from the same position, advance after the last one
+        if channel + 1 < self.channels {
+            return;
+        }
+        for voice in self.playing.iter_mut() {
+            let step = f64::from(voice.sound.sample_rate) / f64::from(sample_rate);
+            voice.position += out.len() as f64 * step;
+            let frames = voice.sound.frames() as f64;
+            if voice.looping && frames > 0.0 {
+                voice.position %= frames;
+            }
+        }
+        self.playing
+            .retain(|voice| voice.position < voice.sound.frames() as f64);
+    }
+}
+
+/// Plays any number of sounds at once on top of an `AudioDevice`, the sum of
+/// them is clipped to the -1 to 1 range of the output
+pub struct Mixer {
+    voices: Arc<Mutex<Voices>>,
+    _device: AudioDevice,
+}
+
+impl Mixer {
+    pub fn new(context: &PlatformContext, channels: u8) -> Mixer {
+        let voices = Arc::new(Mutex::new(Voices {
+            channels,
+            next_id: 0,
+            playing: Vec::new(),
+        }));
+        let device_voices = voices.clone();
+        let device = AudioDevice::new(context, channels, move |channel, sample_rate, out| {
+            device_voices.lock().unwrap().mix(channel, sample_rate, out)
+        });
+        Mixer {
+            voices,
+            _device: device,
+        }
+    }
+
+    /// Starts playing `sound`, looping sounds keep playing until stopped
+    pub fn play(&self, sound: &Sound, volume: f32, looping: bool) -> Playback {
+        let mut voices = self.voices.lock().unwrap();
+        let id = Playback(voices.next_id);
+        voices.next_id += 1;
+        voices.playing.push(Voice {
+            id,
+            sound: sound.clone(),
+            volume,
+            looping,
+            position: 0.0,
+        });
+        id
+    }
+
+    pub fn set_volume(&self, playback: Playback, volume: f32) {
+        let mut voices = self.voices.lock().unwrap();
+        for voice in voices.playing.iter_mut().filter(|v| v.id == playback) {
+            voice.volume = volume;
+        }
+    }
+
+    pub fn stop(&self, playback: Playback) {
+        self.voices
+            .lock()
+            .unwrap()
+            .playing
+            .retain(|v| v.id != playback);
+    }
+
+    pub fn stop_all(&self) {
+        self.voices.lock().unwrap().playing.clear();
+    }
+
+    pub fn is_playing(&self, playback: Playback) -> bool {
+        self.voices
+            .lock()
+            .unwrap()
+            .playing
+            .iter()
+            .any(|v| v.id == playback)
+    }
+}
+
+#[cfg(test)]
+fn wav_16(channels: u16, sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+    let mut data = Vec::new();
+    let u16_bytes = |v: u16| vec![v as u8, (v >> 8) as u8];
+    let u32_bytes = |v: u32| vec![v as u8, (v >> 8) as u8, (v >> 16) as u8, (v >> 24) as u8];
+    data.extend(b"RIFF");
+    data.extend(u32_bytes(36 + samples.len() as u32 * 2));
+    data.extend(b"WAVEfmt ");
+    data.extend(u32_bytes(16));
+    data.extend(u16_bytes(1));
+    data.extend(u16_bytes(channels));
+    data.extend(u32_bytes(sample_rate));
+    data.extend(u32_bytes(sample_rate * u32::from(channels) * 2));
+    data.extend(u16_bytes(channels * 2));
+    data.extend(u16_bytes(16));
+    data.extend(b"data");
+    data.extend(u32_bytes(samples.len() as u32 * 2));
+    for &s in samples {
+        data.extend(u16_bytes(s as u16));
+    }
+    data
+}
+
+#[test]
+fn decode_wav() {
+    let sound = Sound::from_wav_bytes(&wav_16(2, 8000, &[16384, -16384, 0, 32767])).unwrap();
+    assert_eq!(sound.sample_rate(), 8000);
+    assert_eq!(
+        *sound.channels,
+        vec![vec![0.5, 0.0], vec![-0.5, 32767.0 / 32768.0]]
+    );
+    assert!(Sound::from_wav_bytes(b"RIFF\0\0\0\0WAVE").is_err());
+}
+
+#[test]
+fn mix_voices() {
+    let sound = Sound::from_wav_bytes(&wav_16(1, 4, &[8192, 16384])).unwrap();
+    let mut voices = Voices {
+        channels: 2,
+        next_id: 2,
+        playing: vec![
+            Voice {
+                id: Playback(0),
+                sound: sound.clone(),
+                volume: 1.0,
+                looping: false,
+                position: 0.0,
+            },
+            Voice {
+                id: Playback(1),
+                sound,
+                volume: 0.5,
+                looping: true,
+                position: 0.0,
+            },
+        ],
+    };
+
+    let mut out = [1.0; 3];
+    voices.mix(0, 4.0, &mut out);
+    assert_eq!(out, [0.375, 0.75, 0.125]);
+    // mono sounds are played on every channel
+    voices.mix(1, 4.0, &mut out);
+    assert_eq!(out, [0.375, 0.75, 0.125]);
+
+    // the one shot sound has finished, the looping one wrapped around
+    assert_eq!(voices.playing.len(), 1);
+    voices.mix(0, 4.0, &mut out);
+    assert_eq!(out, [0.25, 0.125, 0.25]);
+}
+
+#[test]
+fn mix_clips() {
+    let loud = Sound::from_wav_bytes(&wav_16(1, 4, &[24576, -24576])).unwrap();
+    let voice = |id| Voice {
+        id: Playback(id),
+        sound: loud.clone(),
+        volume: 1.0,
+        looping: true,
+        position: 0.0,
+    };
+    let mut voices = Voices {
+        channels: 1,
+        next_id: 2,
+        playing: vec![voice(0), voice(1)],
+    };
+
+    let mut out = [0.0; 2];
+    voices.mix(0, 4.0, &mut out);
+    assert_eq!(out, [1.0, -1.0]);
+}
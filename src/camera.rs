@@ -0,0 +1,144 @@
+use math::Vec2;
+
+/// Converts a point in screen pixels with the origin at the top left, like the
+/// coordinates of `InputEvent::MouseMove`, to NDC with y pointing up
+pub fn screen_to_ndc(x: f32, y: f32, width: f32, height: f32) -> Vec2<f32> {
+    Vec2::new(2.0 * x / width - 1.0, 1.0 - 2.0 * y / height)
+}
+
+/// The inverse of `screen_to_ndc`
+pub fn ndc_to_screen(x: f32, y: f32, width: f32, height: f32) -> Vec2<f32> {
+    Vec2::new((x + 1.0) * width / 2.0, (1.0 - y) * height / 2.0)
+}
+
+/// A 2D view into the world, `position` is the world point shown at the center of the screen
+#[derive(Clone, Copy, Debug)]
+pub struct Camera2D {
+    pub position: Vec2<f32>,
+    /// Screen pixels per world unit
+    pub zoom: f32,
+    /// Counter clockwise rotation of the camera in radians
+    pub rotation: f32,
+}
+
+impl Camera2D {
+    pub fn new(position: Vec2<f32>) -> Camera2D {
+        Camera2D {
+            position,
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// Column major view projection matrix from world coordinates to NDC, ready to be
+    /// passed as a `Uniform::Mat4`
+    pub fn ortho_matrix(&self, screen_width: f32, screen_height: f32) -> [f32; 16] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let sx = 2.0 * self.zoom / screen_width;
+        let sy = 2.0 * self.zoom / screen_height;
+
+        // the world rotates the opposite way of the camera
+        let (m00, m01) = (sx * cos, sx * sin);
+        let (m10, m11) = (-sy * sin, sy * cos);
+        let tx = -(m00 * self.position.x + m01 * self.position.y);
+        let ty = -(m10 * self.position.x + m11 * self.position.y);
+
+        [
+            m00, m10, 0.0, 0.0, //
+            m01, m11, 0.0, 0.0, //
+            0.0, 0.0, 1.0, 0.0, //
+            tx, ty, 0.0, 1.0,
+        ]
+    }
+
+    /// The world point under screen pixel `screen`, the inverse of `ortho_matrix`
+    /// for picking with mouse coordinates
+    pub fn screen_to_world(
+        &self,
+        screen: Vec2<f32>,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> Vec2<f32> {
+        let ndc = screen_to_ndc(screen.x, screen.y, screen_width, screen_height);
+        let (sin, cos) = self.rotation.sin_cos();
+        let dx = ndc.x * screen_width / (2.0 * self.zoom);
+        let dy = ndc.y * screen_height / (2.0 * self.zoom);
+        Vec2::new(
+            self.position.x + cos * dx - sin * dy,
+            self.position.y + sin * dx + cos * dy,
+        )
+    }
+
+    /// The screen pixel `world` is drawn at
+    pub fn world_to_screen(
+        &self,
+        world: Vec2<f32>,
+        screen_width: f32,
+        screen_height: f32,
+    ) -> Vec2<f32> {
+        let m = self.ortho_matrix(screen_width, screen_height);
+        let x = m[0] * world.x + m[4] * world.y + m[12];
+        let y = m[1] * world.x + m[5] * world.y + m[13];
+        ndc_to_screen(x, y, screen_width, screen_height)
+    }
+}
+
+impl Default for Camera2D {
+    fn default() -> Camera2D {
+        Camera2D::new(Vec2::zero())
+    }
+}
+
+#[test]
+fn ortho_matrix() {
+    use std::f32;
+
+    fn project(m: &[f32; 16], x: f32, y: f32) -> (f32, f32) {
+        (m[0] * x + m[4] * y + m[12], m[1] * x + m[5] * y + m[13])
+    }
+    fn assert_near(a: (f32, f32), b: (f32, f32)) {
+        assert!(
+            (a.0 - b.0).abs() < 1e-5 && (a.1 - b.1).abs() < 1e-5,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    let mut camera = Camera2D::new(Vec2::new(100.0, 50.0));
+    let m = camera.ortho_matrix(800.0, 600.0);
+    assert_near(project(&m, 100.0, 50.0), (0.0, 0.0));
+    assert_near(project(&m, 500.0, 350.0), (1.0, 1.0));
+    assert_near(project(&m, -300.0, -250.0), (-1.0, -1.0));
+
+    camera.zoom = 2.0;
+    let m = camera.ortho_matrix(800.0, 600.0);
+    assert_near(project(&m, 300.0, 50.0), (1.0, 0.0));
+
+    // turning the camera left moves a point above it to the right of the screen
+    camera.zoom = 1.0;
+    camera.rotation = f32::consts::FRAC_PI_2;
+    let m = camera.ortho_matrix(800.0, 600.0);
+    assert_near(project(&m, 100.0, 450.0), (1.0, 0.0));
+}
+
+#[test]
+fn screen_world_round_trip() {
+    let camera = Camera2D {
+        position: Vec2::new(-20.0, 35.0),
+        zoom: 2.5,
+        rotation: 0.7,
+    };
+    for &(x, y) in &[(0.0, 0.0), (640.0, 360.0), (17.0, 701.0), (1279.0, 3.0)] {
+        let world = camera.screen_to_world(Vec2::new(x, y), 1280.0, 720.0);
+        let screen = camera.world_to_screen(world, 1280.0, 720.0);
+        assert!((screen.x - x).abs() < 1e-3 && (screen.y - y).abs() < 1e-3);
+    }
+
+    // the top left corner is the top left of NDC, the center shows the camera position
+    let ndc = screen_to_ndc(0.0, 0.0, 1280.0, 720.0);
+    assert_eq!((ndc.x, ndc.y), (-1.0, 1.0));
+    let center =
+        Camera2D::new(Vec2::new(4.0, 2.0)).screen_to_world(Vec2::new(400.0, 300.0), 800.0, 600.0);
+    assert_eq!((center.x, center.y), (4.0, 2.0));
+}
@@ -0,0 +1,225 @@
+use failure::Error;
+use std::collections::HashMap;
+
+use assets::{load_image, Image, Load};
+use rendering::{Renderer, Texture, TextureFiltering, TextureWrap};
+
+/// Textures loaded by key, so that every part of a game drawing the same image
+/// shares one texture. Keys are paths for `load`, natively relative to the
+/// working directory and on the web to the page, or any id for `insert_image`.
+pub struct Assets {
+    textures: Cache<Texture>,
+    filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
+}
+
+impl Assets {
+    /// `filtering` and `wrap` are used for every texture created from a
+    /// loaded image
+    pub fn new(filtering: Option<TextureFiltering>, wrap: Option<TextureWrap>) -> Assets {
+        Assets {
+            textures: Cache::new(),
+            filtering,
+            wrap,
+        }
+    }
+
+    /// Starts loading the PNG at `path` in the background, does nothing when it
+    /// is loaded or loading already. The texture is available from `get` after
+    /// the `update` following its arrival.
+    pub fn load(&mut self, path: &str) {
+        self.textures.load(path);
+    }
+
+    /// Creates the texture for `key` from an image that is already in memory,
+    /// the cached texture is returned when there is one
+    pub fn insert_image(
+        &mut self,
+        renderer: &Renderer,
+        key: &str,
+        image: &Image,
+    ) -> Result<Texture, Error> {
+        let (filtering, wrap) = (self.filtering, self.wrap);
+        self.textures.insert_with(key, || {
+            renderer.create_texture_from_image(image, filtering, wrap)
+        })
+    }
+
+    /// Creates textures for the images that have finished loading, call it every
+    /// frame while anything is loading. A failed load is returned as an error
+    /// naming its key and forgotten, so that it can be loaded again.
+    pub fn update(&mut self, renderer: &Renderer) -> Result<(), Error> {
+        let (filtering, wrap) = (self.filtering, self.wrap);
+        self.textures
+            .update(|image| renderer.create_texture_from_image(image, filtering, wrap))
+    }
+
+    pub fn get(&self, key: &str) -> Option<Texture> {
+        self.textures.get(key)
+    }
+
+    /// Whether every load has finished, successfully or not
+    pub fn is_loaded(&self) -> bool {
+        self.textures.loading().next().is_none()
+    }
+
+    /// Forgets the texture of `key`, it is freed once the last clone of it is
+    /// dropped
+    pub fn remove(&mut self, key: &str) {
+        self.textures.entries.remove(key);
+    }
+}
+
+enum Entry<T> {
+    Loading(Load<Image>),
+    Ready(T),
+}
+
+// keeps what is created from loaded images apart from the renderer, so that the
+// caching works the same for anything made from an image
+struct Cache<T> {
+    entries: HashMap<String, Entry<T>>,
+}
+
+impl<T: Clone> Cache<T> {
+    fn new() -> Cache<T> {
+        Cache {
+            entries: HashMap::new(),
+        }
+    }
+
+    fn load(&mut self, path: &str) {
+        if !self.entries.contains_key(path) {
+            self.entries
+                .insert(path.to_string(), Entry::Loading(load_image(path)));
+        }
+    }
+
+    fn insert_with<F>(&mut self, key: &str, create: F) -> Result<T, Error>
+    where
+        F: FnOnce() -> Result<T, Error>,
+    {
+        if let Some(value) = self.get(key) {
+            return Ok(value);
+        }
+        let value = create()?;
+        self.entries
+            .insert(key.to_string(), Entry::Ready(value.clone()));
+        Ok(value)
+    }
+
+    fn update<F>(&mut self, mut create: F) -> Result<(), Error>
+    where
+        F: FnMut(&Image) -> Result<T, Error>,
+    {
+        let loading: Vec<String> = self.loading().cloned().collect();
+        for key in loading {
+            let loaded = match self.entries.get_mut(&key) {
+                Some(&mut Entry::Loading(ref mut load)) => load.take(),
+                _ => None,
+            };
+            match loaded {
+                Some(Ok(image)) => {
+                    let value = create(&image).map_err(|e| {
+                        self.entries.remove(&key);
+                        format_err!("could not load {}: {}", key, e)
+                    })?;
+                    self.entries.insert(key, Entry::Ready(value));
+                }
+                Some(Err(e)) => {
+                    self.entries.remove(&key);
+                    return Err(format_err!("could not load {}: {}", key, e));
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Option<T> {
+        match self.entries.get(key) {
+            Some(&Entry::Ready(ref value)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    fn loading<'a>(&'a self) -> impl Iterator<Item = &'a String> + 'a {
+        self.entries.iter().filter_map(|(key, entry)| match entry {
+            &Entry::Loading(_) => Some(key),
+            &Entry::Ready(_) => None,
+        })
+    }
+}
+
+#[test]
+fn cached_loads() {
+    use std::rc::Rc;
+    use std::thread;
+    use std::time::Duration;
+
+    let mut cache = Cache::new();
+    let mut created = 0;
+    let path = "examples/assets/embla.png";
+    cache.load(path);
+    cache.load(path);
+    while cache.get(path).is_none() {
+        thread::sleep(Duration::from_millis(1));
+        cache
+            .update(|image| {
+                created += 1;
+                Ok(Rc::new(image.width))
+            })
+            .unwrap();
+    }
+    cache.load(path);
+    assert_eq!(created, 1);
+    assert!(Rc::ptr_eq(
+        &cache.get(path).unwrap(),
+        &cache.get(path).unwrap()
+    ));
+
+    let inserted = cache.insert_with("id", || Ok(Rc::new(1))).unwrap();
+    let again = cache.insert_with("id", || Ok(Rc::new(2))).unwrap();
+    assert!(Rc::ptr_eq(&inserted, &again));
+
+    cache.load("examples/assets/missing.png");
+    while cache.loading().next().is_some() {
+        thread::sleep(Duration::from_millis(1));
+        if cache.update(|_| Ok(Rc::new(0))).is_err() {
+            break;
+        }
+    }
+    assert!(cache.get("examples/assets/missing.png").is_none());
+    assert!(cache.loading().next().is_none());
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn shared_textures() {
+    use platform::with_test_renderer;
+    use std::thread;
+    use std::time::Duration;
+
+    with_test_renderer(|renderer| {
+        let mut assets = Assets::new(None, None);
+        let path = "examples/assets/embla.png";
+        assets.load(path);
+        assets.load(path);
+        while assets.get(path).is_none() {
+            thread::sleep(Duration::from_millis(1));
+            assets.update(renderer).unwrap();
+        }
+        let texture = assets.get(path).unwrap();
+
+        // a second load of the same path reuses the GL texture
+        assets.load(path);
+        assets.update(renderer).unwrap();
+        assert!(assets.get(path).unwrap() == texture);
+        assert!(
+            renderer
+                .create_texture_from_image(&Image::solid(1, 1, [0; 4]), None, None)
+                .unwrap()
+                != texture
+        );
+    });
+}
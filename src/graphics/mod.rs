@@ -1,5 +1,13 @@
+mod asset_cache;
+mod sprite_batch;
+mod stencil_mask;
 mod texture_atlas;
 mod texture_image;
 
-pub use self::texture_atlas::TextureAtlas;
+pub use self::asset_cache::Assets;
+pub use self::sprite_batch::{
+    default_sprite_program, quad, SpriteBatch, SpriteBatchPass, SpriteVertex,
+};
+pub use self::stencil_mask::StencilMask;
+pub use self::texture_atlas::{AtlasRegion, TextureAtlas};
 pub use self::texture_image::TextureImage;
@@ -0,0 +1,9 @@
+varying highp vec2 v_uv;
+varying lowp vec4 v_color;
+
+uniform sampler2D texture;
+
+void main()
+{
+    gl_FragColor = texture2D(texture, v_uv) * v_color;
+}
@@ -0,0 +1,269 @@
+use failure::Error;
+
+use math::Rect;
+use rendering::{
+    BufferUsage, Program, Renderer, Texture, Uniform, Vertex, VertexAttributeType, VertexBuffer,
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpriteVertex {
+    pub position: (f32, f32),
+    pub uv: (f32, f32),
+    pub color: (f32, f32, f32, f32),
+}
+
+impl Vertex for SpriteVertex {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![
+            ("position".into(), 2, VertexAttributeType::Float),
+            ("uv".into(), 2, VertexAttributeType::Float),
+            ("color".into(), 4, VertexAttributeType::Float),
+        ]
+    }
+}
+
+// without #version and precision, which ShaderOptions fills in for each backend
+const SPRITE_VERTEX_SHADER: &'static str = include_str!("shaders/sprite_vertex_shader.glsl");
+const SPRITE_FRAGMENT_SHADER: &'static str = include_str!("shaders/sprite_fragment_shader.glsl");
+
+/// A program for drawing `SpriteVertex` vertices in pixel coordinates with y
+/// pointing up, sampling the `texture` uniform and multiplying it with the vertex
+/// color. `screen_size` starts out as the size of the viewport, set it again
+/// after the window is resized.
+pub fn default_sprite_program(renderer: &Renderer) -> Result<Program<SpriteVertex>, Error> {
+    let mut program = renderer.create_labeled_program(
+        "default sprite program",
+        SPRITE_VERTEX_SHADER,
+        SPRITE_FRAGMENT_SHADER,
+    )?;
+    let (width, height) = renderer.screen_size();
+    program.set_uniform("screen_size", Uniform::Vec2((width as f32, height as f32)));
+    Ok(program)
+}
+
+/// Accumulates sprites sharing a texture and program and draws them with as few
+/// draw calls as possible.
+pub struct SpriteBatch {
+    vertex_buffer: VertexBuffer,
+    vertices: Vec<SpriteVertex>,
+    indices: Vec<u32>,
+    texture: Option<Texture>,
+    texture_uniform: String,
+    capacity: usize,
+    draw_calls: usize,
+}
+
+impl SpriteBatch {
+    /// Creates a batch which flushes after `capacity` sprites. The texture of each
+    /// flush is bound to the `texture` uniform of the program.
+    pub fn new(renderer: &Renderer, capacity: usize) -> Result<SpriteBatch, Error> {
+        Ok(SpriteBatch {
+            vertex_buffer: renderer.create_vertex_buffer(BufferUsage::Stream)?,
+            vertices: Vec::with_capacity(capacity * 4),
+            indices: Vec::with_capacity(capacity * 6),
+            texture: None,
+            texture_uniform: "texture".into(),
+            capacity,
+            draw_calls: 0,
+        })
+    }
+
+    pub fn set_texture_uniform(&mut self, name: &str) {
+        self.texture_uniform = name.into();
+    }
+
+    pub fn begin<'a, 'r>(
+        &'a mut self,
+        renderer: &'a Renderer<'r>,
+        program: &'a mut Program<SpriteVertex>,
+    ) -> SpriteBatchPass<'a, 'r> {
+        self.vertices.clear();
+        self.indices.clear();
+        self.texture = None;
+        self.draw_calls = 0;
+        SpriteBatchPass {
+            batch: self,
+            renderer,
+            program,
+        }
+    }
+
+    /// Number of draw calls issued by the last pass
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
+    fn flush(
+        &mut self,
+        renderer: &Renderer,
+        program: &mut Program<SpriteVertex>,
+    ) -> Result<(), Error> {
+        if let Some(ref texture) = self.texture {
+            if !self.indices.is_empty() {
+                program.set_uniform(&self.texture_uniform, Uniform::Texture(texture.clone()));
+                renderer.render_indexed(
+                    &self.vertex_buffer,
+                    program,
+                    &self.vertices,
+                    &self.indices,
+                )?;
+                self.draw_calls += 1;
+            }
+        }
+        self.vertices.clear();
+        self.indices.clear();
+        Ok(())
+    }
+}
+
+/// A batch in progress, started with `SpriteBatch::begin`
+pub struct SpriteBatchPass<'a, 'r: 'a> {
+    batch: &'a mut SpriteBatch,
+    renderer: &'a Renderer<'r>,
+    program: &'a mut Program<SpriteVertex>,
+}
+
+impl<'a, 'r> SpriteBatchPass<'a, 'r> {
+    /// Queues a sprite covering `dest`, sampling the `src` region of the texture in
+    /// normalized texture coordinates. Queued sprites are flushed when the texture
+    /// changes or the batch is full.
+    pub fn draw(
+        &mut self,
+        texture: &Texture,
+        dest: Rect<f32>,
+        src: Rect<f32>,
+        tint: (f32, f32, f32, f32),
+    ) -> Result<(), Error> {
+        let texture_changed = match self.batch.texture {
+            Some(ref current) => current != texture,
+            None => true,
+        };
+        if texture_changed || self.batch.indices.len() / 6 >= self.batch.capacity {
+            self.batch.flush(self.renderer, self.program)?;
+        }
+        if texture_changed {
+            self.batch.texture = Some(texture.clone());
+        }
+
+        push_quad(
+            &mut self.batch.vertices,
+            &mut self.batch.indices,
+            dest,
+            src,
+            tint,
+        );
+        Ok(())
+    }
+
+    pub fn end(self) -> Result<(), Error> {
+        self.batch.flush(self.renderer, self.program)
+    }
+}
+
+/// The two triangles of a sprite covering `dest` and sampling the `src` region in
+/// normalized texture coordinates, for drawing a single quad with
+/// `Renderer::render_vertices` without a `SpriteBatch`. Untinted when `tint` is
+/// `None`.
+pub fn quad(
+    dest: Rect<f32>,
+    src: Rect<f32>,
+    tint: Option<(f32, f32, f32, f32)>,
+) -> Vec<SpriteVertex> {
+    let corners = quad_corners(&dest, &src, tint.unwrap_or((1.0, 1.0, 1.0, 1.0)));
+    QUAD_INDICES.iter().map(|&i| corners[i as usize]).collect()
+}
+
+// two counter clockwise triangles over the corners of `quad_corners`
+const QUAD_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+fn quad_corners(
+    dest: &Rect<f32>,
+    src: &Rect<f32>,
+    color: (f32, f32, f32, f32),
+) -> [SpriteVertex; 4] {
+    let corner = |position, uv| SpriteVertex {
+        position,
+        uv,
+        color,
+    };
+    [
+        corner((dest.min.x, dest.min.y), (src.min.x, src.min.y)),
+        corner((dest.max.x, dest.min.y), (src.max.x, src.min.y)),
+        corner((dest.max.x, dest.max.y), (src.max.x, src.max.y)),
+        corner((dest.min.x, dest.max.y), (src.min.x, src.max.y)),
+    ]
+}
+
+fn push_quad(
+    vertices: &mut Vec<SpriteVertex>,
+    indices: &mut Vec<u32>,
+    dest: Rect<f32>,
+    src: Rect<f32>,
+    color: (f32, f32, f32, f32),
+) {
+    let base = vertices.len() as u32;
+    vertices.extend_from_slice(&quad_corners(&dest, &src, color));
+    indices.extend(QUAD_INDICES.iter().map(|&i| base + i));
+}
+
+#[test]
+fn sprite_batch_quads() {
+    use math::Vec2;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let white = (1.0, 1.0, 1.0, 1.0);
+    let dest = Rect::new(Vec2::new(10.0, 20.0), Vec2::new(30.0, 40.0));
+    let src = Rect::new(Vec2::new(0.0, 0.0), Vec2::new(0.5, 1.0));
+    push_quad(
+        &mut vertices,
+        &mut indices,
+        dest.clone(),
+        src.clone(),
+        white,
+    );
+    push_quad(&mut vertices, &mut indices, dest, src, white);
+
+    assert_eq!(vertices.len(), 8);
+    assert_eq!(indices, vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7]);
+    assert_eq!(vertices[0].position, (10.0, 20.0));
+    assert_eq!(vertices[2].position, (30.0, 40.0));
+    assert_eq!(vertices[1].uv, (0.5, 0.0));
+    assert_eq!(vertices[3].uv, (0.0, 1.0));
+}
+
+#[test]
+fn single_quad() {
+    use math::Vec2;
+
+    let dest = Rect::new(Vec2::new(10.0, 20.0), Vec2::new(30.0, 40.0));
+    let src = Rect::new(Vec2::new(0.25, 0.0), Vec2::new(0.5, 1.0));
+    let vertices = quad(dest, src, None);
+
+    let positions: Vec<_> = vertices.iter().map(|v| v.position).collect();
+    assert_eq!(
+        positions,
+        vec![
+            (10.0, 20.0),
+            (30.0, 20.0),
+            (30.0, 40.0),
+            (10.0, 20.0),
+            (30.0, 40.0),
+            (10.0, 40.0),
+        ]
+    );
+    let uvs: Vec<_> = vertices.iter().map(|v| v.uv).collect();
+    assert_eq!(
+        uvs,
+        vec![
+            (0.25, 0.0),
+            (0.5, 0.0),
+            (0.5, 1.0),
+            (0.25, 0.0),
+            (0.5, 1.0),
+            (0.25, 1.0),
+        ]
+    );
+    assert!(vertices.iter().all(|v| v.color == (1.0, 1.0, 1.0, 1.0)));
+}
@@ -0,0 +1,184 @@
+use failure::Error;
+
+use rendering::{RenderState, Renderer, StencilFunc, StencilOp};
+
+// one stencil bit per mask
+const MAX_DEPTH: u8 = 8;
+
+/// Clips drawing to arbitrary shapes such as rounded panels or a circular minimap
+/// through the stencil buffer. Whatever is drawn between `begin_mask` and
+/// `end_mask` becomes the shape of a mask instead of showing up, after which
+/// draws only reach the pixels inside every pushed mask until it is popped.
+///
+/// Masks nest up to 8 deep, each one using a stencil bit and clipped by the
+/// masks below it. Every pushed mask has to be popped before the stencil buffer
+/// is used for anything else.
+#[derive(Debug, Default)]
+pub struct StencilMask {
+    depth: u8,
+    // the color mask from before `begin_mask`, put back by `end_mask`
+    color_mask: Option<(bool, bool, bool, bool)>,
+}
+
+impl StencilMask {
+    pub fn new() -> StencilMask {
+        StencilMask {
+            depth: 0,
+            color_mask: None,
+        }
+    }
+
+    /// Number of masks currently applied
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Starts drawing the shape of a new mask, only the parts inside the masks
+    /// that are already pushed count
+    pub fn begin_mask(&mut self, renderer: &Renderer) -> Result<(), Error> {
+        if self.depth >= MAX_DEPTH {
+            return Err(format_err!("stencil masks nest at most {} deep", MAX_DEPTH));
+        }
+
+        let bit = 1 << self.depth;
+        let parents = depth_bits(self.depth);
+        self.color_mask = Some(RenderState::capture(renderer).color_mask);
+        renderer.set_stencil_test(true);
+        renderer.set_color_write(false);
+        renderer.set_stencil_write_mask(bit);
+        renderer.set_stencil_func(StencilFunc::Equal, parents | bit, parents);
+        renderer.set_stencil_op(StencilOp::Keep, StencilOp::Keep, StencilOp::Replace);
+        Ok(())
+    }
+
+    /// Finishes the shape started with `begin_mask` and restores the color mask
+    /// it replaced, later draws are clipped to the shape
+    pub fn end_mask(&mut self, renderer: &Renderer) {
+        self.depth = (self.depth + 1).min(MAX_DEPTH);
+        let (red, green, blue, alpha) = self.color_mask.take().unwrap_or((true, true, true, true));
+        renderer.set_color_mask(red, green, blue, alpha);
+        self.apply(renderer);
+    }
+
+    /// Removes the innermost mask by clearing its stencil bit, which is limited
+    /// by the scissor rectangle like any clear
+    pub fn pop_mask(&mut self, renderer: &Renderer) {
+        if self.depth == 0 {
+            return;
+        }
+        self.depth -= 1;
+        renderer.set_stencil_write_mask(1 << self.depth);
+        renderer.clear_stencil(0);
+        self.apply(renderer);
+    }
+
+    fn apply(&self, renderer: &Renderer) {
+        renderer.set_stencil_op(StencilOp::Keep, StencilOp::Keep, StencilOp::Keep);
+        if self.depth == 0 {
+            renderer.set_stencil_write_mask(0xFF);
+            renderer.set_stencil_test(false);
+        } else {
+            // draws inside the masks must not change them
+            renderer.set_stencil_write_mask(0);
+            let bits = depth_bits(self.depth);
+            renderer.set_stencil_func(StencilFunc::Equal, bits, bits);
+        }
+    }
+}
+
+// stencil bits of the first `depth` masks, all set inside of them
+fn depth_bits(depth: u8) -> u8 {
+    ((1u16 << depth) - 1) as u8
+}
+
+#[test]
+fn mask_bits() {
+    assert_eq!(depth_bits(0), 0);
+    assert_eq!(depth_bits(1), 0b1);
+    assert_eq!(depth_bits(3), 0b111);
+    assert_eq!(depth_bits(MAX_DEPTH), 0xFF);
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn triangle_mask() {
+    use platform::with_test_renderer;
+    use rendering::{BufferUsage, Uniform, Vertex, VertexAttributeType};
+
+    #[allow(dead_code)]
+    struct Position(f32, f32);
+    impl Vertex for Position {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![("position".to_string(), 2, VertexAttributeType::Float)]
+        }
+    }
+
+    with_test_renderer(|renderer| {
+        // the window may have no stencil buffer, framebuffers always do
+        let framebuffer = renderer.create_framebuffer((64, 64), None).unwrap();
+        let vs = "attribute vec2 position;\n\
+                  void main() { gl_Position = vec4(position, 0.0, 1.0); }";
+        let fs = "uniform vec4 color;\nvoid main() { gl_FragColor = color; }";
+        let mut program = renderer.create_program(vs, fs).unwrap();
+        program.set_uniform("color", Uniform::Vec4((0.0, 1.0, 0.0, 1.0)));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        renderer.bind_framebuffer(Some(&framebuffer));
+        renderer.clear_all(Some((1.0, 0.0, 0.0, 1.0)));
+        renderer.set_color_mask(true, true, true, false);
+
+        // the lower left half of the screen
+        let mut mask = StencilMask::new();
+        mask.begin_mask(renderer).unwrap();
+        let triangle = [
+            Position(-1.0, -1.0),
+            Position(1.0, -1.0),
+            Position(-1.0, 1.0),
+        ];
+        renderer
+            .render_vertices(&buffer, &program, &triangle)
+            .unwrap();
+        mask.end_mask(renderer);
+        assert_eq!(
+            RenderState::capture(renderer).color_mask,
+            (true, true, true, false)
+        );
+
+        let screen = [
+            Position(-1.0, -1.0),
+            Position(1.0, -1.0),
+            Position(1.0, 1.0),
+            Position(-1.0, -1.0),
+            Position(1.0, 1.0),
+            Position(-1.0, 1.0),
+        ];
+        renderer
+            .render_vertices(&buffer, &program, &screen)
+            .unwrap();
+        mask.pop_mask(renderer);
+        renderer.set_color_mask(true, true, true, true);
+
+        let pixels = renderer.read_pixels(0, 0, 64, 64).unwrap();
+        renderer.bind_framebuffer(None);
+        // rows start at the top, so the triangle covers the pixels below the diagonal
+        for &(x, y) in &[(4, 60), (40, 60), (4, 20), (20, 40)] {
+            let i = (y * 64 + x) * 4;
+            assert_eq!(
+                &pixels.data[i..i + 4],
+                &[0, 255, 0, 255],
+                "inside {}, {}",
+                x,
+                y
+            );
+        }
+        for &(x, y) in &[(60, 4), (40, 20), (20, 4), (60, 40)] {
+            let i = (y * 64 + x) * 4;
+            assert_eq!(
+                &pixels.data[i..i + 4],
+                &[255, 0, 0, 255],
+                "outside {}, {}",
+                x,
+                y
+            );
+        }
+    });
+}
@@ -2,65 +2,186 @@ use failure::Error;
 use std::collections::HashMap;
 
 use super::texture_image::TextureImage;
+use assets::Image;
+use math::{Rect, Vec2};
+use rendering::{Renderer, Texture, TextureFiltering, TextureFormat, TextureWrap};
 
+// empty pixels kept around every image so that filtering doesn't bleed neighbours in
+const PADDING: u32 = 1;
+
+/// Where an image was placed in a `TextureAtlas`, both in pixels and in texture
+/// coordinates normalized to the size of the atlas
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasRegion {
+    pub position: (u32, u32),
+    pub size: (u32, u32),
+    pub uv_min: (f32, f32),
+    pub uv_max: (f32, f32),
+}
+
+impl AtlasRegion {
+    /// The normalized texture coordinates as expected by `SpriteBatchPass::draw`
+    pub fn uv_rect(&self) -> Rect<f32> {
+        Rect::new(
+            Vec2::new(self.uv_min.0, self.uv_min.1),
+            Vec2::new(self.uv_max.0, self.uv_max.1),
+        )
+    }
+}
+
+/// A texture that images are packed into as they are added, so that many small
+/// images can be drawn from a single texture. Images are never removed.
 pub struct TextureAtlas {
-    size: (u32, u32),
-    texture_blocks: HashMap<u64, [u32; 4]>,
+    texture: Texture,
+    packer: ShelfPacker,
+    images: HashMap<u64, AtlasRegion>,
 }
+
 impl TextureAtlas {
-    pub fn new(size: (u32, u32)) -> TextureAtlas {
-        TextureAtlas {
-            size: size,
-            texture_blocks: HashMap::<u64, [u32; 4]>::new(),
+    pub fn new(
+        renderer: &Renderer,
+        size: (u32, u32),
+        filtering: Option<TextureFiltering>,
+    ) -> Result<TextureAtlas, Error> {
+        let texture = renderer.create_texture(
+            size,
+            TextureFormat::Rgba8,
+            filtering,
+            Some(TextureWrap::ClampToEdge),
+        )?;
+        Ok(TextureAtlas {
+            texture,
+            packer: ShelfPacker::new(size, PADDING),
+            images: HashMap::new(),
+        })
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    /// Uploads `image` into a free part of the atlas, fails once there is no
+    /// room left for it
+    pub fn add(&mut self, image: &Image) -> Result<AtlasRegion, Error> {
+        let atlas_size = self.packer.size;
+        let position = self.packer.pack(image.width, image.height).ok_or_else(|| {
+            format_err!(
+                "no room for a {}x{} image in the {}x{} texture atlas",
+                image.width,
+                image.height,
+                atlas_size.0,
+                atlas_size.1
+            )
+        })?;
+        self.texture.set_region(image, position)?;
+
+        let (width, height) = (atlas_size.0 as f32, atlas_size.1 as f32);
+        Ok(AtlasRegion {
+            position,
+            size: (image.width, image.height),
+            uv_min: (position.0 as f32 / width, position.1 as f32 / height),
+            uv_max: (
+                (position.0 + image.width) as f32 / width,
+                (position.1 + image.height) as f32 / height,
+            ),
+        })
+    }
+
+    /// Like `add`, but a texture image that was already added is only looked up
+    pub fn add_texture_image(&mut self, image: &TextureImage) -> Result<AtlasRegion, Error> {
+        if let Some(region) = self.region(image) {
+            return Ok(region);
+        }
+        let region = self.add(image.image())?;
+        self.images.insert(image.id(), region);
+        Ok(region)
+    }
+
+    pub fn region(&self, image: &TextureImage) -> Option<AtlasRegion> {
+        self.images.get(&image.id()).cloned()
+    }
+}
+
+// Places rectangles left to right on horizontal shelves, each as tall as the
+// first rectangle put on it. New rectangles go on the shelf that wastes the
+// least height, or on a new shelf when none fit.
+struct ShelfPacker {
+    size: (u32, u32),
+    padding: u32,
+    // (y, height, used width)
+    shelves: Vec<(u32, u32, u32)>,
+}
+
+impl ShelfPacker {
+    fn new(size: (u32, u32), padding: u32) -> ShelfPacker {
+        ShelfPacker {
+            size,
+            padding,
+            shelves: Vec::new(),
         }
     }
-    pub fn get_texture_block(&mut self, texture: &TextureImage) -> Option<[u32; 4]> {
-        self.texture_blocks.get(&texture.id()).map(|r| *r)
+
+    fn pack(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (padded_width, padded_height) = (width + self.padding, height + self.padding);
+        let size = self.size;
+
+        let best = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.1 >= padded_height && shelf.2 + width <= size.0)
+            .min_by_key(|shelf| shelf.1 - padded_height);
+        if let Some(shelf) = best {
+            let position = (shelf.2, shelf.0);
+            shelf.2 += padded_width;
+            return Some(position);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map(|&(y, height, _)| y + height)
+            .unwrap_or(0);
+        if width > size.0 || y + height > size.1 {
+            return None;
+        }
+        self.shelves.push((y, padded_height, padded_width));
+        Some((0, y))
     }
-    pub fn add_texture(&mut self, texture: &TextureImage) -> Result<[u32; 4], Error> {
-        let pad = |rect: [u32; 4]| [rect[0] - 1, rect[1] - 1, rect[2] + 1, rect[3] + 1];
-        let unpad = |rect: [u32; 4]| [rect[0] + 1, rect[1] + 1, rect[2] - 1, rect[3] - 1];
-        let size = (texture.width(), texture.height());
-        let tex_coords = {
-            let mut y = 1;
-            let mut x = 1;
-            let mut coords = None;
-            'outer: while y < self.size.1 - size.1 {
-                let mut next_y = self.size.1;
-                while x < self.size.0 - size.0 {
-                    let t1 = pad([x, y, x + size.0, y + size.1]);
-                    let overlap = self.texture_blocks.values().filter(|t2| {
-                        !(t1[0] >= t2[2] || t2[2] <= t2[0] || t1[1] >= t2[3] || t1[3] <= t2[1])
-                    });
-                    let mut any_intersect = false;
-                    // on the x axis, skip past any overlapping textures
-                    // on the y axis, jump up to the lowest top edge in the row
-                    for rect in overlap {
-                        if rect[3] < next_y {
-                            next_y = rect[3] + 1;
-                        }
-                        if rect[2] > x {
-                            x = rect[2] + 1;
-                        }
-                        any_intersect = true;
-                    }
-                    if !any_intersect {
-                        coords = Some(unpad(t1));
-                        break 'outer;
-                    }
-                }
-                x = 0;
-                y = next_y;
-            }
-            coords
-        };
-
-        match tex_coords {
-            Some(coords) => {
-                self.texture_blocks.insert(texture.id(), coords);
-                Ok(coords)
-            }
-            None => Err(format_err!("Texture atlas overflow")),
+}
+
+#[test]
+fn shelf_packing() {
+    let mut packer = ShelfPacker::new((64, 64), 1);
+    let sizes = [
+        (30, 20),
+        (30, 10),
+        (10, 20),
+        (64, 10),
+        (20, 20),
+        (40, 5),
+        (5, 5),
+    ];
+    let mut packed: Vec<[u32; 4]> = Vec::new();
+    for &(width, height) in sizes.iter() {
+        let (x, y) = packer
+            .pack(width, height)
+            .expect("atlas should have room left");
+        let rect = [x, y, x + width, y + height];
+        assert!(
+            rect[2] <= 64 && rect[3] <= 64,
+            "{:?} is out of bounds",
+            rect
+        );
+        for other in packed.iter() {
+            let overlaps = rect[0] < other[2]
+                && other[0] < rect[2]
+                && rect[1] < other[3]
+                && other[1] < rect[3];
+            assert!(!overlaps, "{:?} overlaps {:?}", rect, other);
         }
+        packed.push(rect);
     }
+
+    assert_eq!(packer.pack(65, 1), None);
+    assert_eq!(packer.pack(64, 64), None);
 }
@@ -1,6 +1,8 @@
 use math::Vec2;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
+/// Physical keys, named after their label on a US layout so that games bound to
+/// e.g. WASD keep working on other layouts
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Key {
     Unknown,
@@ -235,6 +237,9 @@ pub enum Key {
     KbdIllumUp,
     Eject,
     Sleep,
+    /// A key without a variant, holding the platform's raw code, an SDL scancode
+    /// natively and the US layout keyCode on the web
+    Other(i32),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -245,9 +250,58 @@ pub enum MouseButton {
     Right,
 }
 
-#[derive(Clone, Debug)]
+/// State of the modifier keys, `meta` is the command key on macOS and the windows
+/// key elsewhere
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+/// Button and axis values of a gamepad in the layout reported by the platform.
+/// Buttons range from 0 to 1 and axes from -1 to 1.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    pub buttons: Vec<f32>,
+    pub axes: Vec<f32>,
+}
+
+impl GamepadState {
+    pub fn button(&self, index: usize) -> f32 {
+        self.buttons.get(index).cloned().unwrap_or(0.0)
+    }
+
+    /// Value of an axis with values within `dead_zone` of the center snapped to 0
+    /// and the remaining range rescaled to start at 0. Sticks rarely rest exactly
+    /// at 0, a dead zone of around 0.15 works for most controllers.
+    pub fn axis(&self, index: usize, dead_zone: f32) -> f32 {
+        let value = self.axes.get(index).cloned().unwrap_or(0.0);
+        if value.abs() <= dead_zone {
+            0.0
+        } else {
+            value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+        }
+    }
+}
+
+/// Identifies a touch point for as long as the finger stays on the screen
+pub type TouchId = i64;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum InputEvent {
     MouseMove(i32, i32),
+    /// Relative mouse movement in pixels, keeps being reported while the pointer
+    /// is locked
+    MouseDelta(i32, i32),
+    PointerLockChanged(bool),
+    MouseEnter,
+    /// Sent when the pointer leaves the window, every held mouse button is
+    /// released since the button up may never arrive
+    MouseLeave,
+    /// Sent when the window enters or leaves fullscreen, followed by a `Resize`
+    FullscreenChanged(bool),
     MouseDown {
         button: MouseButton,
         position: (i32, i32),
@@ -256,8 +310,48 @@ pub enum InputEvent {
         button: MouseButton,
         position: (i32, i32),
     },
+    /// Horizontal and vertical scroll in pixels, positive values scroll right and
+    /// down
+    MouseWheel(f32, f32),
     KeyDown(Key),
+    /// Sent instead of `KeyDown` for the repeated presses of a held key
+    KeyRepeat(Key),
     KeyUp(Key),
+    TouchStart {
+        id: TouchId,
+        position: (i32, i32),
+    },
+    TouchMove {
+        id: TouchId,
+        position: (i32, i32),
+    },
+    /// Sent when a finger is lifted or the touch is cancelled
+    TouchEnd {
+        id: TouchId,
+        position: (i32, i32),
+    },
+    GamepadConnected(u32),
+    GamepadDisconnected(u32),
+    /// Text typed by the user, sent once for every composed character or IME
+    /// composition, alongside the key events of the keys pressed
+    TextInput(String),
+    /// New size of the window's drawable area in pixels, also sent once at startup
+    Resize(i32, i32),
+    /// Sent right before the key or mouse button event during which the modifier
+    /// keys changed
+    ModifiersChanged(Modifiers),
+    /// Sent with `false` when the window or browser tab loses focus and `true`
+    /// when it gets it back, every held key and mouse button is released on blur
+    Focus(bool),
+    /// Sent when the browser takes the WebGL context away, for example when a
+    /// mobile tab is backgrounded or the GPU is reset. Every texture, buffer,
+    /// program and framebuffer is gone and draws do nothing until the context
+    /// is restored.
+    ContextLost,
+    /// Sent once the context is back after `ContextLost`, with nothing created
+    /// before the loss. Create the GPU resources again from here, with the same
+    /// code that created them at startup.
+    ContextRestored,
     Exit,
 }
 
@@ -266,6 +360,15 @@ struct InputState {
     pub keys_down: HashSet<Key>,
     pub mouse_buttons_down: HashSet<MouseButton>,
     pub mouse_position: Vec2<i32>,
+    pub mouse_wheel: Vec2<f32>,
+    pub mouse_delta: Vec2<i32>,
+    pub mouse_inside: bool,
+    pub pointer_locked: bool,
+    pub focused: bool,
+    pub fullscreen: bool,
+    pub context_lost: bool,
+    pub modifiers: Modifiers,
+    pub touches: HashMap<TouchId, Vec2<i32>>,
 }
 
 impl InputState {
@@ -274,6 +377,15 @@ impl InputState {
             keys_down: HashSet::new(),
             mouse_buttons_down: HashSet::new(),
             mouse_position: Vec2::zero(),
+            mouse_wheel: Vec2::zero(),
+            mouse_delta: Vec2::zero(),
+            mouse_inside: true,
+            pointer_locked: false,
+            focused: true,
+            fullscreen: false,
+            context_lost: false,
+            modifiers: Modifiers::default(),
+            touches: HashMap::new(),
         }
     }
 }
@@ -282,7 +394,13 @@ impl InputState {
 pub struct Input {
     last_state: InputState,
     current_state: InputState,
+    gamepads: Vec<Option<GamepadState>>,
+    text_input: String,
+    keys_repeated: HashSet<Key>,
     events: Vec<InputEvent>,
+    // everything since the last call to the deprecated `events`, which used to
+    // accumulate until drained
+    undrained_events: Vec<InputEvent>,
 }
 
 impl Input {
@@ -290,18 +408,32 @@ impl Input {
         Input {
             last_state: InputState::new(),
             current_state: InputState::new(),
+            gamepads: Vec::new(),
+            text_input: String::new(),
+            keys_repeated: HashSet::new(),
             events: Vec::new(),
+            undrained_events: Vec::new(),
         }
     }
 
     pub fn update(&mut self, events: Vec<InputEvent>) {
         self.last_state = self.current_state.clone();
+        self.current_state.mouse_wheel = Vec2::zero();
+        self.current_state.mouse_delta = Vec2::zero();
+        self.text_input.clear();
+        self.keys_repeated.clear();
+        self.events.clear();
 
         for e in events {
             match e {
                 InputEvent::KeyDown(key) => {
                     self.current_state.keys_down.insert(key);
                 }
+                InputEvent::KeyRepeat(key) => {
+                    // a key held since before the window got focus only repeats
+                    self.current_state.keys_down.insert(key);
+                    self.keys_repeated.insert(key);
+                }
                 InputEvent::KeyUp(key) => {
                     self.current_state.keys_down.remove(&key);
                 }
@@ -314,11 +446,72 @@ impl Input {
                 InputEvent::MouseMove(x, y) => {
                     self.current_state.mouse_position = Vec2::new(x, y);
                 }
+                InputEvent::MouseDelta(dx, dy) => {
+                    self.current_state.mouse_delta += Vec2::new(dx, dy);
+                }
+                InputEvent::MouseEnter => {
+                    self.current_state.mouse_inside = true;
+                }
+                InputEvent::MouseLeave => {
+                    self.current_state.mouse_inside = false;
+                    self.current_state.mouse_buttons_down.clear();
+                }
+                InputEvent::PointerLockChanged(locked) => {
+                    self.current_state.pointer_locked = locked;
+                }
+                InputEvent::FullscreenChanged(fullscreen) => {
+                    self.current_state.fullscreen = fullscreen;
+                }
+                InputEvent::ContextLost => {
+                    self.current_state.context_lost = true;
+                }
+                InputEvent::ContextRestored => {
+                    self.current_state.context_lost = false;
+                }
+                InputEvent::MouseWheel(dx, dy) => {
+                    self.current_state.mouse_wheel += Vec2::new(dx, dy);
+                }
+                InputEvent::TouchStart { id, position }
+                | InputEvent::TouchMove { id, position } => {
+                    self.current_state
+                        .touches
+                        .insert(id, Vec2::new(position.0, position.1));
+                }
+                InputEvent::TouchEnd { id, .. } => {
+                    self.current_state.touches.remove(&id);
+                }
+                InputEvent::TextInput(ref text) => {
+                    self.text_input.push_str(text);
+                }
+                InputEvent::ModifiersChanged(modifiers) => {
+                    self.current_state.modifiers = modifiers;
+                }
+                InputEvent::Focus(focused) => {
+                    self.current_state.focused = focused;
+                    // the key up events are sent to whatever has focus instead
+                    if !focused {
+                        self.current_state.keys_down.clear();
+                        self.current_state.mouse_buttons_down.clear();
+                        self.current_state.modifiers = Modifiers::default();
+                    }
+                }
                 _ => {}
             }
 
             self.events.push(e);
         }
+        self.undrained_events.extend(self.events.iter().cloned());
+    }
+
+    /// Replaces the polled gamepad states, indexed by gamepad
+    pub fn update_gamepads(&mut self, gamepads: Vec<Option<GamepadState>>) {
+        self.gamepads = gamepads;
+    }
+
+    pub fn gamepad(&self, index: u32) -> Option<&GamepadState> {
+        self.gamepads
+            .get(index as usize)
+            .and_then(|gamepad| gamepad.as_ref())
     }
 
     pub fn key_is_down(&self, key: &Key) -> bool {
@@ -329,6 +522,12 @@ impl Input {
         !self.last_state.keys_down.contains(key) && self.current_state.keys_down.contains(key)
     }
 
+    /// True when the key was pressed or repeated since the last update, for
+    /// keys that act again while held such as backspace
+    pub fn key_is_repeated(&self, key: &Key) -> bool {
+        self.key_is_pressed(key) || self.keys_repeated.contains(key)
+    }
+
     pub fn key_is_released(&self, key: &Key) -> bool {
         self.last_state.keys_down.contains(key) && !self.current_state.keys_down.contains(key)
     }
@@ -351,7 +550,257 @@ impl Input {
         self.current_state.mouse_position
     }
 
+    /// Positions of the fingers currently on the screen, in no particular order
+    pub fn touches<'a>(&'a self) -> impl Iterator<Item = (TouchId, Vec2<i32>)> + 'a {
+        self.current_state
+            .touches
+            .iter()
+            .map(|(&id, &position)| (id, position))
+    }
+
+    pub fn touch_position(&self, id: TouchId) -> Option<Vec2<i32>> {
+        self.current_state.touches.get(&id).cloned()
+    }
+
+    /// Text typed since the last update
+    pub fn text_input(&self) -> &str {
+        &self.text_input
+    }
+
+    /// Modifier keys held during the last key or mouse button event
+    pub fn modifiers(&self) -> Modifiers {
+        self.current_state.modifiers
+    }
+
+    /// Relative mouse movement since the last update, use this rather than
+    /// `mouse_position` while the pointer is locked
+    pub fn mouse_delta(&self) -> Vec2<i32> {
+        self.current_state.mouse_delta
+    }
+
+    /// Whether the pointer is over the window, assumed to be until the first
+    /// `MouseLeave`
+    pub fn mouse_inside(&self) -> bool {
+        self.current_state.mouse_inside
+    }
+
+    pub fn focused(&self) -> bool {
+        self.current_state.focused
+    }
+
+    /// Whether the WebGL context is lost, between `InputEvent::ContextLost` and
+    /// `InputEvent::ContextRestored`. Games can pause and skip rendering while
+    /// it is.
+    pub fn context_lost(&self) -> bool {
+        self.current_state.context_lost
+    }
+
+    pub fn pointer_locked(&self) -> bool {
+        self.current_state.pointer_locked
+    }
+
+    pub fn fullscreen(&self) -> bool {
+        self.current_state.fullscreen
+    }
+
+    /// Scroll distance accumulated since the last update
+    pub fn mouse_wheel(&self) -> Vec2<f32> {
+        self.current_state.mouse_wheel
+    }
+
+    /// The events that arrived since the last frame in the order they happened,
+    /// for handling input as a queue instead of through the state queries. The
+    /// queue is replaced every frame, events that are not polled are dropped.
+    pub fn poll_events(&self) -> &[InputEvent] {
+        &self.events
+    }
+
+    /// Takes every event that arrived since the last call out of the queue, over
+    /// as many frames as that spans
+    #[deprecated(note = "use poll_events, which holds the events of the current frame")]
     pub fn events<'a>(&'a mut self) -> impl Iterator<Item = InputEvent> + 'a {
-        self.events.drain(0..)
+        self.undrained_events.drain(0..)
     }
 }
+
+#[test]
+fn gamepad_dead_zone() {
+    let gamepad = GamepadState {
+        buttons: vec![1.0],
+        axes: vec![0.1, -0.1, 0.55, -1.0],
+    };
+    assert_eq!(gamepad.axis(0, 0.2), 0.0);
+    assert_eq!(gamepad.axis(1, 0.2), 0.0);
+    assert!((gamepad.axis(2, 0.1) - 0.5).abs() < 1e-6);
+    assert_eq!(gamepad.axis(3, 0.2), -1.0);
+    assert_eq!(gamepad.axis(4, 0.2), 0.0);
+    assert_eq!(gamepad.button(0), 1.0);
+    assert_eq!(gamepad.button(1), 0.0);
+}
+
+#[test]
+fn blur_releases_keys() {
+    let mut input = Input::new();
+    input.update(vec![
+        InputEvent::KeyDown(Key::W),
+        InputEvent::MouseDown {
+            button: MouseButton::Left,
+            position: (0, 0),
+        },
+    ]);
+    assert!(input.key_is_down(&Key::W));
+
+    input.update(vec![InputEvent::Focus(false)]);
+    assert!(!input.focused());
+    assert!(input.key_is_released(&Key::W));
+    assert!(input.mouse_button_is_released(&MouseButton::Left));
+
+    input.update(vec![InputEvent::Focus(true)]);
+    assert!(input.focused());
+    assert!(!input.key_is_down(&Key::W));
+}
+
+#[test]
+fn context_loss() {
+    let mut input = Input::new();
+    assert!(!input.context_lost());
+    input.update(vec![InputEvent::ContextLost]);
+    assert!(input.context_lost());
+    // stays lost over frames without events
+    input.update(vec![]);
+    assert!(input.context_lost());
+    input.update(vec![InputEvent::ContextRestored]);
+    assert!(!input.context_lost());
+    assert_eq!(input.poll_events(), &[InputEvent::ContextRestored]);
+}
+
+#[test]
+fn event_queue() {
+    let mut input = Input::new();
+    let events = vec![
+        InputEvent::MouseMove(4, 2),
+        InputEvent::KeyDown(Key::A),
+        InputEvent::MouseDown {
+            button: MouseButton::Left,
+            position: (4, 2),
+        },
+        InputEvent::KeyUp(Key::A),
+    ];
+    input.update(events.clone());
+    assert_eq!(input.poll_events(), &events[..]);
+
+    // only the events of the latest frame are kept
+    input.update(vec![InputEvent::MouseLeave]);
+    assert_eq!(input.poll_events(), &[InputEvent::MouseLeave]);
+    input.update(vec![]);
+    assert!(input.poll_events().is_empty());
+}
+
+#[test]
+#[allow(deprecated)]
+fn drained_events_accumulate() {
+    let mut input = Input::new();
+    input.update(vec![InputEvent::KeyDown(Key::A)]);
+    input.update(vec![InputEvent::KeyUp(Key::A)]);
+    assert_eq!(
+        input.events().collect::<Vec<_>>(),
+        vec![InputEvent::KeyDown(Key::A), InputEvent::KeyUp(Key::A)]
+    );
+    assert_eq!(input.events().count(), 0);
+    // draining leaves the current frame to poll_events
+    assert_eq!(input.poll_events(), &[InputEvent::KeyUp(Key::A)]);
+}
+
+#[test]
+fn key_repeat() {
+    let mut input = Input::new();
+    input.update(vec![InputEvent::KeyDown(Key::Backspace)]);
+    assert!(input.key_is_pressed(&Key::Backspace));
+    assert!(input.key_is_repeated(&Key::Backspace));
+
+    input.update(vec![]);
+    assert!(input.key_is_down(&Key::Backspace));
+    assert!(!input.key_is_repeated(&Key::Backspace));
+
+    input.update(vec![InputEvent::KeyRepeat(Key::Backspace)]);
+    assert!(input.key_is_down(&Key::Backspace));
+    assert!(!input.key_is_pressed(&Key::Backspace));
+    assert!(input.key_is_repeated(&Key::Backspace));
+
+    input.update(vec![InputEvent::KeyUp(Key::Backspace)]);
+    assert!(!input.key_is_down(&Key::Backspace));
+    assert!(input.key_is_released(&Key::Backspace));
+    assert!(!input.key_is_repeated(&Key::Backspace));
+}
+
+#[test]
+fn modifier_state() {
+    let mut input = Input::new();
+    let shift = Modifiers {
+        shift: true,
+        ..Modifiers::default()
+    };
+    input.update(vec![
+        InputEvent::ModifiersChanged(shift),
+        InputEvent::MouseDown {
+            button: MouseButton::Left,
+            position: (0, 0),
+        },
+    ]);
+    assert_eq!(input.modifiers(), shift);
+
+    // held over frames without events
+    input.update(vec![]);
+    assert!(input.modifiers().shift);
+
+    input.update(vec![InputEvent::ModifiersChanged(Modifiers::default())]);
+    assert_eq!(input.modifiers(), Modifiers::default());
+
+    input.update(vec![
+        InputEvent::ModifiersChanged(shift),
+        InputEvent::Focus(false),
+    ]);
+    assert_eq!(input.modifiers(), Modifiers::default());
+}
+
+#[test]
+fn text_input_per_frame() {
+    let mut input = Input::new();
+    input.update(vec![
+        InputEvent::KeyDown(Key::A),
+        InputEvent::TextInput("a".to_string()),
+        InputEvent::TextInput("é".to_string()),
+        // a finished IME composition arrives as one event
+        InputEvent::TextInput("日本".to_string()),
+    ]);
+    assert_eq!(input.text_input(), "aé日本");
+    assert!(input.key_is_pressed(&Key::A));
+
+    input.update(vec![]);
+    assert_eq!(input.text_input(), "");
+    assert!(input.key_is_down(&Key::A));
+}
+
+#[test]
+fn mouse_leave_releases_buttons() {
+    let mut input = Input::new();
+    assert!(input.mouse_inside());
+    input.update(vec![
+        InputEvent::MouseDown {
+            button: MouseButton::Left,
+            position: (3, 4),
+        },
+        InputEvent::KeyDown(Key::Space),
+    ]);
+    assert!(input.mouse_button_is_down(&MouseButton::Left));
+
+    input.update(vec![InputEvent::MouseLeave]);
+    assert!(!input.mouse_inside());
+    assert!(input.mouse_button_is_released(&MouseButton::Left));
+    // keys stay down, the window still has focus
+    assert!(input.key_is_down(&Key::Space));
+
+    input.update(vec![InputEvent::MouseEnter]);
+    assert!(input.mouse_inside());
+    assert!(!input.mouse_button_is_down(&MouseButton::Left));
+}
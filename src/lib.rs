@@ -6,7 +6,11 @@ extern crate serde_derive;
 extern crate bincode;
 #[macro_use]
 extern crate failure;
+extern crate embla_derive;
 extern crate num_traits;
+// lets the derive macros refer to ::embla in the tests of this crate
+#[cfg(test)]
+extern crate self as embla;
 
 // Web backend
 #[cfg(target_arch = "wasm32")]
@@ -26,17 +30,25 @@ mod platform;
 
 pub mod assets;
 pub mod audio;
+pub mod camera;
 pub mod graphics;
 pub mod input;
 pub mod math;
 pub mod rendering;
+pub mod time;
 pub mod util;
 pub mod window;
 
+/// Traits both platform backends implement, for code that is generic over the
+/// backend such as a game loop that is also run against a mock in tests
+pub mod backend {
+    pub use platform::{Input, Renderer, Window};
+}
+
 use failure::Error;
 use input::Input;
 
-use audio::AudioDevice;
+use audio::{AudioDevice, Mixer};
 use window::{Window, WindowSettings};
 
 pub struct PlatformContext(platform::Context);
@@ -52,12 +64,17 @@ impl PlatformContext {
     ) -> AudioDevice {
         AudioDevice::new(self, channels, cb)
     }
+    pub fn mixer(&self, channels: u8) -> Mixer {
+        Mixer::new(self, channels)
+    }
 }
 
 pub fn rand() -> f32 {
     platform::rand::rand()
 }
 
+/// Runs the main loop returned by `app_factory` once per frame, with the
+/// seconds since the previous frame and the input of this one
 pub fn init<
     F: FnOnce(PlatformContext) -> T,
     T: FnMut(f64, &Input) -> Result<(), Error> + 'static,
@@ -1,3 +1,20 @@
+// Both backends provide the same modules with the same function signatures and
+// one of them is picked at compile time. Their windows and renderers implement
+// the `Window` and `Renderer` traits below, which the wrappers in `rendering`
+// and `window` call them through, so the same code builds for native and wasm
+// without any cfg of its own. Games reach the traits through `embla::backend`.
+use std::mem;
+
+use failure::Error;
+
+use input::{InputEvent, Key, MouseButton};
+use math::Vec2;
+use rendering::{
+    BlendMode, BufferUsage, Index, Primitive, RenderError, TextureFiltering, TextureFormat,
+    TextureWrap, Vertex,
+};
+use window::CursorStyle;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod native;
 #[cfg(not(target_arch = "wasm32"))]
@@ -8,4 +25,364 @@ mod web;
 #[cfg(target_arch = "wasm32")]
 use self::web as platform_impl;
 
-pub use self::platform_impl::{audio, init, rand, rendering, window, Context};
+/// The canvas on the web, an SDL window natively
+pub trait Window {
+    type GLContext;
+
+    /// Size of the drawable area in pixels
+    fn size(&self) -> (u32, u32);
+    /// Size in screen points, CSS pixels on the web
+    fn logical_size(&self) -> (f32, f32);
+    fn set_title(&self, title: &str) -> Result<(), Error>;
+    fn set_cursor_visible(&self, visible: bool) -> Result<(), Error>;
+    fn set_cursor_style(&self, style: CursorStyle) -> Result<(), Error>;
+    fn set_fullscreen(&self, fullscreen: bool) -> Result<(), Error>;
+    fn set_pointer_lock(&self, locked: bool);
+    /// Loses or restores the GL context on purpose, where contexts can be lost
+    fn set_context_lost(&self, lost: bool);
+    fn gl_create_context(&self) -> Result<Self::GLContext, Error>;
+    fn gl_set_current(&self, gl_context: &Self::GLContext);
+}
+
+/// Draws with the GL context last made current through `Window::gl_set_current`
+pub trait Renderer {
+    type Texture;
+    type Program;
+    type VertexBuffer;
+
+    fn create_texture(
+        &self,
+        size: (u32, u32),
+        format: TextureFormat,
+        filtering: Option<TextureFiltering>,
+        wrap: Option<TextureWrap>,
+    ) -> Result<Self::Texture, RenderError>;
+    fn create_program(&self, label: &str, vs: &str, fs: &str)
+        -> Result<Self::Program, RenderError>;
+    fn create_vertex_buffer(&self, usage: BufferUsage) -> Result<Self::VertexBuffer, Error>;
+    fn render_vertices<V: Vertex>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &Self::VertexBuffer,
+        program: &Self::Program,
+        vertices: &[V],
+    ) -> Result<(), Error>;
+    fn render_indexed<V: Vertex, I: Index>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &Self::VertexBuffer,
+        program: &Self::Program,
+        vertices: &[V],
+        indices: &[I],
+    ) -> Result<(), Error>;
+    fn set_blend_mode(&self, mode: BlendMode);
+    /// `(x, y, width, height)` with `(x, y)` the bottom left corner, like GL
+    fn viewport(&self) -> (i32, i32, u32, u32);
+    fn set_viewport(&self, x: i32, y: i32, width: u32, height: u32);
+    /// RGBA8 pixels of the bound target, bottom row first
+    fn read_pixels(&self, x: i32, y: i32, width: u32, height: u32) -> Result<Vec<u8>, Error>;
+    fn clear(&self, color: Option<(f32, f32, f32, f32)>, depth: Option<f32>, stencil: Option<u8>);
+}
+
+/// Input state of a frame. Both backends turn their events into `InputEvent`s for
+/// the same `input::Input`, other implementations can replay recorded input
+pub trait Input {
+    fn poll_events(&self) -> &[InputEvent];
+    fn key_is_down(&self, key: &Key) -> bool;
+    fn key_is_pressed(&self, key: &Key) -> bool;
+    fn mouse_button_is_down(&self, button: &MouseButton) -> bool;
+    fn mouse_position(&self) -> Vec2<i32>;
+}
+
+impl Input for ::input::Input {
+    fn poll_events(&self) -> &[InputEvent] {
+        ::input::Input::poll_events(self)
+    }
+    fn key_is_down(&self, key: &Key) -> bool {
+        ::input::Input::key_is_down(self, key)
+    }
+    fn key_is_pressed(&self, key: &Key) -> bool {
+        ::input::Input::key_is_pressed(self, key)
+    }
+    fn mouse_button_is_down(&self, button: &MouseButton) -> bool {
+        ::input::Input::mouse_button_is_down(self, button)
+    }
+    fn mouse_position(&self) -> Vec2<i32> {
+        ::input::Input::mouse_position(self)
+    }
+}
+
+// Shader source prefixed with line numbers, included in compile errors so that
+// the lines reported by the driver can be found
+pub fn numbered_source(src: &str) -> String {
+    src.lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:4} | {}\n", i + 1, line))
+        .collect()
+}
+
+// Errors when `value` is past `max`, a limit of the GPU such as the number of
+// texture units. Going past those fails silently or with a GL error that only
+// debug builds check for.
+pub fn check_limit(what: &'static str, value: u32, max: u32) -> Result<(), RenderError> {
+    if value > max {
+        return Err(RenderError::LimitExceeded { what, value, max });
+    }
+    Ok(())
+}
+
+// GL and WebGL use the same values for the blend factors
+const ZERO: u32 = 0;
+const ONE: u32 = 1;
+const SRC_ALPHA: u32 = 0x0302;
+const ONE_MINUS_SRC_ALPHA: u32 = 0x0303;
+const DST_COLOR: u32 = 0x0306;
+
+// Source and destination factor of every mode but None, which disables blending
+const BLEND_FACTORS: [(BlendMode, (u32, u32)); 4] = [
+    (BlendMode::Alpha, (SRC_ALPHA, ONE_MINUS_SRC_ALPHA)),
+    (BlendMode::Additive, (SRC_ALPHA, ONE)),
+    (BlendMode::Multiply, (DST_COLOR, ZERO)),
+    (BlendMode::PremultipliedAlpha, (ONE, ONE_MINUS_SRC_ALPHA)),
+];
+
+pub fn blend_factors(mode: BlendMode) -> Option<(u32, u32)> {
+    BLEND_FACTORS
+        .iter()
+        .find(|&&(m, _)| m == mode)
+        .map(|&(_, factors)| factors)
+}
+
+// The mode with these factors, None when they were set by other GL code
+pub fn blend_mode_from_factors(src: u32, dst: u32) -> Option<BlendMode> {
+    BLEND_FACTORS
+        .iter()
+        .find(|&&(_, factors)| factors == (src, dst))
+        .map(|&(mode, _)| mode)
+}
+
+// Uniform values of a program in the order they were first set, setting a
+// uniform again replaces its value. Each backend stores its own uniform type.
+pub struct Uniforms<U> {
+    values: Vec<(String, U)>,
+}
+
+impl<U> Default for Uniforms<U> {
+    fn default() -> Uniforms<U> {
+        Uniforms { values: Vec::new() }
+    }
+}
+
+impl<U> Uniforms<U> {
+    pub fn set(&mut self, name: &str, value: U) {
+        match self.values.iter_mut().find(|u| u.0 == name) {
+            Some(existing) => existing.1 = value,
+            None => self.values.push((name.into(), value)),
+        }
+    }
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &(String, U)> {
+        self.values.iter()
+    }
+}
+
+// Errors when the attributes of `V` don't add up to its stride, or the stride to
+// the size of `V`, either way the vertex data would be read with the wrong layout
+fn check_vertex_layout<V: Vertex>() -> Result<(), Error> {
+    let attributes_size: usize = V::attributes()
+        .iter()
+        .map(|&(_, count, ty)| count * ty.size())
+        .sum();
+    if attributes_size != V::stride() {
+        return Err(format_err!(
+            "vertex attributes take up {} bytes but the stride is {}",
+            attributes_size,
+            V::stride()
+        ));
+    }
+    if V::stride() != mem::size_of::<V>() {
+        return Err(format_err!(
+            "vertex stride is {} bytes but the vertex struct is {}",
+            V::stride(),
+            mem::size_of::<V>()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+pub use self::native::test_renderer::with_test_renderer;
+pub use self::platform_impl::{audio, fetch, init, rand, rendering, time, window, Context};
+
+#[test]
+fn limits() {
+    let cases = [
+        (0, 16, true),
+        (16, 16, true),
+        (17, 16, false),
+        (0, 0, true),
+        (1, 0, false),
+        (u32::max_value(), u32::max_value(), true),
+    ];
+    for &(value, max, ok) in cases.iter() {
+        assert_eq!(
+            check_limit("samples", value, max).is_ok(),
+            ok,
+            "{} of {}",
+            value,
+            max
+        );
+    }
+
+    let err = check_limit("texture size", 8192, 4096).unwrap_err();
+    match err {
+        RenderError::LimitExceeded { what, value, max } => {
+            assert_eq!((what, value, max), ("texture size", 8192, 4096))
+        }
+        other => panic!("unexpected error {:?}", other),
+    }
+    assert_eq!(
+        err.to_string(),
+        "texture size: 8192 requested but the GPU supports at most 4096"
+    );
+}
+
+#[test]
+fn vertex_layout() {
+    use rendering::VertexAttributeType;
+
+    #[allow(dead_code)]
+    struct Position(f32, f32);
+    impl Vertex for Position {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![("position".into(), 2, VertexAttributeType::Float)]
+        }
+    }
+    assert!(check_vertex_layout::<Position>().is_ok());
+
+    #[allow(dead_code)]
+    struct WrongStride(f32, f32);
+    impl Vertex for WrongStride {
+        fn stride() -> usize {
+            12
+        }
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![("position".into(), 2, VertexAttributeType::Float)]
+        }
+    }
+    let err = check_vertex_layout::<WrongStride>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "vertex attributes take up 8 bytes but the stride is 12"
+    );
+
+    #[allow(dead_code)]
+    struct WrongCount(f32, f32);
+    impl Vertex for WrongCount {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![("position".into(), 3, VertexAttributeType::Float)]
+        }
+    }
+    let err = check_vertex_layout::<WrongCount>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "vertex stride is 12 bytes but the vertex struct is 8"
+    );
+}
+
+#[test]
+fn uniform_replace_and_clear() {
+    let mut uniforms = Uniforms::default();
+    uniforms.set("time", 1.0);
+    uniforms.set("scale", 2.0);
+    uniforms.set("time", 3.0);
+    let values: Vec<_> = uniforms.iter().cloned().collect();
+    assert_eq!(
+        values,
+        vec![("time".to_string(), 3.0), ("scale".to_string(), 2.0)]
+    );
+
+    uniforms.clear();
+    assert_eq!(uniforms.iter().count(), 0);
+    uniforms.set("scale", 4.0);
+    let values: Vec<_> = uniforms.iter().cloned().collect();
+    assert_eq!(values, vec![("scale".to_string(), 4.0)]);
+}
+
+#[test]
+fn shader_line_numbers() {
+    assert_eq!(
+        numbered_source("#version 100\nvoid main()\n{\n}"),
+        "   1 | #version 100\n   2 | void main()\n   3 | {\n   4 | }\n"
+    );
+}
+
+#[test]
+fn blend_factor_round_trip() {
+    for &mode in &[
+        BlendMode::Alpha,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+        BlendMode::PremultipliedAlpha,
+    ] {
+        let (src, dst) = blend_factors(mode).unwrap();
+        assert_eq!(blend_mode_from_factors(src, dst), Some(mode));
+    }
+    assert_eq!(blend_factors(BlendMode::None), None);
+    assert_eq!(blend_mode_from_factors(ONE, ZERO), None);
+    assert_eq!(
+        blend_mode_from_factors(ONE_MINUS_SRC_ALPHA, SRC_ALPHA),
+        None
+    );
+}
+
+// A frame of a game loop written against the traits only, it is generic over
+// the backend so nothing in it knows which one it runs on
+#[cfg(test)]
+fn draw_frame<W: Window, R: Renderer, I: Input>(
+    window: &W,
+    gl_context: &W::GLContext,
+    renderer: &R,
+    program: &R::Program,
+    vertex_buffer: &R::VertexBuffer,
+    input: &I,
+) -> Result<(), Error> {
+    use rendering::VertexAttributeType;
+
+    #[allow(dead_code)]
+    struct Position(f32, f32);
+    impl Vertex for Position {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![("position".into(), 2, VertexAttributeType::Float)]
+        }
+    }
+
+    window.gl_set_current(gl_context);
+    let (width, height) = window.size();
+    renderer.set_viewport(0, 0, width, height);
+    let red = if input.key_is_down(&Key::Space) {
+        1.0
+    } else {
+        0.0
+    };
+    renderer.clear(Some((red, 0.0, 0.0, 1.0)), Some(1.0), None);
+    let position = input.mouse_position();
+    let x = position.x as f32 / width as f32 * 2.0 - 1.0;
+    let y = 1.0 - position.y as f32 / height as f32 * 2.0;
+    let vertices = vec![Position(x, y), Position(x + 0.1, y), Position(x, y + 0.1)];
+    renderer.render_vertices(Primitive::Triangles, vertex_buffer, program, &vertices)
+}
+
+#[test]
+fn backends_implement_the_traits() {
+    // instantiating the frame for the backend of the target is the compile test,
+    // wasm builds get the web backend through the same cfg as the wrappers
+    let frame = draw_frame::<
+        platform_impl::window::Window,
+        platform_impl::rendering::Backend,
+        ::input::Input,
+    >;
+    let _ = frame;
+}
@@ -0,0 +1,13 @@
+use std::fs;
+use std::thread;
+
+use failure::Error;
+
+/// Reads the file at `path` on another thread and calls `done` on that thread
+/// with its contents
+pub fn fetch_bytes<F: FnOnce(Result<Vec<u8>, Error>) + Send + 'static>(path: &str, done: F) {
+    let path = path.to_string();
+    thread::spawn(move || {
+        done(fs::read(&path).map_err(|e| format_err!("could not read {}: {}", path, e)));
+    });
+}
@@ -1,6 +1,67 @@
-use input::{Key, MouseButton};
-use sdl2::keyboard::Keycode as Sdl2Keycode;
+use input::{GamepadState, Key, Modifiers, MouseButton};
+use sdl2::joystick::Joystick;
+use sdl2::keyboard::Mod as Sdl2Mod;
+use sdl2::keyboard::Scancode as Sdl2Scancode;
 use sdl2::mouse::MouseButton as Sdl2MouseButton;
+use sdl2::JoystickSubsystem;
+
+/// Open joysticks, indexed by the gamepad index reported to the application.
+/// Slots of disconnected joysticks are reused by the next one connected.
+pub struct Gamepads {
+    // None when SDL could not initialize joysticks, no gamepad ever connects then
+    subsystem: Option<JoystickSubsystem>,
+    slots: Vec<Option<Joystick>>,
+}
+
+impl Gamepads {
+    pub fn new(subsystem: Option<JoystickSubsystem>) -> Gamepads {
+        Gamepads {
+            subsystem,
+            slots: Vec::new(),
+        }
+    }
+
+    pub fn connect(&mut self, device_index: u32) -> Option<u32> {
+        let joystick = self.subsystem.as_ref()?.open(device_index).ok()?;
+        let slot = match self.slots.iter().position(|s| s.is_none()) {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(None);
+                self.slots.len() - 1
+            }
+        };
+        self.slots[slot] = Some(joystick);
+        Some(slot as u32)
+    }
+
+    pub fn disconnect(&mut self, instance_id: u32) -> Option<u32> {
+        let slot = self.slots.iter().position(|s| match s {
+            Some(joystick) => joystick.instance_id() == instance_id,
+            None => false,
+        })?;
+        self.slots[slot] = None;
+        Some(slot as u32)
+    }
+
+    pub fn states(&self) -> Vec<Option<GamepadState>> {
+        self.slots
+            .iter()
+            .map(|slot| {
+                slot.as_ref().map(|joystick| GamepadState {
+                    buttons: (0..joystick.num_buttons())
+                        .map(|b| match joystick.button(b) {
+                            Ok(true) => 1.0,
+                            _ => 0.0,
+                        })
+                        .collect(),
+                    axes: (0..joystick.num_axes())
+                        .map(|a| (joystick.axis(a).unwrap_or(0) as f32 / 32767.0).max(-1.0))
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
 
 pub fn to_mouse_button(button: Sdl2MouseButton) -> MouseButton {
     match button {
@@ -12,109 +73,135 @@ pub fn to_mouse_button(button: Sdl2MouseButton) -> MouseButton {
     }
 }
 
-pub fn to_key(key_code: Sdl2Keycode) -> Key {
-    match key_code {
-        Sdl2Keycode::Backspace => Key::Backspace,
-        Sdl2Keycode::Space => Key::Space,
-        Sdl2Keycode::Tab => Key::Tab,
-        Sdl2Keycode::Return => Key::Return,
-        Sdl2Keycode::LShift => Key::Shift,
-        Sdl2Keycode::LCtrl => Key::Ctrl,
-        Sdl2Keycode::LAlt => Key::Alt,
-        Sdl2Keycode::RShift => Key::Shift,
-        Sdl2Keycode::RCtrl => Key::Ctrl,
-        Sdl2Keycode::RAlt => Key::Alt,
-        Sdl2Keycode::Pause => Key::Pause,
-        Sdl2Keycode::CapsLock => Key::CapsLock,
-        Sdl2Keycode::Escape => Key::Escape,
-        Sdl2Keycode::PageUp => Key::PageUp,
-        Sdl2Keycode::PageDown => Key::PageDown,
-        Sdl2Keycode::End => Key::End,
-        Sdl2Keycode::Home => Key::Home,
-        Sdl2Keycode::Left => Key::Left,
-        Sdl2Keycode::Up => Key::Up,
-        Sdl2Keycode::Right => Key::Right,
-        Sdl2Keycode::Down => Key::Down,
-        Sdl2Keycode::Insert => Key::Insert,
-        Sdl2Keycode::Delete => Key::Delete,
-        Sdl2Keycode::Num0 => Key::Num0,
-        Sdl2Keycode::Num1 => Key::Num1,
-        Sdl2Keycode::Num2 => Key::Num2,
-        Sdl2Keycode::Num3 => Key::Num3,
-        Sdl2Keycode::Num4 => Key::Num4,
-        Sdl2Keycode::Num5 => Key::Num5,
-        Sdl2Keycode::Num6 => Key::Num6,
-        Sdl2Keycode::Num7 => Key::Num7,
-        Sdl2Keycode::Num8 => Key::Num8,
-        Sdl2Keycode::Num9 => Key::Num9,
-        Sdl2Keycode::A => Key::A,
-        Sdl2Keycode::B => Key::B,
-        Sdl2Keycode::C => Key::C,
-        Sdl2Keycode::D => Key::D,
-        Sdl2Keycode::E => Key::E,
-        Sdl2Keycode::F => Key::F,
-        Sdl2Keycode::G => Key::G,
-        Sdl2Keycode::H => Key::H,
-        Sdl2Keycode::I => Key::I,
-        Sdl2Keycode::J => Key::J,
-        Sdl2Keycode::K => Key::K,
-        Sdl2Keycode::L => Key::L,
-        Sdl2Keycode::M => Key::M,
-        Sdl2Keycode::N => Key::N,
-        Sdl2Keycode::O => Key::O,
-        Sdl2Keycode::P => Key::P,
-        Sdl2Keycode::Q => Key::Q,
-        Sdl2Keycode::R => Key::R,
-        Sdl2Keycode::S => Key::S,
-        Sdl2Keycode::T => Key::T,
-        Sdl2Keycode::U => Key::U,
-        Sdl2Keycode::V => Key::V,
-        Sdl2Keycode::W => Key::W,
-        Sdl2Keycode::X => Key::X,
-        Sdl2Keycode::Y => Key::Y,
-        Sdl2Keycode::Z => Key::Z,
-        Sdl2Keycode::Application => Key::Application,
-        Sdl2Keycode::Select => Key::Select,
-        Sdl2Keycode::Kp0 => Key::Kp0,
-        Sdl2Keycode::Kp1 => Key::Kp1,
-        Sdl2Keycode::Kp2 => Key::Kp2,
-        Sdl2Keycode::Kp3 => Key::Kp3,
-        Sdl2Keycode::Kp4 => Key::Kp4,
-        Sdl2Keycode::Kp5 => Key::Kp5,
-        Sdl2Keycode::Kp6 => Key::Kp6,
-        Sdl2Keycode::Kp7 => Key::Kp7,
-        Sdl2Keycode::Kp8 => Key::Kp8,
-        Sdl2Keycode::Kp9 => Key::Kp9,
-        Sdl2Keycode::KpMultiply => Key::KpMultiply,
-        Sdl2Keycode::KpPlus => Key::KpPlus,
-        Sdl2Keycode::KpMinus => Key::KpMinus,
-        Sdl2Keycode::KpDecimal => Key::KpDecimal,
-        Sdl2Keycode::KpDivide => Key::KpDivide,
-        Sdl2Keycode::F1 => Key::F1,
-        Sdl2Keycode::F2 => Key::F2,
-        Sdl2Keycode::F3 => Key::F3,
-        Sdl2Keycode::F4 => Key::F4,
-        Sdl2Keycode::F5 => Key::F5,
-        Sdl2Keycode::F6 => Key::F6,
-        Sdl2Keycode::F7 => Key::F7,
-        Sdl2Keycode::F8 => Key::F8,
-        Sdl2Keycode::F9 => Key::F9,
-        Sdl2Keycode::F10 => Key::F10,
-        Sdl2Keycode::F11 => Key::F11,
-        Sdl2Keycode::F12 => Key::F12,
-        Sdl2Keycode::NumLockClear => Key::NumLockClear,
-        Sdl2Keycode::ScrollLock => Key::ScrollLock,
-        Sdl2Keycode::Semicolon => Key::Semicolon,
-        Sdl2Keycode::Equals => Key::Equals,
-        Sdl2Keycode::Comma => Key::Comma,
-        Sdl2Keycode::Minus => Key::Minus,
-        Sdl2Keycode::Period => Key::Period,
-        Sdl2Keycode::Slash => Key::Slash,
-        Sdl2Keycode::Backquote => Key::Backquote,
-        Sdl2Keycode::LeftBracket => Key::LeftBracket,
-        Sdl2Keycode::Backslash => Key::Backslash,
-        Sdl2Keycode::RightBracket => Key::RightBracket,
-        Sdl2Keycode::Quote => Key::Quote,
-        _ => Key::Unknown,
+pub fn to_modifiers(keymod: Sdl2Mod) -> Modifiers {
+    Modifiers {
+        shift: keymod.intersects(Sdl2Mod::LSHIFTMOD | Sdl2Mod::RSHIFTMOD),
+        ctrl: keymod.intersects(Sdl2Mod::LCTRLMOD | Sdl2Mod::RCTRLMOD),
+        alt: keymod.intersects(Sdl2Mod::LALTMOD | Sdl2Mod::RALTMOD),
+        meta: keymod.intersects(Sdl2Mod::LGUIMOD | Sdl2Mod::RGUIMOD),
+    }
+}
+
+/// Maps the physical key, SDL's keycodes follow the active layout
+pub fn to_key(scancode: Sdl2Scancode) -> Key {
+    match scancode {
+        Sdl2Scancode::Backspace => Key::Backspace,
+        Sdl2Scancode::Space => Key::Space,
+        Sdl2Scancode::Tab => Key::Tab,
+        Sdl2Scancode::Return => Key::Return,
+        Sdl2Scancode::LShift => Key::Shift,
+        Sdl2Scancode::LCtrl => Key::Ctrl,
+        Sdl2Scancode::LAlt => Key::Alt,
+        Sdl2Scancode::RShift => Key::Shift,
+        Sdl2Scancode::RCtrl => Key::Ctrl,
+        Sdl2Scancode::RAlt => Key::Alt,
+        Sdl2Scancode::LGui => Key::Gui,
+        Sdl2Scancode::RGui => Key::Gui,
+        Sdl2Scancode::Pause => Key::Pause,
+        Sdl2Scancode::CapsLock => Key::CapsLock,
+        Sdl2Scancode::Escape => Key::Escape,
+        Sdl2Scancode::PageUp => Key::PageUp,
+        Sdl2Scancode::PageDown => Key::PageDown,
+        Sdl2Scancode::End => Key::End,
+        Sdl2Scancode::Home => Key::Home,
+        Sdl2Scancode::Left => Key::Left,
+        Sdl2Scancode::Up => Key::Up,
+        Sdl2Scancode::Right => Key::Right,
+        Sdl2Scancode::Down => Key::Down,
+        Sdl2Scancode::Insert => Key::Insert,
+        Sdl2Scancode::Delete => Key::Delete,
+        Sdl2Scancode::Num0 => Key::Num0,
+        Sdl2Scancode::Num1 => Key::Num1,
+        Sdl2Scancode::Num2 => Key::Num2,
+        Sdl2Scancode::Num3 => Key::Num3,
+        Sdl2Scancode::Num4 => Key::Num4,
+        Sdl2Scancode::Num5 => Key::Num5,
+        Sdl2Scancode::Num6 => Key::Num6,
+        Sdl2Scancode::Num7 => Key::Num7,
+        Sdl2Scancode::Num8 => Key::Num8,
+        Sdl2Scancode::Num9 => Key::Num9,
+        Sdl2Scancode::A => Key::A,
+        Sdl2Scancode::B => Key::B,
+        Sdl2Scancode::C => Key::C,
+        Sdl2Scancode::D => Key::D,
+        Sdl2Scancode::E => Key::E,
+        Sdl2Scancode::F => Key::F,
+        Sdl2Scancode::G => Key::G,
+        Sdl2Scancode::H => Key::H,
+        Sdl2Scancode::I => Key::I,
+        Sdl2Scancode::J => Key::J,
+        Sdl2Scancode::K => Key::K,
+        Sdl2Scancode::L => Key::L,
+        Sdl2Scancode::M => Key::M,
+        Sdl2Scancode::N => Key::N,
+        Sdl2Scancode::O => Key::O,
+        Sdl2Scancode::P => Key::P,
+        Sdl2Scancode::Q => Key::Q,
+        Sdl2Scancode::R => Key::R,
+        Sdl2Scancode::S => Key::S,
+        Sdl2Scancode::T => Key::T,
+        Sdl2Scancode::U => Key::U,
+        Sdl2Scancode::V => Key::V,
+        Sdl2Scancode::W => Key::W,
+        Sdl2Scancode::X => Key::X,
+        Sdl2Scancode::Y => Key::Y,
+        Sdl2Scancode::Z => Key::Z,
+        Sdl2Scancode::Application => Key::Application,
+        Sdl2Scancode::Select => Key::Select,
+        Sdl2Scancode::Kp0 => Key::Kp0,
+        Sdl2Scancode::Kp1 => Key::Kp1,
+        Sdl2Scancode::Kp2 => Key::Kp2,
+        Sdl2Scancode::Kp3 => Key::Kp3,
+        Sdl2Scancode::Kp4 => Key::Kp4,
+        Sdl2Scancode::Kp5 => Key::Kp5,
+        Sdl2Scancode::Kp6 => Key::Kp6,
+        Sdl2Scancode::Kp7 => Key::Kp7,
+        Sdl2Scancode::Kp8 => Key::Kp8,
+        Sdl2Scancode::Kp9 => Key::Kp9,
+        Sdl2Scancode::KpMultiply => Key::KpMultiply,
+        Sdl2Scancode::KpPlus => Key::KpPlus,
+        Sdl2Scancode::KpMinus => Key::KpMinus,
+        Sdl2Scancode::KpDecimal => Key::KpDecimal,
+        Sdl2Scancode::KpDivide => Key::KpDivide,
+        Sdl2Scancode::F1 => Key::F1,
+        Sdl2Scancode::F2 => Key::F2,
+        Sdl2Scancode::F3 => Key::F3,
+        Sdl2Scancode::F4 => Key::F4,
+        Sdl2Scancode::F5 => Key::F5,
+        Sdl2Scancode::F6 => Key::F6,
+        Sdl2Scancode::F7 => Key::F7,
+        Sdl2Scancode::F8 => Key::F8,
+        Sdl2Scancode::F9 => Key::F9,
+        Sdl2Scancode::F10 => Key::F10,
+        Sdl2Scancode::F11 => Key::F11,
+        Sdl2Scancode::F12 => Key::F12,
+        Sdl2Scancode::NumLockClear => Key::NumLockClear,
+        Sdl2Scancode::ScrollLock => Key::ScrollLock,
+        Sdl2Scancode::Semicolon => Key::Semicolon,
+        Sdl2Scancode::Equals => Key::Equals,
+        Sdl2Scancode::Comma => Key::Comma,
+        Sdl2Scancode::Minus => Key::Minus,
+        Sdl2Scancode::Period => Key::Period,
+        Sdl2Scancode::Slash => Key::Slash,
+        Sdl2Scancode::Grave => Key::Backquote,
+        Sdl2Scancode::LeftBracket => Key::LeftBracket,
+        Sdl2Scancode::Backslash => Key::Backslash,
+        Sdl2Scancode::RightBracket => Key::RightBracket,
+        Sdl2Scancode::Apostrophe => Key::Quote,
+        other => Key::Other(other as i32),
     }
 }
+
+#[test]
+fn arrow_keys() {
+    assert_eq!(to_key(Sdl2Scancode::Left), Key::Left);
+    assert_eq!(to_key(Sdl2Scancode::Up), Key::Up);
+    assert_eq!(to_key(Sdl2Scancode::Right), Key::Right);
+    assert_eq!(to_key(Sdl2Scancode::Down), Key::Down);
+    assert_eq!(to_key(Sdl2Scancode::Apostrophe), Key::Quote);
+    assert_eq!(to_key(Sdl2Scancode::Grave), Key::Backquote);
+    assert_eq!(
+        to_key(Sdl2Scancode::F24),
+        Key::Other(Sdl2Scancode::F24 as i32)
+    );
+}
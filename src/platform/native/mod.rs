@@ -1,7 +1,11 @@
 pub mod audio;
+pub mod fetch;
 mod input;
 pub mod rand;
 pub mod rendering;
+#[cfg(test)]
+pub mod test_renderer;
+pub mod time;
 //pub mod websocket;
 pub mod window;
 
@@ -10,16 +14,21 @@ use std::rc::Rc;
 
 use failure::Error;
 use sdl2;
-use sdl2::event::Event;
+use sdl2::event::{Event, WindowEvent};
+use std::mem;
 use std::thread;
 use std::time::Duration;
 use std::time::Instant;
 
-use input::{Input, InputEvent};
+use input::{Input, InputEvent, Modifiers};
 use window::WindowSettings;
 
-use self::input::{to_key, to_mouse_button};
+use self::input::{to_key, to_modifiers, to_mouse_button, Gamepads};
 use self::window::Window;
+use platform::Window as PlatformWindow;
+
+// pixels scrolled per wheel notch, matches the web backend
+const WHEEL_LINE_HEIGHT: f32 = 16.0;
 
 pub struct Context {
     video: sdl2::VideoSubsystem,
@@ -47,7 +56,18 @@ pub fn init<F: FnOnce(Context) -> T, T: FnMut(f64, &Input) -> Result<(), Error>
         .refresh_rate;
 
     let mut event_pump = sdl_context.event_pump().unwrap();
+    let keyboard = sdl_context.keyboard();
+    let mouse = sdl_context.mouse();
+    let mut pointer_locked = false;
+    let mut fullscreen = false;
     let mut input = Input::new();
+    let mut modifiers = Modifiers::default();
+    // a game without gamepads still runs when there is no joystick support
+    let joystick = sdl_context
+        .joystick()
+        .map_err(|e| eprintln!("gamepads are disabled, could not initialize them: {}", e))
+        .ok();
+    let mut gamepads = Gamepads::new(joystick);
 
     let windows = Rc::new(RefCell::new(Vec::new()));
     let context = Context {
@@ -57,6 +77,16 @@ pub fn init<F: FnOnce(Context) -> T, T: FnMut(f64, &Input) -> Result<(), Error>
     };
 
     let mut main_loop = app_factory(context);
+
+    // report the initial size of every window so projections can be set up
+    let mut input_events: Vec<InputEvent> = windows
+        .borrow()
+        .iter()
+        .map(|w| {
+            let (width, height) = w.size();
+            InputEvent::Resize(width as i32, height as i32)
+        })
+        .collect();
     let mut start_frame = Instant::now();
     let mut frame_delay = 0.0;
     let target_frame_time = 1.0 / refresh_rate as f64;
@@ -67,38 +97,164 @@ pub fn init<F: FnOnce(Context) -> T, T: FnMut(f64, &Input) -> Result<(), Error>
             + (frame_elapsed.subsec_nanos() as f64 / 1_000_000_000.0);
         frame_delay += target_frame_time - frame_dt;
 
-        let mut input_events = Vec::new();
+        // relative mouse mode is toggled through the windows, report when it changes
+        if mouse.relative_mouse_mode() != pointer_locked {
+            pointer_locked = !pointer_locked;
+            input_events.push(InputEvent::PointerLockChanged(pointer_locked));
+        }
+        if windows.borrow().iter().any(|w| w.is_fullscreen()) != fullscreen {
+            fullscreen = !fullscreen;
+            input_events.push(InputEvent::FullscreenChanged(fullscreen));
+        }
+
         for event in event_pump.poll_iter() {
             match event {
-                Event::MouseMotion { x, y, .. } => {
+                Event::MouseMotion {
+                    window_id,
+                    x,
+                    y,
+                    xrel,
+                    yrel,
+                    ..
+                } => {
+                    let (x, y) = drawable_position(&windows.borrow(), window_id, x, y);
+                    let (xrel, yrel) = drawable_position(&windows.borrow(), window_id, xrel, yrel);
                     input_events.push(InputEvent::MouseMove(x, y));
+                    input_events.push(InputEvent::MouseDelta(xrel, yrel));
                 }
                 Event::MouseButtonDown {
-                    mouse_btn, x, y, ..
+                    window_id,
+                    mouse_btn,
+                    x,
+                    y,
+                    ..
                 } => {
+                    let new_modifiers = to_modifiers(keyboard.mod_state());
+                    update_modifiers(&mut input_events, &mut modifiers, new_modifiers);
                     input_events.push(InputEvent::MouseDown {
                         button: to_mouse_button(mouse_btn),
-                        position: (x, y),
+                        position: drawable_position(&windows.borrow(), window_id, x, y),
                     });
                 }
                 Event::MouseButtonUp {
-                    mouse_btn, x, y, ..
+                    window_id,
+                    mouse_btn,
+                    x,
+                    y,
+                    ..
                 } => {
+                    let new_modifiers = to_modifiers(keyboard.mod_state());
+                    update_modifiers(&mut input_events, &mut modifiers, new_modifiers);
                     input_events.push(InputEvent::MouseUp {
                         button: to_mouse_button(mouse_btn),
-                        position: (x, y),
+                        position: drawable_position(&windows.borrow(), window_id, x, y),
+                    });
+                }
+                Event::Window {
+                    window_id,
+                    win_event: WindowEvent::SizeChanged(width, height),
+                    ..
+                } => {
+                    let (width, height) =
+                        drawable_position(&windows.borrow(), window_id, width, height);
+                    input_events.push(InputEvent::Resize(width, height));
+                }
+                Event::Window {
+                    win_event: WindowEvent::Enter,
+                    ..
+                } => {
+                    input_events.push(InputEvent::MouseEnter);
+                }
+                Event::Window {
+                    win_event: WindowEvent::Leave,
+                    ..
+                } => {
+                    input_events.push(InputEvent::MouseLeave);
+                }
+                Event::Window {
+                    win_event: WindowEvent::FocusGained,
+                    ..
+                } => {
+                    input_events.push(InputEvent::Focus(true));
+                }
+                Event::Window {
+                    win_event: WindowEvent::FocusLost,
+                    ..
+                } => {
+                    input_events.push(InputEvent::Focus(false));
+                }
+                Event::FingerDown {
+                    finger_id, x, y, ..
+                } => {
+                    input_events.push(InputEvent::TouchStart {
+                        id: finger_id,
+                        position: touch_position(&windows.borrow(), x, y),
+                    });
+                }
+                Event::FingerMotion {
+                    finger_id, x, y, ..
+                } => {
+                    input_events.push(InputEvent::TouchMove {
+                        id: finger_id,
+                        position: touch_position(&windows.borrow(), x, y),
                     });
                 }
+                Event::FingerUp {
+                    finger_id, x, y, ..
+                } => {
+                    input_events.push(InputEvent::TouchEnd {
+                        id: finger_id,
+                        position: touch_position(&windows.borrow(), x, y),
+                    });
+                }
+                Event::TextInput { text, .. } => {
+                    input_events.push(InputEvent::TextInput(text));
+                }
+                Event::JoyDeviceAdded { which, .. } => {
+                    if let Some(index) = gamepads.connect(which) {
+                        input_events.push(InputEvent::GamepadConnected(index));
+                    }
+                }
+                Event::JoyDeviceRemoved { which, .. } => {
+                    if let Some(index) = gamepads.disconnect(which) {
+                        input_events.push(InputEvent::GamepadDisconnected(index));
+                    }
+                }
+                Event::MouseWheel { x, y, .. } => {
+                    // SDL reports wheel notches with positive y away from the user
+                    input_events.push(InputEvent::MouseWheel(
+                        x as f32 * WHEEL_LINE_HEIGHT,
+                        -y as f32 * WHEEL_LINE_HEIGHT,
+                    ));
+                }
                 Event::KeyDown {
-                    keycode: Some(key), ..
-                } => input_events.push(InputEvent::KeyDown(to_key(key))),
+                    scancode: Some(key),
+                    repeat: true,
+                    ..
+                } => {
+                    input_events.push(InputEvent::KeyRepeat(to_key(key)));
+                }
+                Event::KeyDown {
+                    scancode: Some(key),
+                    keymod,
+                    ..
+                } => {
+                    update_modifiers(&mut input_events, &mut modifiers, to_modifiers(keymod));
+                    input_events.push(InputEvent::KeyDown(to_key(key)));
+                }
                 Event::KeyUp {
-                    keycode: Some(key), ..
-                } => input_events.push(InputEvent::KeyUp(to_key(key))),
+                    scancode: Some(key),
+                    keymod,
+                    ..
+                } => {
+                    update_modifiers(&mut input_events, &mut modifiers, to_modifiers(keymod));
+                    input_events.push(InputEvent::KeyUp(to_key(key)));
+                }
                 _ => {}
             }
         }
-        input.update(input_events);
+        input.update(mem::replace(&mut input_events, Vec::new()));
+        input.update_gamepads(gamepads.states());
 
         let start_update = Instant::now();
         main_loop(frame_dt, &input).unwrap();
@@ -121,3 +277,37 @@ pub fn init<F: FnOnce(Context) -> T, T: FnMut(f64, &Input) -> Result<(), Error>
         }
     }
 }
+
+fn update_modifiers(events: &mut Vec<InputEvent>, current: &mut Modifiers, new: Modifiers) {
+    if *current != new {
+        *current = new;
+        events.push(InputEvent::ModifiersChanged(new));
+    }
+}
+
+// SDL reports mouse positions and window sizes in screen points, which are larger
+// than pixels on high DPI screens. Each window is scaled by its own screen.
+fn drawable_position(windows: &[Rc<Window>], window_id: u32, x: i32, y: i32) -> (i32, i32) {
+    let (scale_x, scale_y) = windows
+        .iter()
+        .find(|w| w.id() == window_id)
+        .map(|w| {
+            let (width, height) = w.size();
+            let (logical_width, logical_height) = w.logical_size();
+            (
+                width as f32 / logical_width.max(1.0),
+                height as f32 / logical_height.max(1.0),
+            )
+        })
+        .unwrap_or((1.0, 1.0));
+    (
+        (x as f32 * scale_x).round() as i32,
+        (y as f32 * scale_y).round() as i32,
+    )
+}
+
+// SDL reports touches normalized to the window, convert them to pixels like mouse positions
+fn touch_position(windows: &[Rc<Window>], x: f32, y: f32) -> (i32, i32) {
+    let (width, height) = windows.first().map(|w| w.size()).unwrap_or((1, 1));
+    ((x * width as f32) as i32, (y * height as f32) as i32)
+}
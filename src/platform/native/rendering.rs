@@ -1,228 +1,1913 @@
 use gl;
 use gl::types::*;
-use std;
-use std::ffi::CString;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::iter;
 use std::mem;
 use std::os::raw::c_void;
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
+use std::rc::Rc;
+use std::slice;
 
 use failure::Error;
 
 use assets::Image;
-use rendering::{TextureFiltering, Vertex, VertexAttributeType};
+use platform::{
+    blend_factors, blend_mode_from_factors, check_limit, check_vertex_layout,
+    Renderer as PlatformRenderer, Uniforms,
+};
+use rendering::{
+    BlendMode, BufferUsage, Capabilities, CompressedFormat, DebugMessage, DebugSeverity, Index,
+    IndexType, Primitive, RenderError, RenderState, ShaderStage, StencilFunc, StencilOp,
+    TextureFiltering, TextureFormat, TextureWrap, Vertex, VertexAttributeType,
+};
 
-pub type VertexBuffer = (u32, u32);
+pub struct VertexBuffer {
+    vao: GLuint,
+    vbo: GLuint,
+    ebo: GLuint,
+    // per instance attributes
+    instance_vbo: GLuint,
+    usage: GLenum,
+    // bytes last uploaded to vbo and ebo, the raw draws are checked against them
+    vertex_bytes: Cell<usize>,
+    index_bytes: Cell<usize>,
+}
+
+impl VertexBuffer {
+    fn new(
+        vao: GLuint,
+        vbo: GLuint,
+        ebo: GLuint,
+        instance_vbo: GLuint,
+        usage: GLenum,
+    ) -> VertexBuffer {
+        VertexBuffer {
+            vao,
+            vbo,
+            ebo,
+            instance_vbo,
+            usage,
+            vertex_bytes: Cell::new(0),
+            index_bytes: Cell::new(0),
+        }
+    }
+
+    pub fn vertex_bytes(&self) -> usize {
+        self.vertex_bytes.get()
+    }
+
+    pub fn index_bytes(&self) -> usize {
+        self.index_bytes.get()
+    }
+
+    pub fn set_label(&self, label: &str) {
+        object_label(gl::VERTEX_ARRAY, self.vao, label);
+        object_label(gl::BUFFER, self.vbo, &format!("{} vertices", label));
+        object_label(gl::BUFFER, self.ebo, &format!("{} indices", label));
+        object_label(
+            gl::BUFFER,
+            self.instance_vbo,
+            &format!("{} instances", label),
+        );
+    }
+}
+
+pub struct TimerQuery(GLuint);
+
+impl TimerQuery {
+    pub fn begin(&self) {
+        unsafe { gl::BeginQuery(gl::TIME_ELAPSED, self.0) };
+    }
+
+    pub fn end(&self) {
+        unsafe { gl::EndQuery(gl::TIME_ELAPSED) };
+    }
+
+    pub fn is_available(&self) -> bool {
+        let mut available = 0;
+        unsafe { gl::GetQueryObjectuiv(self.0, gl::QUERY_RESULT_AVAILABLE, &mut available) };
+        available != 0
+    }
+
+    pub fn result_ns(&self) -> u64 {
+        let mut result = 0;
+        unsafe { gl::GetQueryObjectui64v(self.0, gl::QUERY_RESULT, &mut result) };
+        result
+    }
+}
+
+impl Drop for TimerQuery {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteQueries(1, &self.0) };
+    }
+}
+
+pub struct UniformBuffer {
+    ubo: GLuint,
+    usage: GLenum,
+}
+
+impl UniformBuffer {
+    pub fn set_data(&self, data: &[u8]) {
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.ubo);
+            upload_buffer(
+                gl::UNIFORM_BUFFER,
+                self.usage,
+                data.len(),
+                data.as_ptr() as *const _,
+            );
+        }
+    }
+
+    pub fn bind(&self, binding: u32) {
+        unsafe { gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, self.ubo) };
+    }
+
+    pub fn set_label(&self, label: &str) {
+        object_label(gl::BUFFER, self.ubo, label);
+    }
+}
+
+impl Drop for UniformBuffer {
+    fn drop(&mut self) {
+        unsafe { gl::DeleteBuffers(1, &self.ubo) };
+    }
+}
+
+impl Drop for VertexBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.instance_vbo);
+            gl::DeleteBuffers(1, &self.ebo);
+            gl::DeleteBuffers(1, &self.vbo);
+            gl::DeleteVertexArrays(1, &self.vao);
+            forget_vertex_array(self.vao);
+        }
+    }
+}
+
+pub struct Framebuffer {
+    fbo: GLuint,
+    depth: GLuint,
+    size: (u32, u32),
+    resolve: Option<Resolve>,
+}
+
+// a multisampled framebuffer draws into a color renderbuffer, the texture is
+// attached to a second framebuffer that the samples are blitted to
+struct Resolve {
+    fbo: GLuint,
+    color: GLuint,
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some(ref resolve) = self.resolve {
+                gl::DeleteRenderbuffers(1, &resolve.color);
+                gl::DeleteFramebuffers(1, &resolve.fbo);
+            }
+            gl::DeleteRenderbuffers(1, &self.depth);
+            gl::DeleteFramebuffers(1, &self.fbo);
+        }
+    }
+}
 
+thread_local! {
+    static BOUND: BoundState = BoundState::default();
+    // callback of enable_debug_output, shared by every context on the thread
+    static DEBUG_OUTPUT: RefCell<Option<(DebugSeverity, Box<FnMut(&DebugMessage)>)>> =
+        RefCell::new(None);
+}
+
+// State last set on the current context so that draws with the same program,
+// vertex array or blend mode skip the GL calls. `None` is unknown, which forces
+// the next call through
+#[derive(Default)]
+struct BoundState {
+    program: Cell<Option<GLuint>>,
+    vertex_array: Cell<Option<GLuint>>,
+    active_texture: Cell<Option<GLenum>>,
+    blend_mode: Cell<Option<BlendMode>>,
+    // bit per texture unit that may have a sampler bound, units past 32 always
+    // count as bound
+    sampler_units: Cell<u32>,
+    // viewport of the default framebuffer, saved while an offscreen target is bound
+    screen_viewport: Cell<Option<[GLint; 4]>>,
+    // GL_MAX_TEXTURE_IMAGE_UNITS, queried on the first draw
+    max_texture_units: Cell<Option<u32>>,
+    // GL_MAX_UNIFORM_BUFFER_BINDINGS, queried on the first uniform buffer bind
+    max_uniform_buffer_bindings: Cell<Option<u32>>,
+}
+
+/// Forgets the cached GL state, called when another context becomes current
+pub fn reset_bound_state() {
+    BOUND.with(|bound| {
+        bound.program.set(None);
+        bound.vertex_array.set(None);
+        bound.active_texture.set(None);
+        bound.blend_mode.set(None);
+        bound.sampler_units.set(!0);
+        bound.screen_viewport.set(None);
+        // contexts of other windows may be on another GPU
+        bound.max_texture_units.set(None);
+        bound.max_uniform_buffer_bindings.set(None);
+    });
+}
+
+fn use_program(program: GLuint) {
+    BOUND.with(|bound| {
+        if bound.program.get() != Some(program) {
+            unsafe { gl::UseProgram(program) };
+            bound.program.set(Some(program));
+        }
+    });
+}
+
+fn bind_vertex_array(vao: GLuint) {
+    BOUND.with(|bound| {
+        if bound.vertex_array.get() != Some(vao) {
+            unsafe { gl::BindVertexArray(vao) };
+            bound.vertex_array.set(Some(vao));
+        }
+    });
+}
+
+fn active_texture(unit: GLenum) {
+    BOUND.with(|bound| {
+        if bound.active_texture.get() != Some(unit) {
+            unsafe { gl::ActiveTexture(unit) };
+            bound.active_texture.set(Some(unit));
+        }
+    });
+}
+
+// 0 unbinds the sampler so the texture's own parameters apply again
+fn bind_sampler(unit: GLuint, sampler: GLuint) {
+    BOUND.with(|bound| {
+        let bit = 1u32.checked_shl(unit).unwrap_or(0);
+        let units = bound.sampler_units.get();
+        if sampler == 0 && bit != 0 && units & bit == 0 {
+            return;
+        }
+        unsafe { gl::BindSampler(unit, sampler) };
+        bound.sampler_units.set(if sampler == 0 {
+            units & !bit
+        } else {
+            units | bit
+        });
+    });
+}
+
+// deleted names get reused, so they must not stay cached
+fn forget_program(program: GLuint) {
+    BOUND.with(|bound| {
+        if bound.program.get() == Some(program) {
+            bound.program.set(None);
+        }
+    });
+}
+
+fn forget_vertex_array(vao: GLuint) {
+    BOUND.with(|bound| {
+        if bound.vertex_array.get() == Some(vao) {
+            bound.vertex_array.set(None);
+        }
+    });
+}
+
+struct TextureHandle(GLuint);
+
+impl Drop for TextureHandle {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.0);
+        }
+    }
+}
+
+struct SamplerHandle(GLuint);
+
+impl Drop for SamplerHandle {
+    fn drop(&mut self) {
+        // deleting a bound sampler unbinds it, a stale bit in sampler_units only
+        // costs a redundant unbind
+        unsafe {
+            gl::DeleteSamplers(1, &self.0);
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Sampler {
+    handle: Rc<SamplerHandle>,
+}
+
+// Clones share the same GL texture, which is deleted once the last clone is dropped
 #[derive(Clone)]
 pub struct Texture {
-    gl_ref: GLuint,
+    handle: Rc<TextureHandle>,
+    size: (u32, u32),
+    format: TextureFormat,
+    compressed: Option<CompressedFormat>,
+    mipmaps: bool,
+}
+
+// generates a texture with its filtering and wrapping set, and leaves it bound
+fn create_texture_handle(filtering: Option<(GLenum, GLenum)>, wrap: Option<GLenum>) -> GLuint {
+    let (min_filter, mag_filter) = filtering.unwrap_or((gl::LINEAR, gl::LINEAR));
+    let mut gl_ref = 0;
+    unsafe {
+        gl::GenTextures(1, &mut gl_ref);
+        gl::BindTexture(gl::TEXTURE_2D, gl_ref);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, min_filter as GLint);
+        gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, mag_filter as GLint);
+        gl::TexParameteri(
+            gl::TEXTURE_2D,
+            gl::TEXTURE_WRAP_S,
+            wrap.unwrap_or(gl::CLAMP_TO_EDGE) as GLint,
+        );
+        gl::TexParameteri(
+            gl::TEXTURE_2D,
+            gl::TEXTURE_WRAP_T,
+            wrap.unwrap_or(gl::CLAMP_TO_EDGE) as GLint,
+        );
+    }
+    gl_ref
+}
+
+impl PartialEq for Texture {
+    fn eq(&self, other: &Texture) -> bool {
+        Rc::ptr_eq(&self.handle, &other.handle)
+    }
 }
 
 impl Texture {
-    fn new(size: (u32, u32), filtering: Option<GLenum>) -> Texture {
-        let mut gl_ref = 0;
+    fn new(
+        size: (u32, u32),
+        format: TextureFormat,
+        filtering: Option<(GLenum, GLenum)>,
+        wrap: Option<GLenum>,
+        mipmaps: bool,
+        data: Option<&[u8]>,
+    ) -> Texture {
+        let (internal_format, pixel_format) = gl_texture_format(format);
+        let gl_ref = create_texture_handle(filtering, wrap);
         unsafe {
-            gl::GenTextures(1, &mut gl_ref);
-            gl::BindTexture(gl::TEXTURE_2D, gl_ref);
-            gl::TexParameteri(
+            // rows of R8 and RGB8 data are not 4 byte aligned
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexImage2D(
                 gl::TEXTURE_2D,
-                gl::TEXTURE_MIN_FILTER,
-                filtering.unwrap_or(gl::LINEAR) as GLint,
+                0,
+                internal_format as GLint,
+                size.0 as GLint,
+                size.1 as GLint,
+                0 as GLint,
+                pixel_format,
+                gl::UNSIGNED_BYTE,
+                data.map(|d| d.as_ptr()).unwrap_or(ptr::null()) as *const _,
             );
+            if mipmaps {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+        }
+        Texture {
+            handle: Rc::new(TextureHandle(gl_ref)),
+            size,
+            format,
+            compressed: None,
+            mipmaps,
+        }
+    }
+    fn new_compressed(
+        size: (u32, u32),
+        format: CompressedFormat,
+        filtering: Option<(GLenum, GLenum)>,
+        wrap: Option<GLenum>,
+        levels: &[&[u8]],
+    ) -> Texture {
+        let gl_ref = create_texture_handle(filtering, wrap);
+        unsafe {
+            for (level, data) in levels.iter().enumerate() {
+                gl::CompressedTexImage2D(
+                    gl::TEXTURE_2D,
+                    level as GLint,
+                    gl_compressed_format(format),
+                    (size.0 >> level).max(1) as GLint,
+                    (size.1 >> level).max(1) as GLint,
+                    0 as GLint,
+                    data.len() as GLsizei,
+                    data.as_ptr() as *const _,
+                );
+            }
+            // the texture is complete without the levels below the last one given
             gl::TexParameteri(
                 gl::TEXTURE_2D,
-                gl::TEXTURE_MAG_FILTER,
-                filtering.unwrap_or(gl::LINEAR) as GLint,
+                gl::TEXTURE_MAX_LEVEL,
+                levels.len() as GLint - 1,
             );
+        }
+        // mipmaps are only generated for uncompressed textures
+        Texture {
+            handle: Rc::new(TextureHandle(gl_ref)),
+            size,
+            format: format.texture_format(),
+            compressed: Some(format),
+            mipmaps: false,
+        }
+    }
+    fn gl_ref(&self) -> GLuint {
+        self.handle.0
+    }
 
-            gl::TexImage2D(
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn compressed_format(&self) -> Option<CompressedFormat> {
+        self.compressed
+    }
+
+    pub fn set_region(&self, image: &Image, offset: (u32, u32)) -> Result<(), Error> {
+        self.set_regions(iter::once((image, offset)))
+    }
+
+    // binds once and regenerates the mipmaps once for all of the regions
+    pub fn set_regions<'a, I: IntoIterator<Item = (&'a Image, (u32, u32))>>(
+        &self,
+        updates: I,
+    ) -> Result<(), Error> {
+        let (_, pixel_format) = gl_texture_format(self.format);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.gl_ref());
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            for (image, offset) in updates {
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    offset.0 as GLint,
+                    offset.1 as GLint,
+                    image.width as GLint,
+                    image.height as GLint,
+                    pixel_format,
+                    gl::UNSIGNED_BYTE,
+                    image.data.as_ptr() as *const _,
+                );
+            }
+            if self.mipmaps {
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+        }
+        check_gl_error("set_regions")
+    }
+
+    pub fn set_label(&self, label: &str) {
+        object_label(gl::TEXTURE, self.gl_ref(), label);
+    }
+
+    // GLES has no glGetTexImage, the texture is attached to a temporary
+    // framebuffer and read from there
+    pub fn read_pixels(&self) -> Result<Vec<u8>, Error> {
+        let (width, height) = self.size;
+        let mut data = vec![0u8; (width * height * 4) as usize];
+        let status = unsafe {
+            let mut previous = 0;
+            gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous);
+            let mut fbo = 0;
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
                 gl::TEXTURE_2D,
+                self.gl_ref(),
                 0,
-                gl::RGBA as GLint,
-                size.0 as GLint,
-                size.1 as GLint,
-                0 as GLint,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                ptr::null() as *const _,
             );
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            if status == gl::FRAMEBUFFER_COMPLETE {
+                gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+                gl::ReadPixels(
+                    0,
+                    0,
+                    width as GLsizei,
+                    height as GLsizei,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    data.as_mut_ptr() as *mut c_void,
+                );
+            }
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous as GLuint);
+            gl::DeleteFramebuffers(1, &fbo);
+            status
+        };
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(format_err!(
+                "texture can't be read back, its format is not renderable (status 0x{:X})",
+                status
+            ));
+        }
+        check_gl_error("read_pixels")?;
+
+        Ok(data)
+    }
+
+    pub fn set_anisotropy(&self, level: f32) -> f32 {
+        if !has_extension("GL_EXT_texture_filter_anisotropic") {
+            return 1.0;
+        }
+        let mut max = 1.0;
+        unsafe {
+            gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max);
+            let level = level.max(1.0).min(max);
+            gl::BindTexture(gl::TEXTURE_2D, self.gl_ref());
+            gl::TexParameterf(gl::TEXTURE_2D, TEXTURE_MAX_ANISOTROPY, level);
+            level
+        }
+    }
+}
+
+// from EXT_texture_filter_anisotropic, which the gl crate doesn't include
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+
+// Names the object in GPU debuggers, skipped without KHR_debug
+fn object_label(identifier: GLenum, name: GLuint, label: &str) {
+    if !gl::ObjectLabel::is_loaded() || !has_extension("GL_KHR_debug") {
+        return;
+    }
+    unsafe {
+        gl::ObjectLabel(
+            identifier,
+            name,
+            label.len() as GLsizei,
+            label.as_ptr() as *const _,
+        );
+    }
+}
+
+fn has_extension(name: &str) -> bool {
+    extensions().iter().any(|extension| extension == name)
+}
+
+fn extensions() -> Vec<String> {
+    unsafe {
+        let mut count = 0;
+        gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        (0..count as GLuint)
+            .map(|i| gl_string(gl::GetStringi(gl::EXTENSIONS, i)))
+            .collect()
+    }
+}
+
+unsafe fn gl_string(string: *const GLubyte) -> String {
+    if string.is_null() {
+        return String::new();
+    }
+    CStr::from_ptr(string as *const _)
+        .to_string_lossy()
+        .into_owned()
+}
+
+pub fn capabilities() -> Capabilities {
+    let mut max_texture_units = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAX_TEXTURE_IMAGE_UNITS, &mut max_texture_units);
+        Capabilities {
+            max_texture_size: max_texture_size(),
+            max_texture_units: max_texture_units as u32,
+            max_samples: max_samples(),
+            max_color_attachments: max_color_attachments(),
+            max_uniform_buffer_bindings: max_uniform_buffer_bindings(),
+            version: gl_string(gl::GetString(gl::VERSION)),
+            renderer: gl_string(gl::GetString(gl::RENDERER)),
+            extensions: extensions(),
         }
-        Texture { gl_ref: gl_ref }
+    }
+}
+
+pub fn enable_debug_output(
+    min_severity: DebugSeverity,
+    callback: Box<FnMut(&DebugMessage)>,
+) -> Result<(), Error> {
+    if !gl::DebugMessageCallback::is_loaded() || !has_extension("GL_KHR_debug") {
+        return Err(format_err!("GL context has no KHR_debug support"));
+    }
+    DEBUG_OUTPUT.with(|output| *output.borrow_mut() = Some((min_severity, callback)));
+    unsafe {
+        gl::Enable(gl::DEBUG_OUTPUT);
+        // report messages during the call that caused them, on this thread
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(debug_message), ptr::null());
+    }
+    Ok(())
+}
+
+extern "system" fn debug_message(
+    source: GLenum,
+    kind: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    length: GLsizei,
+    message: *const GLchar,
+    _: *mut c_void,
+) {
+    let severity = match severity {
+        gl::DEBUG_SEVERITY_HIGH => DebugSeverity::High,
+        gl::DEBUG_SEVERITY_MEDIUM => DebugSeverity::Medium,
+        gl::DEBUG_SEVERITY_LOW => DebugSeverity::Low,
+        _ => DebugSeverity::Notification,
+    };
+    report_debug_message(severity, || {
+        let message = if length < 0 {
+            unsafe { CStr::from_ptr(message) }.to_bytes()
+        } else {
+            unsafe { slice::from_raw_parts(message as *const u8, length as usize) }
+        };
+        DebugMessage {
+            source: debug_source_name(source),
+            kind: debug_type_name(kind),
+            severity,
+            id,
+            message: String::from_utf8_lossy(message).into_owned(),
+        }
+    });
+}
+
+// passes a message to the enable_debug_output callback, `message` is only built
+// when one is set and interested in `severity`
+fn report_debug_message<F: FnOnce() -> DebugMessage>(severity: DebugSeverity, message: F) {
+    DEBUG_OUTPUT.with(|output| {
+        // GL calls made by the callback itself can report again, drop those
+        let mut output = match output.try_borrow_mut() {
+            Ok(output) => output,
+            Err(_) => return,
+        };
+        let (min_severity, callback) = match *output {
+            Some((min_severity, ref mut callback)) => (min_severity, callback),
+            None => return,
+        };
+        if severity < min_severity {
+            return;
+        }
+        let message = message();
+        // unwinding into the driver is undefined behavior, a panicking callback
+        // only loses the message and the panic hook has printed the panic already
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| callback(&message)));
+    });
+}
+
+fn debug_source_name(source: GLenum) -> &'static str {
+    match source {
+        gl::DEBUG_SOURCE_API => "api",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "third party",
+        gl::DEBUG_SOURCE_APPLICATION => "application",
+        _ => "other",
+    }
+}
+
+fn debug_type_name(kind: GLenum) -> &'static str {
+    match kind {
+        gl::DEBUG_TYPE_ERROR => "error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+        gl::DEBUG_TYPE_PORTABILITY => "portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "performance",
+        gl::DEBUG_TYPE_MARKER => "marker",
+        _ => "other",
+    }
+}
+
+fn gl_texture_format(format: TextureFormat) -> (GLenum, GLenum) {
+    match format {
+        TextureFormat::R8 => (gl::R8, gl::RED),
+        TextureFormat::Rg8 => (gl::RG8, gl::RG),
+        TextureFormat::Rgb8 => (gl::RGB8, gl::RGB),
+        TextureFormat::Rgba8 => (gl::RGBA8, gl::RGBA),
+        TextureFormat::Srgba8 => (gl::SRGB8_ALPHA8, gl::RGBA),
+    }
+}
+
+// from EXT_texture_compression_s3tc and KHR_texture_compression_astc_ldr, which
+// the gl bindings don't include
+const COMPRESSED_RGB_S3TC_DXT1: GLenum = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT5: GLenum = 0x83F3;
+const COMPRESSED_RGBA_ASTC_4X4: GLenum = 0x93B0;
+
+fn gl_compressed_format(format: CompressedFormat) -> GLenum {
+    match format {
+        CompressedFormat::Dxt1 => COMPRESSED_RGB_S3TC_DXT1,
+        CompressedFormat::Dxt5 => COMPRESSED_RGBA_S3TC_DXT5,
+        CompressedFormat::Etc2Rgb8 => gl::COMPRESSED_RGB8_ETC2,
+        CompressedFormat::Etc2Rgba8 => gl::COMPRESSED_RGBA8_ETC2_EAC,
+        CompressedFormat::Astc4x4 => COMPRESSED_RGBA_ASTC_4X4,
+    }
+}
+
+pub struct Program {
+    uniforms: Uniforms<Uniform>,
+    uniform_blocks: Vec<(String, GLuint)>,
+    uniform_locations: RefCell<HashMap<String, GLint>>,
+    attrib_locations: RefCell<HashMap<String, GLint>>,
+    gl_ref: GLuint,
+}
+
+#[derive(Clone)]
+pub enum Uniform {
+    Float(f32),
+    Int(i32),
+    Vec2((f32, f32)),
+    Vec3((f32, f32, f32)),
+    Vec4((f32, f32, f32, f32)),
+    Mat3([f32; 9]),
+    Mat4([f32; 16]),
+    Texture(Texture),
+    SampledTexture(Texture, Sampler),
+}
+
+impl Program {
+    fn new(
+        label: &str,
+        vertex_shader: GLVertexShader,
+        frag_shader: GLFragmentShader,
+    ) -> Result<Program, RenderError> {
+        Ok(Program {
+            uniforms: Uniforms::default(),
+            uniform_blocks: Vec::new(),
+            uniform_locations: RefCell::new(HashMap::new()),
+            attrib_locations: RefCell::new(HashMap::new()),
+            gl_ref: link_program(label, vertex_shader.gl_ref(), frag_shader.gl_ref())?,
+        })
     }
     fn gl_ref(&self) -> GLuint {
         self.gl_ref
     }
 
-    pub fn set_region(&self, image: &Image, offset: (u32, u32)) {
-        unsafe {
-            gl::BindTexture(gl::TEXTURE_2D, self.gl_ref);
-            gl::TexSubImage2D(
-                gl::TEXTURE_2D,
-                0,
-                offset.0 as GLint,
-                offset.1 as GLint,
-                image.width as GLint,
-                image.height as GLint,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                image.data.as_ptr() as *const _,
-            );
-        }
+    pub fn set_label(&self, label: &str) {
+        object_label(gl::PROGRAM, self.gl_ref, label);
+    }
+
+    // locations are looked up once and cached, a negative location means the
+    // uniform is not used by the program
+    fn uniform_location(&self, name: &str) -> Result<GLint, Error> {
+        if let Some(&location) = self.uniform_locations.borrow().get(name) {
+            return Ok(location);
+        }
+        let c_name = CString::new(name)
+            .map_err(|_| format_err!("invalid uniform name {:?}, contains a NUL byte", name))?;
+        let location = unsafe { gl::GetUniformLocation(self.gl_ref, c_name.as_ptr()) };
+        if location < 0 {
+            // uniforms may legitimately be optimized out, so this is only a warning
+            // to the debug output, reported once per program since the location is
+            // cached
+            report_debug_message(DebugSeverity::Low, || DebugMessage {
+                source: debug_source_name(gl::DEBUG_SOURCE_APPLICATION),
+                kind: debug_type_name(gl::DEBUG_TYPE_OTHER),
+                severity: DebugSeverity::Low,
+                id: 0,
+                message: format!("uniform {} not found in program, skipping it", name),
+            });
+        }
+        self.uniform_locations
+            .borrow_mut()
+            .insert(name.into(), location);
+        Ok(location)
+    }
+    fn attrib_location(&self, name: &str) -> Result<GLint, Error> {
+        if let Some(&location) = self.attrib_locations.borrow().get(name) {
+            return Ok(location);
+        }
+        let c_name = CString::new(name)
+            .map_err(|_| format_err!("invalid attribute name {:?}, contains a NUL byte", name))?;
+        let location = unsafe { gl::GetAttribLocation(self.gl_ref, c_name.as_ptr()) };
+        self.attrib_locations
+            .borrow_mut()
+            .insert(name.into(), location);
+        Ok(location)
+    }
+
+    /// Replaces the shaders of the program, it is left untouched if they fail to
+    /// compile or link
+    pub fn relink(&mut self, label: &str, vs: &str, fs: &str) -> Result<(), Error> {
+        let vs = GLVertexShader::new(label, vs)?;
+        let fs = GLFragmentShader::new(label, fs)?;
+        let gl_ref = link_program(label, vs.gl_ref(), fs.gl_ref())?;
+
+        unsafe {
+            gl::DeleteProgram(self.gl_ref);
+        }
+        forget_program(self.gl_ref);
+        self.gl_ref = gl_ref;
+        // locations differ between programs
+        self.uniform_locations.borrow_mut().clear();
+        self.attrib_locations.borrow_mut().clear();
+        // blocks removed from the shaders are skipped, they come back with them
+        for &(ref block, binding) in self.uniform_blocks.iter() {
+            let _ = uniform_block_binding(gl_ref, block, binding);
+        }
+        Ok(())
+    }
+
+    pub fn set_uniform_block_binding(&mut self, block: &str, binding: u32) -> Result<(), Error> {
+        uniform_block_binding(self.gl_ref, block, binding)?;
+        match self.uniform_blocks.iter_mut().find(|b| b.0 == block) {
+            Some(existing) => existing.1 = binding,
+            None => self.uniform_blocks.push((block.into(), binding)),
+        }
+        Ok(())
+    }
+
+    pub fn set_uniform(&mut self, name: &str, uniform: Uniform) {
+        self.uniforms.set(name, uniform);
+    }
+    pub fn clear_uniforms(&mut self) {
+        self.uniforms.clear();
+    }
+    pub fn uniforms(&self) -> impl Iterator<Item = &(String, Uniform)> {
+        self.uniforms.iter()
+    }
+}
+
+impl Drop for Program {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteProgram(self.gl_ref);
+        }
+        forget_program(self.gl_ref);
+    }
+}
+
+pub fn screen_size() -> (i32, i32) {
+    let (_, _, width, height) = viewport();
+    (width as i32, height as i32)
+}
+pub fn viewport() -> (i32, i32, u32, u32) {
+    let mut rect: [GLint; 4] = [0; 4];
+    unsafe {
+        gl::GetIntegerv(gl::VIEWPORT, rect.as_mut_ptr() as *mut GLint);
+    }
+    (rect[0], rect[1], rect[2] as u32, rect[3] as u32)
+}
+pub fn create_vertex_buffer(usage: BufferUsage) -> Result<VertexBuffer, Error> {
+    let mut vao = 0;
+    unsafe {
+        gl::GenVertexArrays(1, &mut vao);
+    }
+
+    Ok(VertexBuffer::new(
+        vao,
+        gen_buffer(),
+        gen_buffer(),
+        gen_buffer(),
+        gl_buffer_usage(usage),
+    ))
+}
+
+// Generated names only become buffer objects once bound, which set_label needs.
+// COPY_WRITE_BUFFER is bound to nothing else, unlike ELEMENT_ARRAY_BUFFER which
+// belongs to the bound vertex array.
+fn gen_buffer() -> GLuint {
+    let mut buffer = 0;
+    unsafe {
+        gl::GenBuffers(1, &mut buffer);
+        gl::BindBuffer(gl::COPY_WRITE_BUFFER, buffer);
+        gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+    }
+    buffer
+}
+
+fn gl_buffer_usage(usage: BufferUsage) -> GLenum {
+    match usage {
+        BufferUsage::Static => gl::STATIC_DRAW,
+        BufferUsage::Dynamic => gl::DYNAMIC_DRAW,
+        BufferUsage::Stream => gl::STREAM_DRAW,
+    }
+}
+// GL_GPU_DISJOINT_EXT, only GLES has it
+const GPU_DISJOINT: GLenum = 0x8FBB;
+
+/// Whether timer queries work, and if so whether disjoint events have to be
+/// checked for
+pub fn gpu_timer_support() -> Option<bool> {
+    if !gl::GetQueryObjectui64v::is_loaded() {
+        return None;
+    }
+    if has_extension("GL_EXT_disjoint_timer_query") {
+        Some(true)
+    } else if has_extension("GL_ARB_timer_query") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+pub fn gpu_timer_disjoint() -> bool {
+    let mut disjoint = 0;
+    unsafe { gl::GetIntegerv(GPU_DISJOINT, &mut disjoint) };
+    disjoint != 0
+}
+
+pub fn create_timer_query() -> TimerQuery {
+    let mut query = 0;
+    unsafe { gl::GenQueries(1, &mut query) };
+    TimerQuery(query)
+}
+
+pub fn create_uniform_buffer(usage: BufferUsage) -> Result<UniformBuffer, Error> {
+    Ok(UniformBuffer {
+        ubo: gen_buffer(),
+        usage: gl_buffer_usage(usage),
+    })
+}
+
+fn uniform_block_binding(program: GLuint, block: &str, binding: u32) -> Result<(), Error> {
+    let c_block = CString::new(block).map_err(|_| {
+        format_err!(
+            "invalid uniform block name {:?}, contains a NUL byte",
+            block
+        )
+    })?;
+    unsafe {
+        let index = gl::GetUniformBlockIndex(program, c_block.as_ptr());
+        if index == gl::INVALID_INDEX {
+            return Err(format_err!(
+                "program has no uniform block named {:?}",
+                block
+            ));
+        }
+        gl::UniformBlockBinding(program, index, binding);
+    }
+    Ok(())
+}
+
+pub fn max_uniform_buffer_bindings() -> u32 {
+    BOUND.with(|bound| {
+        let max = &bound.max_uniform_buffer_bindings;
+        max.get().unwrap_or_else(|| {
+            let mut bindings = 0;
+            unsafe {
+                gl::GetIntegerv(gl::MAX_UNIFORM_BUFFER_BINDINGS, &mut bindings);
+            }
+            max.set(Some(bindings as u32));
+            bindings as u32
+        })
+    })
+}
+
+// a draw writes to at most MAX_DRAW_BUFFERS of the attachments
+pub fn max_color_attachments() -> u32 {
+    let mut max_attachments = 0;
+    let mut max_draw_buffers = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAX_COLOR_ATTACHMENTS, &mut max_attachments);
+        gl::GetIntegerv(gl::MAX_DRAW_BUFFERS, &mut max_draw_buffers);
+    }
+    max_attachments.min(max_draw_buffers) as u32
+}
+
+pub fn max_samples() -> u32 {
+    let mut max = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAX_SAMPLES, &mut max);
+    }
+    max as u32
+}
+pub fn max_texture_size() -> u32 {
+    let mut max = 0;
+    unsafe {
+        gl::GetIntegerv(gl::MAX_TEXTURE_SIZE, &mut max);
+    }
+    max as u32
+}
+// multisampled framebuffers have a single color attachment
+pub fn create_framebuffer(
+    textures: &[&Texture],
+    size: (u32, u32),
+    samples: u32,
+) -> Result<Framebuffer, Error> {
+    let mut fbo = 0;
+    let mut depth = 0;
+    let mut resolve = None;
+    let status = unsafe {
+        let mut previous = 0;
+        gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous);
+
+        if samples > 1 {
+            let mut resolve_fbo = 0;
+            gl::GenFramebuffers(1, &mut resolve_fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, resolve_fbo);
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                textures[0].handle.0,
+                0,
+            );
+
+            let mut color = 0;
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            gl::GenRenderbuffers(1, &mut color);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, color);
+            gl::RenderbufferStorageMultisample(
+                gl::RENDERBUFFER,
+                samples as GLsizei,
+                gl::RGBA8,
+                size.0 as GLsizei,
+                size.1 as GLsizei,
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::RENDERBUFFER,
+                color,
+            );
+            resolve = Some(Resolve {
+                fbo: resolve_fbo,
+                color,
+            });
+        } else {
+            gl::GenFramebuffers(1, &mut fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+            let attachments: Vec<GLenum> = (0..textures.len() as GLenum)
+                .map(|i| gl::COLOR_ATTACHMENT0 + i)
+                .collect();
+            for (texture, &attachment) in textures.iter().zip(attachments.iter()) {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    attachment,
+                    gl::TEXTURE_2D,
+                    texture.handle.0,
+                    0,
+                );
+            }
+            // only the first attachment is drawn to unless told otherwise, output N
+            // of the fragment shader goes to attachment N
+            if attachments.len() > 1 {
+                gl::DrawBuffers(attachments.len() as GLsizei, attachments.as_ptr());
+            }
+        }
+
+        gl::GenRenderbuffers(1, &mut depth);
+        gl::BindRenderbuffer(gl::RENDERBUFFER, depth);
+        gl::RenderbufferStorageMultisample(
+            gl::RENDERBUFFER,
+            if samples > 1 { samples as GLsizei } else { 0 },
+            gl::DEPTH24_STENCIL8,
+            size.0 as GLsizei,
+            size.1 as GLsizei,
+        );
+        gl::FramebufferRenderbuffer(
+            gl::FRAMEBUFFER,
+            gl::DEPTH_STENCIL_ATTACHMENT,
+            gl::RENDERBUFFER,
+            depth,
+        );
+
+        let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous as GLuint);
+        status
+    };
+    let framebuffer = Framebuffer {
+        fbo,
+        depth,
+        size,
+        resolve,
+    };
+
+    if status != gl::FRAMEBUFFER_COMPLETE {
+        return Err(RenderError::IncompleteFramebuffer(status).into());
+    }
+    check_gl_error("create_framebuffer")?;
+
+    Ok(framebuffer)
+}
+pub fn resolve_framebuffer(framebuffer: &Framebuffer) -> Result<(), Error> {
+    let resolve = match framebuffer.resolve {
+        Some(ref resolve) => resolve,
+        None => return Ok(()),
+    };
+    let (width, height) = (framebuffer.size.0 as GLint, framebuffer.size.1 as GLint);
+    unsafe {
+        let mut previous = 0;
+        gl::GetIntegerv(gl::FRAMEBUFFER_BINDING, &mut previous);
+        // the scissor rectangle also clips blits
+        let scissor = gl::IsEnabled(gl::SCISSOR_TEST) == gl::TRUE;
+        gl::Disable(gl::SCISSOR_TEST);
+
+        gl::BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer.fbo);
+        gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, resolve.fbo);
+        gl::BlitFramebuffer(
+            0,
+            0,
+            width,
+            height,
+            0,
+            0,
+            width,
+            height,
+            gl::COLOR_BUFFER_BIT,
+            gl::NEAREST,
+        );
+
+        gl::BindFramebuffer(gl::FRAMEBUFFER, previous as GLuint);
+        if scissor {
+            gl::Enable(gl::SCISSOR_TEST);
+        }
+    }
+    check_gl_error("resolve_framebuffer")
+}
+/// Unbinding restores the viewport from before the first bind, or covers the
+/// whole window when that was lost to a context switch
+pub fn bind_framebuffer(framebuffer: Option<&Framebuffer>) {
+    BOUND.with(|bound| unsafe {
+        let screen = &bound.screen_viewport;
+        match framebuffer {
+            Some(framebuffer) => {
+                if screen.get().is_none() {
+                    let mut rect: [GLint; 4] = [0; 4];
+                    gl::GetIntegerv(gl::VIEWPORT, rect.as_mut_ptr());
+                    screen.set(Some(rect));
+                }
+                gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer.fbo);
+                gl::Viewport(
+                    0,
+                    0,
+                    framebuffer.size.0 as GLsizei,
+                    framebuffer.size.1 as GLsizei,
+                );
+            }
+            None => {
+                gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+                let rect = screen.take().unwrap_or_else(|| {
+                    let (mut width, mut height) = (0, 0);
+                    sdl2::sys::SDL_GL_GetDrawableSize(
+                        sdl2::sys::SDL_GL_GetCurrentWindow(),
+                        &mut width,
+                        &mut height,
+                    );
+                    [0, 0, width, height]
+                });
+                gl::Viewport(rect[0], rect[1], rect[2], rect[3]);
+            }
+        }
+    });
+}
+pub fn set_viewport(x: i32, y: i32, width: u32, height: u32) {
+    unsafe {
+        gl::Viewport(x, y, width as GLsizei, height as GLsizei);
+    }
+}
+pub fn create_program(label: &str, vs: &str, fs: &str) -> Result<Program, RenderError> {
+    let vs = GLVertexShader::new(label, vs)?;
+    let fs = GLFragmentShader::new(label, fs)?;
+
+    Program::new(label, vs, fs)
+}
+pub fn create_texture(
+    size: (u32, u32),
+    format: TextureFormat,
+    filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
+) -> Result<Texture, RenderError> {
+    new_texture(size, format, filtering, wrap, None)
+}
+pub fn create_texture_from_image(
+    image: &Image,
+    filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
+) -> Result<Texture, RenderError> {
+    new_texture(
+        (image.width, image.height),
+        TextureFormat::Rgba8,
+        filtering,
+        wrap,
+        Some(&image.data),
+    )
+}
+fn new_texture(
+    size: (u32, u32),
+    format: TextureFormat,
+    filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
+    data: Option<&[u8]>,
+) -> Result<Texture, RenderError> {
+    let mipmaps = match filtering {
+        Some(TextureFiltering::Trilinear) => true,
+        _ => false,
+    };
+    let filtering = filtering.map(gl_filtering);
+    let wrap = wrap.map(gl_wrap);
+
+    let texture = Texture::new(size, format, filtering, wrap, mipmaps, data);
+    gl_errors("create_texture")?;
+    Ok(texture)
+}
+pub fn create_compressed_texture(
+    format: CompressedFormat,
+    size: (u32, u32),
+    levels: &[&[u8]],
+    filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
+) -> Result<Texture, Error> {
+    let filtering = filtering.map(gl_filtering);
+    let wrap = wrap.map(gl_wrap);
+
+    let texture = Texture::new_compressed(size, format, filtering, wrap, levels);
+    check_gl_error("create_compressed_texture")?;
+    Ok(texture)
+}
+// ETC2 is part of GLES 3.0, the others are extensions
+pub fn compressed_format_supported(format: CompressedFormat) -> bool {
+    match format {
+        CompressedFormat::Dxt1 | CompressedFormat::Dxt5 => {
+            has_extension("GL_EXT_texture_compression_s3tc")
+        }
+        CompressedFormat::Etc2Rgb8 | CompressedFormat::Etc2Rgba8 => true,
+        CompressedFormat::Astc4x4 => has_extension("GL_KHR_texture_compression_astc_ldr"),
+    }
+}
+
+// minification and magnification filter
+fn gl_filtering(filtering: TextureFiltering) -> (GLenum, GLenum) {
+    match filtering {
+        TextureFiltering::Linear => (gl::LINEAR, gl::LINEAR),
+        TextureFiltering::Nearest => (gl::NEAREST, gl::NEAREST),
+        TextureFiltering::Trilinear => (gl::LINEAR_MIPMAP_LINEAR, gl::LINEAR),
+    }
+}
+
+fn gl_wrap(wrap: TextureWrap) -> GLenum {
+    match wrap {
+        TextureWrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+        TextureWrap::Repeat => gl::REPEAT,
+        TextureWrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+    }
+}
+
+pub fn create_sampler(filtering: TextureFiltering, wrap: TextureWrap) -> Result<Sampler, Error> {
+    let (min_filter, mag_filter) = gl_filtering(filtering);
+    let wrap = gl_wrap(wrap);
+    let mut sampler = 0;
+    unsafe {
+        gl::GenSamplers(1, &mut sampler);
+        gl::SamplerParameteri(sampler, gl::TEXTURE_MIN_FILTER, min_filter as GLint);
+        gl::SamplerParameteri(sampler, gl::TEXTURE_MAG_FILTER, mag_filter as GLint);
+        gl::SamplerParameteri(sampler, gl::TEXTURE_WRAP_S, wrap as GLint);
+        gl::SamplerParameteri(sampler, gl::TEXTURE_WRAP_T, wrap as GLint);
+    }
+    let sampler = Sampler {
+        handle: Rc::new(SamplerHandle(sampler)),
+    };
+    check_gl_error("create_sampler")?;
+    Ok(sampler)
+}
+
+pub fn render_vertices<V: Vertex>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    vertices: &[V],
+) -> Result<(), Error> {
+    setup_vertices(vertex_buffer, program, vertices)?;
+    unsafe {
+        gl::DrawArrays(gl_primitive(primitive), 0, vertices.len() as GLsizei);
+    }
+
+    check_gl_error("render_vertices")
+}
+
+pub fn render_indexed<V: Vertex, I: Index>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    vertices: &[V],
+    indices: &[I],
+) -> Result<(), Error> {
+    setup_vertices(vertex_buffer, program, vertices)?;
+    unsafe {
+        // the element buffer binding is stored in the bound vertex array
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, vertex_buffer.ebo);
+        upload_buffer(
+            gl::ELEMENT_ARRAY_BUFFER,
+            vertex_buffer.usage,
+            indices.len() * mem::size_of::<I>(),
+            indices.as_ptr() as *const c_void,
+        );
+        vertex_buffer
+            .index_bytes
+            .set(indices.len() * mem::size_of::<I>());
+
+        gl::DrawElements(
+            gl_primitive(primitive),
+            indices.len() as GLsizei,
+            gl_index_type(I::index_type()),
+            ptr::null(),
+        );
     }
-}
 
-pub struct Program {
-    uniforms: Vec<(String, Uniform)>,
-    gl_ref: GLuint,
+    check_gl_error("render_indexed")
 }
 
-#[derive(Clone)]
-pub enum Uniform {
-    Vec2((f32, f32)),
-    Texture(Texture),
+pub fn draw_arrays_raw<V: Vertex>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    first: u32,
+    count: u32,
+) -> Result<(), Error> {
+    unsafe {
+        bind_vertex_attributes::<V>(vertex_buffer, program)?;
+        gl::DrawArrays(gl_primitive(primitive), first as GLint, count as GLsizei);
+    }
+
+    check_gl_error("draw_arrays_raw")
 }
 
-impl Program {
-    fn new(vertex_shader: GLVertexShader, frag_shader: GLFragmentShader) -> Result<Program, Error> {
-        Ok(Program {
-            uniforms: Vec::new(),
-            gl_ref: link_program(vertex_shader.gl_ref(), frag_shader.gl_ref())?,
-        })
-    }
-    fn gl_ref(&self) -> GLuint {
-        self.gl_ref
+pub fn draw_elements_raw<V: Vertex>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    index_type: IndexType,
+    first: u32,
+    count: u32,
+) -> Result<(), Error> {
+    unsafe {
+        bind_vertex_attributes::<V>(vertex_buffer, program)?;
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, vertex_buffer.ebo);
+        gl::DrawElements(
+            gl_primitive(primitive),
+            count as GLsizei,
+            gl_index_type(index_type),
+            (first as usize * index_type.size()) as *const c_void,
+        );
     }
 
-    pub fn set_uniform(&mut self, name: &str, uniform: Uniform) {
-        self.uniforms.push((name.into(), uniform));
-    }
-    pub fn uniforms(&self) -> impl Iterator<Item = &(String, Uniform)> {
-        self.uniforms.iter()
-    }
+    check_gl_error("draw_elements_raw")
 }
 
-pub fn screen_size() -> (i32, i32) {
-    let mut rect: [GLint; 4] = [0; 4];
+// points the attributes of the program at the vertices already in the buffer
+unsafe fn bind_vertex_attributes<V: Vertex>(
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+) -> Result<(), Error> {
+    bind_vertex_array(vertex_buffer.vao);
+    gl::BindBuffer(gl::ARRAY_BUFFER, vertex_buffer.vbo);
+    setup_attributes::<V>(program, 0)
+}
+
+pub fn render_instanced<V: Vertex, I: Vertex>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    vertices: &[V],
+    instances: &[I],
+) -> Result<(), Error> {
+    setup_vertices(vertex_buffer, program, vertices)?;
     unsafe {
-        gl::GetIntegerv(gl::VIEWPORT, rect.as_mut_ptr() as *mut GLint);
+        gl::BindBuffer(gl::ARRAY_BUFFER, vertex_buffer.instance_vbo);
+        upload_buffer(
+            gl::ARRAY_BUFFER,
+            vertex_buffer.usage,
+            instances.len() * I::stride(),
+            instances.as_ptr() as *const c_void,
+        );
+        setup_attributes::<I>(program, 1)?;
+
+        gl::DrawArraysInstanced(
+            gl_primitive(primitive),
+            0,
+            vertices.len() as GLsizei,
+            instances.len() as GLsizei,
+        );
     }
-    (rect[2], rect[3])
+
+    check_gl_error("render_instanced")
 }
-pub fn create_vertex_buffer() -> Result<(GLuint, GLuint), Error> {
-    let mut vao = 0;
-    let mut vbo = 0;
 
-    unsafe {
-        gl::GenVertexArrays(1, &mut vao);
-        gl::GenBuffers(1, &mut vbo);
+unsafe fn upload_buffer(target: GLenum, usage: GLenum, size: usize, data: *const c_void) {
+    if usage == gl::STATIC_DRAW {
+        gl::BufferData(target, size as GLsizeiptr, data, usage);
+    } else {
+        // orphan the old storage so the driver can hand out a fresh block
+        // instead of synchronizing with draws still using it
+        gl::BufferData(target, size as GLsizeiptr, ptr::null(), usage);
+        gl::BufferSubData(target, 0, size as GLsizeiptr, data);
     }
+}
 
-    Ok((vao, vbo))
+fn max_texture_units() -> u32 {
+    BOUND.with(|bound| {
+        let max = &bound.max_texture_units;
+        max.get().unwrap_or_else(|| {
+            let mut units = 0;
+            unsafe {
+                gl::GetIntegerv(gl::MAX_TEXTURE_IMAGE_UNITS, &mut units);
+            }
+            max.set(Some(units as u32));
+            units as u32
+        })
+    })
 }
-pub fn create_program(vs: &str, fs: &str) -> Result<Program, Error> {
-    let vs = GLVertexShader::new(vs)?;
-    let fs = GLFragmentShader::new(fs)?;
 
-    Ok(Program::new(vs, fs)?)
+fn gl_index_type(index_type: IndexType) -> GLenum {
+    match index_type {
+        IndexType::U16 => gl::UNSIGNED_SHORT,
+        IndexType::U32 => gl::UNSIGNED_INT,
+    }
 }
-pub fn create_texture(
-    size: (u32, u32),
-    filtering: Option<TextureFiltering>,
-) -> Result<Texture, Error> {
-    let filtering = filtering.map(|f| match f {
-        TextureFiltering::Linear => gl::LINEAR,
-        TextureFiltering::Nearest => gl::NEAREST,
-    });
 
-    Ok(Texture::new(size, filtering))
+fn gl_primitive(primitive: Primitive) -> GLenum {
+    match primitive {
+        Primitive::Triangles => gl::TRIANGLES,
+        Primitive::TriangleStrip => gl::TRIANGLE_STRIP,
+        Primitive::Lines => gl::LINES,
+        Primitive::LineStrip => gl::LINE_STRIP,
+        Primitive::Points => gl::POINTS,
+    }
 }
 
-pub fn render_vertices<V: Vertex>(
-    vertex_buffer: &(GLuint, GLuint),
+fn setup_vertices<V: Vertex>(
+    vertex_buffer: &VertexBuffer,
     program: &Program,
-    vertices: &Vec<V>,
+    vertices: &[V],
 ) -> Result<(), Error> {
     unsafe {
-        gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-        gl::Enable(gl::BLEND);
-
         // push vertex data
-        let &(vao, vbo) = vertex_buffer;
-        gl::BindVertexArray(vao);
+        bind_vertex_array(vertex_buffer.vao);
 
-        gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
-        gl::BufferData(
+        gl::BindBuffer(gl::ARRAY_BUFFER, vertex_buffer.vbo);
+        upload_buffer(
             gl::ARRAY_BUFFER,
-            (vertices.len() * V::stride()) as GLsizeiptr,
-            mem::transmute(vertices.as_ptr()),
-            gl::STATIC_DRAW,
+            vertex_buffer.usage,
+            vertices.len() * V::stride(),
+            vertices.as_ptr() as *const c_void,
         );
+        vertex_buffer.vertex_bytes.set(vertices.len() * V::stride());
 
-        gl::UseProgram(program.gl_ref());
+        use_program(program.gl_ref());
 
         // set uniforms
+        let max_texture_units = max_texture_units();
         let mut texture_index = 0;
         for &(ref name, ref uniform) in program.uniforms() {
-            let attr = gl::GetUniformLocation(
-                program.gl_ref(),
-                CString::new(name.clone().into_bytes()).unwrap().as_ptr(),
-            );
+            let attr = program.uniform_location(name)?;
+            if attr < 0 {
+                continue;
+            }
             match uniform {
+                &Uniform::Float(v) => gl::Uniform1f(attr, v as GLfloat),
+                &Uniform::Int(v) => gl::Uniform1i(attr, v as GLint),
                 &Uniform::Vec2(gl_vec2) => {
                     gl::Uniform2f(attr, gl_vec2.0 as GLfloat, gl_vec2.1 as GLfloat)
                 }
+                &Uniform::Vec3(gl_vec3) => gl::Uniform3f(
+                    attr,
+                    gl_vec3.0 as GLfloat,
+                    gl_vec3.1 as GLfloat,
+                    gl_vec3.2 as GLfloat,
+                ),
+                &Uniform::Vec4(gl_vec4) => gl::Uniform4f(
+                    attr,
+                    gl_vec4.0 as GLfloat,
+                    gl_vec4.1 as GLfloat,
+                    gl_vec4.2 as GLfloat,
+                    gl_vec4.3 as GLfloat,
+                ),
+                &Uniform::Mat3(ref gl_mat3) => {
+                    gl::UniformMatrix3fv(attr, 1, gl::FALSE, gl_mat3.as_ptr())
+                }
+                &Uniform::Mat4(ref gl_mat4) => {
+                    gl::UniformMatrix4fv(attr, 1, gl::FALSE, gl_mat4.as_ptr())
+                }
                 &Uniform::Texture(ref gl_texture) => {
-                    gl::ActiveTexture(gl::TEXTURE0 + texture_index);
+                    check_limit("textures per draw", texture_index + 1, max_texture_units)?;
+                    active_texture(gl::TEXTURE0 + texture_index);
+                    gl::BindTexture(gl::TEXTURE_2D, gl_texture.gl_ref());
+                    bind_sampler(texture_index, 0);
+                    gl::Uniform1i(attr, texture_index as GLint);
+                    texture_index += 1;
+                }
+                &Uniform::SampledTexture(ref gl_texture, ref sampler) => {
+                    check_limit("textures per draw", texture_index + 1, max_texture_units)?;
+                    active_texture(gl::TEXTURE0 + texture_index);
                     gl::BindTexture(gl::TEXTURE_2D, gl_texture.gl_ref());
+                    bind_sampler(texture_index, sampler.handle.0);
                     gl::Uniform1i(attr, texture_index as GLint);
                     texture_index += 1;
                 }
             }
         }
 
-        // define vertex format
-        let mut step = 0;
-        for (attr_name, attr_count, attr_type) in V::attributes() {
-            let attr =
-                gl::GetAttribLocation(program.gl_ref(), CString::new(attr_name).unwrap().as_ptr());
-            gl::EnableVertexAttribArray(attr as GLuint);
-            match attr_type {
-                VertexAttributeType::Float => {
-                    gl::VertexAttribPointer(
-                        attr as GLuint,
-                        attr_count as GLsizei,
-                        gl::FLOAT,
-                        gl::FALSE as GLboolean,
-                        V::stride() as GLsizei,
-                        step as *const c_void,
-                    );
-                }
-                VertexAttributeType::Unsigned => {
-                    gl::VertexAttribPointer(
-                        attr as GLuint,
-                        attr_count as GLsizei,
-                        gl::UNSIGNED_INT,
-                        gl::FALSE as GLboolean,
-                        V::stride() as GLsizei,
-                        step as *const c_void,
-                    );
-                }
-            }
+        setup_attributes::<V>(program, 0)?;
+    }
+
+    check_gl_error("vertex setup")
+}
 
-            step += attr_count * attr_type.size();
+// Points the attributes of `V` at the bound array buffer, advancing once per
+// vertex with a divisor of 0 or once per instance with 1
+unsafe fn setup_attributes<V: Vertex>(program: &Program, divisor: GLuint) -> Result<(), Error> {
+    check_vertex_layout::<V>()?;
+    let mut step = 0;
+    for (attr_name, attr_count, attr_type) in V::attributes() {
+        let attr = program.attrib_location(&attr_name)?;
+        if attr < 0 {
+            return Err(format_err!(
+                "could not find location of attribute {}",
+                attr_name
+            ));
+        }
+        gl::EnableVertexAttribArray(attr as GLuint);
+        gl::VertexAttribDivisor(attr as GLuint, divisor);
+        let (gl_type, normalized) = match attr_type {
+            VertexAttributeType::Float => (gl::FLOAT, gl::FALSE),
+            VertexAttributeType::Unsigned => (gl::UNSIGNED_INT, gl::FALSE),
+            VertexAttributeType::Int => (gl::INT, gl::FALSE),
+            VertexAttributeType::UnsignedByteNormalized => (gl::UNSIGNED_BYTE, gl::TRUE),
+            VertexAttributeType::ShortNormalized => (gl::SHORT, gl::TRUE),
+        };
+        match attr_type {
+            // VertexAttribPointer would convert these to floats
+            VertexAttributeType::Unsigned | VertexAttributeType::Int => {
+                gl::VertexAttribIPointer(
+                    attr as GLuint,
+                    attr_count as GLsizei,
+                    gl_type,
+                    V::stride() as GLsizei,
+                    step as *const c_void,
+                );
+            }
+            _ => {
+                gl::VertexAttribPointer(
+                    attr as GLuint,
+                    attr_count as GLsizei,
+                    gl_type,
+                    normalized,
+                    V::stride() as GLsizei,
+                    step as *const c_void,
+                );
+            }
         }
 
-        gl::DrawArrays(gl::TRIANGLES, 0, vertices.len() as GLsizei);
+        step += attr_count * attr_type.size();
     }
 
     Ok(())
 }
 
-pub fn clear(color: Option<(f32, f32, f32, f32)>) {
-    let (r, g, b, a) = color.unwrap_or((0.0, 0.0, 0.0, 1.0));
+pub fn set_blend_mode(mode: BlendMode) {
+    if BOUND.with(|bound| bound.blend_mode.replace(Some(mode))) == Some(mode) {
+        return;
+    }
+    let (src, dst) = match blend_factors(mode) {
+        Some(factors) => factors,
+        None => {
+            unsafe {
+                gl::Disable(gl::BLEND);
+            }
+            return;
+        }
+    };
+    unsafe {
+        gl::Enable(gl::BLEND);
+        gl::BlendEquation(gl::FUNC_ADD);
+        gl::BlendFunc(src, dst);
+    }
+}
+
+pub fn set_framebuffer_srgb(enabled: bool) -> Result<(), Error> {
+    // core in desktop GL, GLES only allows it with EXT_sRGB_write_control
+    let gles = unsafe { gl_string(gl::GetString(gl::VERSION)) }.starts_with("OpenGL ES");
+    if gles && !has_extension("GL_EXT_sRGB_write_control") {
+        return Err(format_err!(
+            "sRGB writes can't be switched without GL_EXT_sRGB_write_control"
+        ));
+    }
+    unsafe {
+        if enabled {
+            gl::Enable(gl::FRAMEBUFFER_SRGB);
+        } else {
+            gl::Disable(gl::FRAMEBUFFER_SRGB);
+        }
+    }
+    check_gl_error("set_framebuffer_srgb")
+}
+
+pub fn set_line_width(width: f32) -> Result<(), Error> {
+    // out of range widths are an error on some drivers and clamped on others
+    let (min, max) = line_width_range();
+    if width.is_nan() || width < min || width > max {
+        return Err(format_err!(
+            "line width {} is outside the supported range of {} to {}",
+            width,
+            min,
+            max
+        ));
+    }
+    unsafe {
+        gl::LineWidth(width);
+    }
+    check_gl_error("set_line_width")
+}
+
+pub fn line_width_range() -> (f32, f32) {
+    let mut range = [0.0; 2];
+    unsafe {
+        gl::GetFloatv(gl::ALIASED_LINE_WIDTH_RANGE, range.as_mut_ptr());
+    }
+    (range[0], range[1])
+}
+
+// only defined for GLES, the gl crate is generated from the core profile
+const ALIASED_POINT_SIZE_RANGE: GLenum = 0x846D;
+
+pub fn point_size_range() -> (f32, f32) {
+    let mut range = [0.0; 2];
+    unsafe {
+        gl::GetFloatv(ALIASED_POINT_SIZE_RANGE, range.as_mut_ptr());
+    }
+    (range[0], range[1])
+}
+
+pub fn set_stencil_test(enabled: bool) {
+    unsafe {
+        if enabled {
+            gl::Enable(gl::STENCIL_TEST);
+        } else {
+            gl::Disable(gl::STENCIL_TEST);
+        }
+    }
+}
+
+pub fn set_stencil_func(func: StencilFunc, reference: u8, mask: u8) {
+    let func = match func {
+        StencilFunc::Never => gl::NEVER,
+        StencilFunc::Less => gl::LESS,
+        StencilFunc::Equal => gl::EQUAL,
+        StencilFunc::LessEqual => gl::LEQUAL,
+        StencilFunc::Greater => gl::GREATER,
+        StencilFunc::NotEqual => gl::NOTEQUAL,
+        StencilFunc::GreaterEqual => gl::GEQUAL,
+        StencilFunc::Always => gl::ALWAYS,
+    };
+    unsafe {
+        gl::StencilFunc(func, reference as GLint, mask as GLuint);
+    }
+}
+
+fn gl_stencil_op(op: StencilOp) -> GLenum {
+    match op {
+        StencilOp::Keep => gl::KEEP,
+        StencilOp::Zero => gl::ZERO,
+        StencilOp::Replace => gl::REPLACE,
+        StencilOp::Increment => gl::INCR,
+        StencilOp::IncrementWrap => gl::INCR_WRAP,
+        StencilOp::Decrement => gl::DECR,
+        StencilOp::DecrementWrap => gl::DECR_WRAP,
+        StencilOp::Invert => gl::INVERT,
+    }
+}
+
+pub fn set_stencil_op(fail: StencilOp, depth_fail: StencilOp, pass: StencilOp) {
+    unsafe {
+        gl::StencilOp(
+            gl_stencil_op(fail),
+            gl_stencil_op(depth_fail),
+            gl_stencil_op(pass),
+        );
+    }
+}
+
+pub fn set_stencil_write_mask(mask: u8) {
+    unsafe {
+        gl::StencilMask(GLuint::from(mask));
+    }
+}
+
+pub fn set_color_mask(red: bool, green: bool, blue: bool, alpha: bool) {
+    let mask = |enabled| if enabled { gl::TRUE } else { gl::FALSE };
+    unsafe {
+        gl::ColorMask(mask(red), mask(green), mask(blue), mask(alpha));
+    }
+}
+
+/// The scissor rectangle has a bottom left origin like `glScissor`
+pub fn render_state() -> RenderState {
+    let mut viewport = [0; 4];
+    let mut scissor = [0; 4];
+    let mut color_mask = [0; 4];
+    let (mut src, mut dst) = (0, 0);
+    unsafe {
+        gl::GetIntegerv(gl::VIEWPORT, viewport.as_mut_ptr());
+        gl::GetIntegerv(gl::SCISSOR_BOX, scissor.as_mut_ptr());
+        gl::GetBooleanv(gl::COLOR_WRITEMASK, color_mask.as_mut_ptr());
+        gl::GetIntegerv(gl::BLEND_SRC_RGB, &mut src);
+        gl::GetIntegerv(gl::BLEND_DST_RGB, &mut dst);
+        let enabled = |capability| gl::IsEnabled(capability) == gl::TRUE;
+
+        let blend_mode = if enabled(gl::BLEND) {
+            blend_mode_from_factors(src as GLenum, dst as GLenum)
+        } else {
+            Some(BlendMode::None)
+        };
+        RenderState {
+            blend_mode,
+            depth_test: enabled(gl::DEPTH_TEST),
+            stencil_test: enabled(gl::STENCIL_TEST),
+            viewport: (
+                viewport[0],
+                viewport[1],
+                viewport[2] as u32,
+                viewport[3] as u32,
+            ),
+            scissor: if enabled(gl::SCISSOR_TEST) {
+                Some((scissor[0], scissor[1], scissor[2], scissor[3]))
+            } else {
+                None
+            },
+            color_mask: (
+                color_mask[0] == gl::TRUE,
+                color_mask[1] == gl::TRUE,
+                color_mask[2] == gl::TRUE,
+                color_mask[3] == gl::TRUE,
+            ),
+        }
+    }
+}
+
+pub fn set_depth_test(enabled: bool) {
+    unsafe {
+        if enabled {
+            gl::Enable(gl::DEPTH_TEST);
+        } else {
+            gl::Disable(gl::DEPTH_TEST);
+        }
+    }
+}
+
+/// Takes the rectangle with a bottom left origin like `glScissor`
+pub fn set_scissor(rect: Option<(i32, i32, i32, i32)>) {
+    unsafe {
+        match rect {
+            Some((x, y, width, height)) => {
+                gl::Enable(gl::SCISSOR_TEST);
+                gl::Scissor(x, y, width, height);
+            }
+            None => gl::Disable(gl::SCISSOR_TEST),
+        }
+    }
+}
+
+/// Reads RGBA pixels from the bound framebuffer, rows are ordered bottom to top
+pub fn read_pixels(x: i32, y: i32, width: u32, height: u32) -> Result<Vec<u8>, Error> {
+    let mut data = vec![0u8; (width * height * 4) as usize];
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(
+            x as GLint,
+            y as GLint,
+            width as GLsizei,
+            height as GLsizei,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data.as_mut_ptr() as *mut c_void,
+        );
+    }
+    check_gl_error("read_pixels")?;
+
+    Ok(data)
+}
+
+// Clears the buffers given a value and leaves the others untouched
+pub fn clear(color: Option<(f32, f32, f32, f32)>, depth: Option<f32>, stencil: Option<u8>) {
+    let mut mask = 0;
     unsafe {
-        gl::ClearColor(r, g, b, a);
-        gl::Clear(gl::COLOR_BUFFER_BIT);
+        if let Some((r, g, b, a)) = color {
+            gl::ClearColor(r, g, b, a);
+            mask |= gl::COLOR_BUFFER_BIT;
+        }
+        if let Some(depth) = depth {
+            gl::ClearDepthf(depth);
+            mask |= gl::DEPTH_BUFFER_BIT;
+        }
+        if let Some(stencil) = stencil {
+            gl::ClearStencil(stencil as GLint);
+            mask |= gl::STENCIL_BUFFER_BIT;
+        }
+        if mask != 0 {
+            gl::Clear(mask);
+        }
+    }
+}
+
+// the renderer of every window, GL state lives in the current context rather
+// than here
+pub struct Backend;
+
+impl PlatformRenderer for Backend {
+    type Texture = Texture;
+    type Program = Program;
+    type VertexBuffer = VertexBuffer;
+
+    fn create_texture(
+        &self,
+        size: (u32, u32),
+        format: TextureFormat,
+        filtering: Option<TextureFiltering>,
+        wrap: Option<TextureWrap>,
+    ) -> Result<Texture, RenderError> {
+        create_texture(size, format, filtering, wrap)
+    }
+    fn create_program(&self, label: &str, vs: &str, fs: &str) -> Result<Program, RenderError> {
+        create_program(label, vs, fs)
+    }
+    fn create_vertex_buffer(&self, usage: BufferUsage) -> Result<VertexBuffer, Error> {
+        create_vertex_buffer(usage)
+    }
+    fn render_vertices<V: Vertex>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &VertexBuffer,
+        program: &Program,
+        vertices: &[V],
+    ) -> Result<(), Error> {
+        render_vertices(primitive, vertex_buffer, program, vertices)
+    }
+    fn render_indexed<V: Vertex, I: Index>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &VertexBuffer,
+        program: &Program,
+        vertices: &[V],
+        indices: &[I],
+    ) -> Result<(), Error> {
+        render_indexed(primitive, vertex_buffer, program, vertices, indices)
+    }
+    fn set_blend_mode(&self, mode: BlendMode) {
+        set_blend_mode(mode)
+    }
+    fn viewport(&self) -> (i32, i32, u32, u32) {
+        viewport()
+    }
+    fn set_viewport(&self, x: i32, y: i32, width: u32, height: u32) {
+        set_viewport(x, y, width, height)
+    }
+    fn read_pixels(&self, x: i32, y: i32, width: u32, height: u32) -> Result<Vec<u8>, Error> {
+        read_pixels(x, y, width, height)
+    }
+    fn clear(&self, color: Option<(f32, f32, f32, f32)>, depth: Option<f32>, stencil: Option<u8>) {
+        clear(color, depth, stencil)
+    }
+}
+
+// Reports any pending GL errors, only checked in debug builds since glGetError
+// stalls the pipeline
+fn check_gl_error(context: &str) -> Result<(), Error> {
+    Ok(gl_errors(context)?)
+}
+
+// check_gl_error for the functions that return a RenderError
+fn gl_errors(context: &str) -> Result<(), RenderError> {
+    if !cfg!(debug_assertions) {
+        return Ok(());
+    }
+
+    let mut errors = Vec::new();
+    loop {
+        let error = unsafe { gl::GetError() };
+        // bail out after a few errors in case of a lost context, which may
+        // keep reporting errors
+        if error == gl::NO_ERROR || errors.len() >= 8 {
+            break;
+        }
+        errors.push(error);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(RenderError::GlError {
+            context: context.into(),
+            codes: errors,
+        })
+    }
+}
+
+fn shader_stage(t: GLenum) -> ShaderStage {
+    if t == gl::VERTEX_SHADER {
+        ShaderStage::Vertex
+    } else {
+        ShaderStage::Fragment
     }
 }
 
@@ -231,9 +1916,9 @@ struct GLVertexShader {
 }
 
 impl GLVertexShader {
-    fn new(src: &str) -> Result<GLVertexShader, Error> {
+    fn new(label: &str, src: &str) -> Result<GLVertexShader, RenderError> {
         Ok(GLVertexShader {
-            gl_ref: compile_shader(src, gl::VERTEX_SHADER)?,
+            gl_ref: compile_shader(label, src, gl::VERTEX_SHADER)?,
         })
     }
     fn gl_ref(&self) -> GLuint {
@@ -241,14 +1926,22 @@ impl GLVertexShader {
     }
 }
 
+impl Drop for GLVertexShader {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteShader(self.gl_ref);
+        }
+    }
+}
+
 struct GLFragmentShader {
     gl_ref: GLuint,
 }
 
 impl GLFragmentShader {
-    fn new(src: &str) -> Result<GLFragmentShader, Error> {
+    fn new(label: &str, src: &str) -> Result<GLFragmentShader, RenderError> {
         Ok(GLFragmentShader {
-            gl_ref: compile_shader(src, gl::FRAGMENT_SHADER)?,
+            gl_ref: compile_shader(label, src, gl::FRAGMENT_SHADER)?,
         })
     }
     fn gl_ref(&self) -> GLuint {
@@ -256,11 +1949,24 @@ impl GLFragmentShader {
     }
 }
 
-fn compile_shader(src: &str, t: GLenum) -> Result<GLuint, Error> {
+impl Drop for GLFragmentShader {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteShader(self.gl_ref);
+        }
+    }
+}
+
+fn compile_shader(label: &str, src: &str, t: GLenum) -> Result<GLuint, RenderError> {
+    let c_str = CString::new(src.as_bytes()).map_err(|_| RenderError::ShaderCompile {
+        label: label.into(),
+        stage: shader_stage(t),
+        log: "the source contains a NUL byte".into(),
+        source: src.into(),
+    })?;
     let shader;
     unsafe {
         shader = gl::CreateShader(t);
-        let c_str = CString::new(src.as_bytes()).expect("Error converting src string to c string");
         gl::ShaderSource(shader, 1, &c_str.as_ptr(), ptr::null());
         gl::CompileShader(shader);
 
@@ -276,17 +1982,21 @@ fn compile_shader(src: &str, t: GLenum) -> Result<GLuint, Error> {
                 ptr::null_mut(),
                 log_buffer.as_mut_ptr() as *mut GLchar,
             );
-            return Err(format_err!(
-                "Error compiling shader: {}",
-                std::str::from_utf8(log_buffer.as_slice())
-                    .expect("Shader Info Log not in utf8 format")
-            ));
+            gl::DeleteShader(shader);
+            return Err(RenderError::ShaderCompile {
+                label: label.into(),
+                stage: shader_stage(t),
+                log: String::from_utf8_lossy(&log_buffer)
+                    .trim_end_matches('\0')
+                    .into(),
+                source: src.into(),
+            });
         }
     }
     Ok(shader)
 }
 
-fn link_program(vs: GLuint, fs: GLuint) -> Result<GLuint, Error> {
+fn link_program(label: &str, vs: GLuint, fs: GLuint) -> Result<GLuint, RenderError> {
     let program;
     unsafe {
         program = gl::CreateProgram();
@@ -307,12 +2017,201 @@ fn link_program(vs: GLuint, fs: GLuint) -> Result<GLuint, Error> {
                 ptr::null_mut(),
                 log_buffer.as_mut_ptr() as *mut GLchar,
             );
-            return Err(format_err!(
-                "Error linking program: {}",
-                std::str::from_utf8(log_buffer.as_slice())
-                    .expect("Program Info Log not in utf8 format")
-            ));
+            gl::DeleteProgram(program);
+            return Err(RenderError::ProgramLink {
+                label: label.into(),
+                log: String::from_utf8_lossy(&log_buffer)
+                    .trim_end_matches('\0')
+                    .into(),
+            });
         }
+
+        // the shaders are no longer needed once linked, detach them so they are
+        // freed when dropped
+        gl::DetachShader(program, vs);
+        gl::DetachShader(program, fs);
     }
     Ok(program)
 }
+
+#[test]
+#[ignore = "needs a GL context"]
+fn textures_are_deleted() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|_| {
+        let mut names = Vec::new();
+        for _ in 0..8 {
+            let texture = create_texture((4, 4), TextureFormat::Rgba8, None, None).unwrap();
+            names.push(texture.gl_ref());
+            // a clone keeps the texture alive
+            let clone = texture.clone();
+            drop(texture);
+            assert_eq!(unsafe { gl::IsTexture(clone.gl_ref()) }, gl::TRUE);
+        }
+        for name in names {
+            assert_eq!(unsafe { gl::IsTexture(name) }, gl::FALSE);
+        }
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn programs_and_buffers_are_deleted() {
+    use platform::with_test_renderer;
+
+    impl Vertex for [f32; 2] {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![("position".to_string(), 2, VertexAttributeType::Float)]
+        }
+    }
+
+    with_test_renderer(|_| {
+        let vs = "attribute vec2 position;\n\
+                  void main() { gl_Position = vec4(position, 0.0, 1.0); }";
+        let fs = "precision mediump float;\nvoid main() { gl_FragColor = vec4(1.0); }";
+        let mut names = Vec::new();
+        for _ in 0..8 {
+            let program = create_program("leak test", vs, fs).unwrap();
+            let buffer = create_vertex_buffer(BufferUsage::Dynamic).unwrap();
+            render_indexed(
+                Primitive::Triangles,
+                &buffer,
+                &program,
+                &vec![[0.0, 0.0]; 3],
+                &[0u16, 1, 2],
+            )
+            .unwrap();
+            unsafe {
+                assert_eq!(gl::IsProgram(program.gl_ref), gl::TRUE);
+                assert_eq!(gl::IsBuffer(buffer.vbo), gl::TRUE);
+                assert_eq!(gl::IsBuffer(buffer.ebo), gl::TRUE);
+            }
+            names.push((program.gl_ref, buffer.vbo, buffer.ebo));
+        }
+        // the last program is still current, programs in use are only flagged
+        use_program(0);
+        for (program, vbo, ebo) in names {
+            unsafe {
+                assert_eq!(gl::IsProgram(program), gl::FALSE);
+                assert_eq!(gl::IsBuffer(vbo), gl::FALSE);
+                assert_eq!(gl::IsBuffer(ebo), gl::FALSE);
+            }
+        }
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn gl_errors_name_the_context() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|_| {
+        if !cfg!(debug_assertions) {
+            // checking is compiled out of release builds
+            return;
+        }
+        unsafe {
+            gl::Enable(0xffff);
+        }
+        let message = check_gl_error("enabling a bogus capability")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            message.contains("enabling a bogus capability"),
+            "{}",
+            message
+        );
+        assert!(message.contains("INVALID_ENUM"), "{}", message);
+        // the error was consumed
+        assert!(check_gl_error("nothing").is_ok());
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn texture_unit_limit() {
+    use platform::with_test_renderer;
+
+    #[allow(dead_code)]
+    struct Position(f32, f32);
+    impl Vertex for Position {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![("position".to_string(), 2, VertexAttributeType::Float)]
+        }
+    }
+
+    with_test_renderer(|_| {
+        let vs = "attribute vec2 position;\n\
+                  void main() { gl_Position = vec4(position, 0.0, 1.0); }";
+        let fs = "precision mediump float;\n\
+                  uniform sampler2D a;\nuniform sampler2D b;\nuniform sampler2D c;\n\
+                  void main() {\n\
+                  gl_FragColor = texture2D(a, vec2(0.5)) + texture2D(b, vec2(0.5))\n\
+                  + texture2D(c, vec2(0.5));\n\
+                  }";
+        let mut program = create_program("texture units", vs, fs).unwrap();
+        for name in &["a", "b", "c"] {
+            let texture = create_texture((1, 1), TextureFormat::Rgba8, None, None).unwrap();
+            program.set_uniform(name, Uniform::Texture(texture));
+        }
+        let buffer = create_vertex_buffer(BufferUsage::Static).unwrap();
+        let vertices = [
+            Position(-1.0, -1.0),
+            Position(1.0, -1.0),
+            Position(-1.0, 1.0),
+        ];
+
+        // pretend the GPU has two units, the limit is cached with the context
+        BOUND.with(|bound| bound.max_texture_units.set(Some(2)));
+        let error =
+            render_vertices(Primitive::Triangles, &buffer, &program, &vertices).unwrap_err();
+        match error.downcast_ref::<RenderError>() {
+            Some(&RenderError::LimitExceeded { what, value, max }) => {
+                assert_eq!((what, value, max), ("textures per draw", 3, 2))
+            }
+            _ => panic!("expected a texture unit limit error, got {}", error),
+        }
+
+        // a context switch queries the real limit again
+        reset_bound_state();
+        assert!(max_texture_units() >= 3);
+        render_vertices(Primitive::Triangles, &buffer, &program, &vertices).unwrap();
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn object_labels() {
+    use platform::with_test_renderer;
+
+    fn label_of(identifier: GLenum, name: GLuint) -> String {
+        let mut label = [0u8; 64];
+        let mut length = 0;
+        unsafe {
+            gl::GetObjectLabel(
+                identifier,
+                name,
+                label.len() as GLsizei,
+                &mut length,
+                label.as_mut_ptr() as *mut GLchar,
+            );
+        }
+        String::from_utf8_lossy(&label[..length as usize]).into_owned()
+    }
+
+    with_test_renderer(|_| {
+        if !gl::GetObjectLabel::is_loaded() || !has_extension("GL_KHR_debug") {
+            // labels are skipped without KHR_debug
+            return;
+        }
+        let texture = create_texture((1, 1), TextureFormat::Rgba8, None, None).unwrap();
+        texture.set_label("atlas");
+        assert_eq!(label_of(gl::TEXTURE, texture.gl_ref()), "atlas");
+
+        let buffer = create_vertex_buffer(BufferUsage::Static).unwrap();
+        buffer.set_label("sprites");
+        assert_eq!(label_of(gl::VERTEX_ARRAY, buffer.vao), "sprites");
+        assert_eq!(label_of(gl::BUFFER, buffer.vbo), "sprites vertices");
+    });
+}
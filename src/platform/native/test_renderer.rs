@@ -0,0 +1,91 @@
+// Runs the GL tests against a hidden window. SDL can only be used from the
+// thread that initialized it while the tests run on threads of their own, so a
+// single thread owns the window and runs the tests one after the other, each
+// with a fresh GL context so that no state leaks from one test into the next.
+//
+// The tests are #[ignore]d since they need a display or an EGL capable driver,
+// `cargo test -- --ignored` runs them. Without a display Mesa's software
+// renderer works with `SDL_VIDEODRIVER=offscreen`.
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+use sdl2;
+
+use super::window::{GLContext, Window};
+use math::Vec2;
+use platform::Window as PlatformWindow;
+use rendering::{BlendMode, RenderTarget, Renderer};
+use window::WindowSettings;
+
+type Test = Box<dyn FnMut(&Renderer) + Send>;
+type TestResult = Result<(), Box<dyn Any + Send>>;
+type Job = (Test, Sender<TestResult>);
+
+struct TestTarget<'a> {
+    window: &'a Window,
+    gl_context: GLContext,
+}
+
+impl<'a> RenderTarget for TestTarget<'a> {
+    fn make_current(&self) {
+        self.window.gl_set_current(&self.gl_context);
+    }
+}
+
+static TESTS: Mutex<Option<Sender<Job>>> = Mutex::new(None);
+
+/// Runs `test` with a renderer of a 64x64 window, panics from it fail the
+/// calling test
+pub fn with_test_renderer<F: FnOnce(&Renderer) + Send + 'static>(test: F) {
+    let mut test = Some(test);
+    let (done, result) = channel();
+    TESTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get_or_insert_with(start_test_thread)
+        .send((
+            Box::new(move |renderer| (test.take().unwrap())(renderer)),
+            done,
+        ))
+        .expect("the GL test thread has stopped");
+    if let Err(panic) = result.recv().expect("the GL test thread has stopped") {
+        panic::resume_unwind(panic);
+    }
+}
+
+fn start_test_thread() -> Sender<Job> {
+    let (tests, received) = channel::<Job>();
+    thread::spawn(move || {
+        let window = sdl2::init()
+            .and_then(|sdl| sdl.video())
+            .map_err(|e| format_err!("{}", e))
+            .and_then(|video| {
+                let settings = WindowSettings::new()
+                    .title("embla tests".to_string())
+                    .size(Vec2::new(64, 64));
+                Window::with_video(&video, settings, true).map(|window| (video, window))
+            });
+        for (mut test, done) in received {
+            let result = match window {
+                Ok((_, ref window)) => panic::catch_unwind(AssertUnwindSafe(|| {
+                    let target = TestTarget {
+                        window,
+                        gl_context: window.gl_create_context().unwrap(),
+                    };
+                    let renderer = Renderer::new(&target);
+                    renderer.set_blend_mode(BlendMode::default());
+                    test(&renderer);
+                })),
+                Err(ref e) => {
+                    let message = format!("no GL context for the test: {}", e);
+                    Err(Box::new(message) as Box<dyn Any + Send>)
+                }
+            };
+            let _ = done.send(result);
+        }
+    });
+    tests
+}
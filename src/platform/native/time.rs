@@ -0,0 +1,39 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static START: Instant = Instant::now();
+}
+
+/// Seconds since the first call
+pub fn now() -> f64 {
+    START.with(|start| {
+        let elapsed = start.elapsed();
+        elapsed.as_secs() as f64 + (elapsed.subsec_nanos() as f64 / 1_000_000_000.0)
+    })
+}
+
+// used instead of the display refresh rate, which is only known once a window
+// has been created
+const FRAME_TIME: f64 = 1.0 / 60.0;
+
+pub fn run_loop<F: FnMut(f64) -> bool + 'static>(mut f: F) {
+    let mut next_frame = now();
+    while f(now()) {
+        next_frame += FRAME_TIME;
+        let until_next = next_frame - now();
+        if until_next > 0.0 {
+            // sleeping can overshoot, leave the last millisecond to the spin below
+            if until_next > 0.001 {
+                thread::sleep(Duration::new(
+                    0,
+                    ((until_next - 0.001) * 1_000_000_000.0) as u32,
+                ));
+            }
+            while now() < next_frame {}
+        } else {
+            // fell behind, don't try to catch up on the missed frames
+            next_frame = now();
+        }
+    }
+}
@@ -1,60 +1,196 @@
+use std::cell::{Cell, RefCell};
+
 use failure::Error;
 use gl;
 use sdl2;
-use sdl2::video::{GLProfile, SwapInterval};
+use sdl2::mouse::{Cursor, SystemCursor};
+use sdl2::video::{FullscreenType, GLProfile, SwapInterval};
 
+use super::rendering;
 use super::Context;
-use window::WindowSettings;
+use platform::Window as PlatformWindow;
+use window::{CursorStyle, WindowSettings};
 
-pub struct Window(sdl2::video::Window);
+pub struct Window {
+    // the title can only be changed through a mutable reference
+    window: RefCell<sdl2::video::Window>,
+    cursor_visible: Cell<bool>,
+    cursor_style: Cell<CursorStyle>,
+    // the system cursor stays active for as long as it is alive
+    cursor: RefCell<Option<Cursor>>,
+}
 
 pub struct GLContext(sdl2::video::GLContext);
 
 impl Window {
     pub fn new(context: &mut Context, settings: WindowSettings) -> Result<Window, Error> {
-        let WindowSettings { title, size, .. } = settings;
+        Window::with_video(&context.video, settings, false)
+    }
+
+    /// Hidden windows are only used for the GL tests, which have no `Context`
+    pub fn with_video(
+        video: &sdl2::VideoSubsystem,
+        settings: WindowSettings,
+        hidden: bool,
+    ) -> Result<Window, Error> {
+        let WindowSettings {
+            title,
+            size,
+            high_dpi,
+            gl_debug,
+            ..
+        } = settings;
 
         let title = title.ok_or_else(|| format_err!("missing title in WindowSettings"))?;
         let size = size.ok_or_else(|| format_err!("missing size in WindowSettings"))?;
-        let window = context
-            .video
-            .window(&title, size.x, size.y)
-            .opengl()
-            .build()?;
-
-        let gl_attr = context.video.gl_attr();
-        gl_attr.set_context_major_version(2);
+        let mut builder = video.window(&title, size.x, size.y);
+        builder.opengl();
+        if hidden {
+            builder.hidden();
+        }
+        if high_dpi {
+            builder.allow_highdpi();
+        }
+        let window = builder.build()?;
+
+        let gl_attr = video.gl_attr();
+        gl_attr.set_context_major_version(3);
         gl_attr.set_context_minor_version(0);
         gl_attr.set_context_profile(GLProfile::GLES);
         gl_attr.set_double_buffer(false);
-        gl_attr.set_depth_size(1);
+        gl_attr.set_depth_size(24);
+        gl_attr.set_stencil_size(8);
+        // needed for set_framebuffer_srgb to have an effect
+        gl_attr.set_framebuffer_srgb_compatible(true);
+        if gl_debug {
+            gl_attr.set_context_flags().debug().set();
+        }
 
-        let _gl_context = window.gl_create_context();
-        gl::load_with(|name| context.video.gl_get_proc_address(name) as *const _);
-        context
-            .video
+        let _gl_context = window
+            .gl_create_context()
+            .map_err(|e| format_err!("could not create a GL context: {}", e))?;
+        gl::load_with(|name| video.gl_get_proc_address(name) as *const _);
+        video
             .gl_set_swap_interval(SwapInterval::VSync)
             .map_err(|e| format_err!("{}", e))?;
 
-        Ok(Window(window))
+        Ok(Window {
+            window: RefCell::new(window),
+            cursor_visible: Cell::new(true),
+            cursor_style: Cell::new(CursorStyle::Default),
+            cursor: RefCell::new(None),
+        })
     }
 
-    pub fn gl_create_context(&self) -> GLContext {
-        GLContext(
-            self.0
-                .gl_create_context()
-                .expect("could not create gl context"),
-        )
+    /// SDL's id of the window, which its events carry
+    pub fn id(&self) -> u32 {
+        self.window.borrow().id()
     }
 
-    pub fn gl_set_current(&self, gl_context: &GLContext) {
-        self.0
-            .gl_make_current(&gl_context.0)
-            .expect("could not set window as current gl context")
+    fn update_cursor(&self) -> Result<(), Error> {
+        let system_cursor = match self.cursor_style.get() {
+            CursorStyle::Default => Some(SystemCursor::Arrow),
+            CursorStyle::Pointer => Some(SystemCursor::Hand),
+            CursorStyle::Crosshair => Some(SystemCursor::Crosshair),
+            CursorStyle::Move => Some(SystemCursor::SizeAll),
+            CursorStyle::None => None,
+        };
+        if let Some(system_cursor) = system_cursor {
+            let cursor = Cursor::from_system(system_cursor).map_err(|e| format_err!("{}", e))?;
+            cursor.set();
+            *self.cursor.borrow_mut() = Some(cursor);
+        }
+
+        self.window
+            .borrow()
+            .subsystem()
+            .sdl()
+            .mouse()
+            .show_cursor(self.cursor_visible.get() && system_cursor.is_some());
+        Ok(())
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.window.borrow().fullscreen_state() != FullscreenType::Off
     }
 
     pub fn gl_finish(&self) {
-        self.0.gl_swap_window();
+        self.window.borrow().gl_swap_window();
         unsafe { gl::Finish() };
     }
 }
+
+impl PlatformWindow for Window {
+    type GLContext = GLContext;
+
+    fn size(&self) -> (u32, u32) {
+        self.window.borrow().drawable_size()
+    }
+
+    fn logical_size(&self) -> (f32, f32) {
+        let (width, height) = self.window.borrow().size();
+        (width as f32, height as f32)
+    }
+
+    fn set_title(&self, title: &str) -> Result<(), Error> {
+        self.window.borrow_mut().set_title(title)?;
+        Ok(())
+    }
+
+    fn set_cursor_visible(&self, visible: bool) -> Result<(), Error> {
+        self.cursor_visible.set(visible);
+        self.update_cursor()
+    }
+
+    fn set_cursor_style(&self, style: CursorStyle) -> Result<(), Error> {
+        self.cursor_style.set(style);
+        self.update_cursor()
+    }
+
+    fn set_fullscreen(&self, fullscreen: bool) -> Result<(), Error> {
+        let fullscreen_type = if fullscreen {
+            FullscreenType::Desktop
+        } else {
+            FullscreenType::Off
+        };
+        self.window
+            .borrow_mut()
+            .set_fullscreen(fullscreen_type)
+            .map_err(|e| format_err!("{}", e))
+    }
+
+    fn set_pointer_lock(&self, locked: bool) {
+        self.window
+            .borrow()
+            .subsystem()
+            .sdl()
+            .mouse()
+            .set_relative_mouse_mode(locked);
+    }
+
+    // desktop GL contexts are not lost, there is nothing to simulate
+    fn set_context_lost(&self, _lost: bool) {}
+
+    fn gl_create_context(&self) -> Result<GLContext, Error> {
+        let gl_context = self
+            .window
+            .borrow()
+            .gl_create_context()
+            .map_err(|e| format_err!("could not create a GL context: {}", e))?;
+        // SDL makes the new context current
+        rendering::reset_bound_state();
+        Ok(GLContext(gl_context))
+    }
+
+    fn gl_set_current(&self, gl_context: &GLContext) {
+        // every renderer call lands here, only switch when it is another context
+        if gl_context.0.is_current() {
+            return;
+        }
+        self.window
+            .borrow()
+            .gl_make_current(&gl_context.0)
+            .expect("could not set window as current gl context");
+        rendering::reset_bound_state();
+    }
+}
@@ -0,0 +1,17 @@
+use failure::Error;
+
+use js;
+use js::window::FetchHandler;
+
+/// Fetches `url` relative to the page and calls `done` with the response body
+/// once it has arrived
+pub fn fetch_bytes<F: FnOnce(Result<Vec<u8>, Error>) + Send + 'static>(url: &str, done: F) {
+    let error_url = url.to_string();
+    let mut done = Some(done);
+    let handler = FetchHandler::new(move |result| {
+        if let Some(done) = done.take() {
+            done(result.map_err(|e| format_err!("could not fetch {}: {}", error_url, e)));
+        }
+    });
+    js::window::fetch_bytes(url, handler);
+}
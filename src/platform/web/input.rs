@@ -1,4 +1,5 @@
-use input::{Key, MouseButton};
+use input::{GamepadState, Key, Modifiers, MouseButton};
+use js::window::{self as js_window, Modifiers as JsModifiers};
 
 pub fn to_mouse_button(button: i8) -> MouseButton {
     match button {
@@ -9,6 +10,37 @@ pub fn to_mouse_button(button: i8) -> MouseButton {
     }
 }
 
+/// Bit of a button in the DOM `buttons` mask, which doesn't follow the
+/// numbering of `button`
+pub fn button_mask(button: i8) -> u32 {
+    match button {
+        0 => 1,
+        1 => 4,
+        2 => 2,
+        _ => 0,
+    }
+}
+
+pub fn to_modifiers(modifiers: JsModifiers) -> Modifiers {
+    Modifiers {
+        shift: modifiers.shift,
+        ctrl: modifiers.ctrl,
+        alt: modifiers.alt,
+        meta: modifiers.meta,
+    }
+}
+
+pub fn poll_gamepads() -> Vec<Option<GamepadState>> {
+    (0..js_window::get_gamepad_count())
+        .map(|index| {
+            js_window::gamepad_state(index).map(|state| GamepadState {
+                buttons: state.buttons,
+                axes: state.axes,
+            })
+        })
+        .collect()
+}
+
 pub fn to_key(key_code: i32) -> Key {
     match key_code {
         8 => Key::Backspace,
@@ -67,8 +99,8 @@ pub fn to_key(key_code: i32) -> Key {
         88 => Key::X,
         89 => Key::Y,
         90 => Key::Z,
-        91 => Key::Application,
-        92 => Key::Application,
+        91 => Key::Gui,
+        92 => Key::Gui,
         93 => Key::Select,
         96 => Key::Kp0,
         97 => Key::Kp1,
@@ -110,6 +142,6 @@ pub fn to_key(key_code: i32) -> Key {
         220 => Key::Backslash,
         221 => Key::RightBracket,
         222 => Key::Quote,
-        _ => Key::Unknown,
+        other => Key::Other(other),
     }
 }
@@ -1,8 +1,10 @@
 pub mod audio;
 mod console_writer;
+pub mod fetch;
 mod input;
 pub mod rand;
 pub mod rendering;
+pub mod time;
 //pub mod websocket;
 pub mod window;
 
@@ -13,9 +15,9 @@ use std::rc::Rc;
 use failure::Error;
 
 use js;
-use js::MainLoopCallback;
 
 use self::console_writer::ConsoleWriter;
+use self::input::poll_gamepads;
 use input::Input;
 use window::WindowSettings;
 
@@ -47,7 +49,11 @@ pub fn init<F: FnOnce(Context) -> T, T: FnMut(f64, &Input) -> Result<(), Error>
         windows: Rc::clone(&windows),
     };
     let mut main_loop = app_factory(context);
-    js::set_main_loop(MainLoopCallback(Box::new(move || {
+    let mut last_frame = None;
+    js::window::run_loop(move |timestamp| {
+        let dt = last_frame.map_or(0.0, |last| (timestamp - last) / 1000.0);
+        last_frame = Some(timestamp);
+
         input.update(
             windows
                 .borrow_mut()
@@ -55,7 +61,8 @@ pub fn init<F: FnOnce(Context) -> T, T: FnMut(f64, &Input) -> Result<(), Error>
                 .flat_map(|w| w.events())
                 .collect(),
         );
+        input.update_gamepads(poll_gamepads());
 
-        main_loop(0.016, &input).unwrap();
-    })));
+        main_loop(dt, &input).unwrap();
+    });
 }
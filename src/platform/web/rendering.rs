@@ -1,19 +1,32 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::iter;
+use std::rc::Rc;
+
 use failure::Error;
 
 use js::webgl;
 use js::webgl::types::*;
 
 use assets::Image;
-use rendering::{TextureFiltering, Vertex, VertexAttributeType};
+use platform::{
+    blend_factors, blend_mode_from_factors, check_limit, check_vertex_layout,
+    Renderer as PlatformRenderer, Uniforms,
+};
+use rendering::{
+    BlendMode, BufferUsage, Capabilities, CompressedFormat, DebugMessage, DebugSeverity, Index,
+    IndexType, Primitive, RenderError, RenderState, ShaderStage, StencilFunc, StencilOp,
+    TextureFiltering, TextureFormat, TextureWrap, Vertex, VertexAttributeType,
+};
 
 struct VertexShader {
     handle: webgl::Shader,
 }
 
 impl VertexShader {
-    fn new(src: &str) -> Result<VertexShader, Error> {
+    fn new(label: &str, src: &str) -> Result<VertexShader, RenderError> {
         Ok(VertexShader {
-            handle: compile_shader(src, webgl::VERTEX_SHADER)?,
+            handle: compile_shader(label, src, webgl::VERTEX_SHADER)?,
         })
     }
     fn handle<'a>(&'a self) -> &'a webgl::Shader {
@@ -32,9 +45,9 @@ struct FragmentShader {
 }
 
 impl FragmentShader {
-    fn new(src: &str) -> Result<FragmentShader, Error> {
+    fn new(label: &str, src: &str) -> Result<FragmentShader, RenderError> {
         Ok(FragmentShader {
-            handle: compile_shader(src, webgl::FRAGMENT_SHADER)?,
+            handle: compile_shader(label, src, webgl::FRAGMENT_SHADER)?,
         })
     }
     fn handle<'a>(&'a self) -> &'a webgl::Shader {
@@ -48,150 +61,984 @@ impl Drop for FragmentShader {
     }
 }
 
-pub struct VertexBuffer(webgl::Buffer);
+pub struct VertexBuffer {
+    vbo: webgl::Buffer,
+    ebo: webgl::Buffer,
+    // per instance attributes
+    instance_vbo: webgl::Buffer,
+    usage: GLenum,
+    // bytes last uploaded to vbo and ebo, the raw draws are checked against them
+    vertex_bytes: Cell<usize>,
+    index_bytes: Cell<usize>,
+}
 
 impl VertexBuffer {
-    fn new(buffer: webgl::Buffer) -> VertexBuffer {
-        VertexBuffer(buffer)
+    fn new(
+        vbo: webgl::Buffer,
+        ebo: webgl::Buffer,
+        instance_vbo: webgl::Buffer,
+        usage: GLenum,
+    ) -> VertexBuffer {
+        VertexBuffer {
+            vbo,
+            ebo,
+            instance_vbo,
+            usage,
+            vertex_bytes: Cell::new(0),
+            index_bytes: Cell::new(0),
+        }
     }
-    fn handle<'a>(&'a self) -> &'a webgl::Shader {
-        &self.0
+    pub fn vertex_bytes(&self) -> usize {
+        self.vertex_bytes.get()
+    }
+    pub fn index_bytes(&self) -> usize {
+        self.index_bytes.get()
+    }
+    fn handle<'a>(&'a self) -> &'a webgl::Buffer {
+        &self.vbo
     }
+    fn element_handle<'a>(&'a self) -> &'a webgl::Buffer {
+        &self.ebo
+    }
+
+    // WebGL has no KHR_debug, browsers name objects in their own tools
+    pub fn set_label(&self, _label: &str) {}
 }
 
 impl Drop for VertexBuffer {
     fn drop(&mut self) {
-        webgl::gl_delete_buffer(&self.0);
+        webgl::gl_delete_buffer(&self.instance_vbo);
+        webgl::gl_delete_buffer(&self.ebo);
+        webgl::gl_delete_buffer(&self.vbo);
     }
 }
 
 #[derive(Clone)]
 pub enum Uniform {
+    Float(f32),
+    Int(i32),
     Vec2((f32, f32)),
+    Vec3((f32, f32, f32)),
+    Vec4((f32, f32, f32, f32)),
+    Mat3([f32; 9]),
+    Mat4([f32; 16]),
     Texture(Texture),
+    SampledTexture(Texture, Sampler),
+}
+
+fn gl_texture_format(format: TextureFormat) -> (GLenum, GLenum) {
+    match format {
+        TextureFormat::R8 => (webgl::R8, webgl::RED),
+        TextureFormat::Rg8 => (webgl::RG8, webgl::RG),
+        TextureFormat::Rgb8 => (webgl::RGB8, webgl::RGB),
+        TextureFormat::Rgba8 => (webgl::RGBA8, webgl::RGBA),
+        TextureFormat::Srgba8 => (webgl::SRGB8_ALPHA8, webgl::RGBA),
+    }
+}
+
+fn gl_compressed_format(format: CompressedFormat) -> GLenum {
+    match format {
+        CompressedFormat::Dxt1 => webgl::COMPRESSED_RGB_S3TC_DXT1,
+        CompressedFormat::Dxt5 => webgl::COMPRESSED_RGBA_S3TC_DXT5,
+        CompressedFormat::Etc2Rgb8 => webgl::COMPRESSED_RGB8_ETC2,
+        CompressedFormat::Etc2Rgba8 => webgl::COMPRESSED_RGBA8_ETC2_EAC,
+        CompressedFormat::Astc4x4 => webgl::COMPRESSED_RGBA_ASTC_4X4,
+    }
 }
 
 pub struct Program {
-    uniforms: Vec<(String, Uniform)>,
+    uniforms: Uniforms<Uniform>,
+    uniform_locations: RefCell<HashMap<String, Option<webgl::UniformLocation>>>,
+    attrib_locations: RefCell<HashMap<String, GLint>>,
     handle: webgl::Program,
 }
 
 impl Program {
-    fn new(vertex_shader: VertexShader, frag_shader: FragmentShader) -> Result<Program, Error> {
+    fn new(
+        label: &str,
+        vertex_shader: VertexShader,
+        frag_shader: FragmentShader,
+    ) -> Result<Program, RenderError> {
         Ok(Program {
-            uniforms: Vec::new(),
-            handle: link_program(&vertex_shader, &frag_shader)?,
+            uniforms: Uniforms::default(),
+            uniform_locations: RefCell::new(HashMap::new()),
+            attrib_locations: RefCell::new(HashMap::new()),
+            handle: link_program(label, &vertex_shader, &frag_shader)?,
         })
     }
     fn handle<'a>(&'a self) -> &webgl::Program {
         &self.handle
     }
 
+    pub fn set_label(&self, _label: &str) {}
+
+    // shaders are not reloaded on the web, so the binding is not kept around
+    pub fn set_uniform_block_binding(&mut self, block: &str, binding: u32) -> Result<(), Error> {
+        let index = webgl::gl_get_uniform_block_index(&self.handle, block);
+        if index == webgl::INVALID_INDEX {
+            return Err(format_err!(
+                "program has no uniform block named {:?}",
+                block
+            ));
+        }
+        webgl::gl_uniform_block_binding(&self.handle, index, binding);
+        Ok(())
+    }
+
+    // locations are looked up once and cached, None means the uniform is not used
+    // by the program
+    fn uniform_location(&self, name: &str) -> Option<webgl::UniformLocation> {
+        if let Some(location) = self.uniform_locations.borrow().get(name) {
+            return location.clone();
+        }
+        let location = webgl::gl_get_uniform_location(&self.handle, name);
+        let location = if location.is_null() {
+            // uniforms may legitimately be optimized out, WebGL has no debug output
+            // to warn on so they are skipped silently
+            None
+        } else {
+            Some(location)
+        };
+        self.uniform_locations
+            .borrow_mut()
+            .insert(name.into(), location.clone());
+        location
+    }
+    fn attrib_location(&self, name: &str) -> GLint {
+        if let Some(&location) = self.attrib_locations.borrow().get(name) {
+            return location;
+        }
+        let location = webgl::gl_get_attrib_location(&self.handle, name);
+        self.attrib_locations
+            .borrow_mut()
+            .insert(name.into(), location);
+        location
+    }
+
     pub fn set_uniform(&mut self, name: &str, uniform: Uniform) {
-        self.uniforms.push((name.into(), uniform));
+        self.uniforms.set(name, uniform);
+    }
+    pub fn clear_uniforms(&mut self) {
+        self.uniforms.clear();
     }
     pub fn uniforms(&self) -> impl Iterator<Item = &(String, Uniform)> {
         self.uniforms.iter()
     }
 }
 
+pub struct TimerQuery(webgl::Query);
+
+impl TimerQuery {
+    pub fn begin(&self) {
+        webgl::gl_begin_query(webgl::TIME_ELAPSED, &self.0);
+    }
+
+    pub fn end(&self) {
+        webgl::gl_end_query(webgl::TIME_ELAPSED);
+    }
+
+    pub fn is_available(&self) -> bool {
+        webgl::gl_get_query_parameter_b(&self.0, webgl::QUERY_RESULT_AVAILABLE)
+    }
+
+    pub fn result_ns(&self) -> u64 {
+        webgl::gl_get_query_parameter_f(&self.0, webgl::QUERY_RESULT) as u64
+    }
+}
+
+impl Drop for TimerQuery {
+    fn drop(&mut self) {
+        webgl::gl_delete_query(&self.0)
+    }
+}
+
+pub struct UniformBuffer {
+    ubo: webgl::Buffer,
+    usage: GLenum,
+}
+
+impl UniformBuffer {
+    pub fn set_data(&self, data: &[u8]) {
+        webgl::gl_bind_buffer(webgl::UNIFORM_BUFFER, &self.ubo);
+        upload_buffer(webgl::UNIFORM_BUFFER, self.usage, data);
+    }
+
+    pub fn bind(&self, binding: u32) {
+        webgl::gl_bind_buffer_base(webgl::UNIFORM_BUFFER, binding, &self.ubo);
+    }
+
+    pub fn set_label(&self, _label: &str) {}
+}
+
+impl Drop for UniformBuffer {
+    fn drop(&mut self) {
+        webgl::gl_delete_buffer(&self.ubo)
+    }
+}
+
 impl Drop for Program {
     fn drop(&mut self) {
         webgl::gl_delete_program(self.handle())
     }
 }
 
+pub struct Framebuffer {
+    fbo: webgl::Framebuffer,
+    depth: webgl::Renderbuffer,
+    size: (u32, u32),
+    resolve: Option<Resolve>,
+}
+
+// a multisampled framebuffer draws into a color renderbuffer, the texture is
+// attached to a second framebuffer that the samples are blitted to
+struct Resolve {
+    fbo: webgl::Framebuffer,
+    color: webgl::Renderbuffer,
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        if let Some(ref resolve) = self.resolve {
+            webgl::gl_delete_renderbuffer(&resolve.color);
+            webgl::gl_delete_framebuffer(&resolve.fbo);
+        }
+        webgl::gl_delete_renderbuffer(&self.depth);
+        webgl::gl_delete_framebuffer(&self.fbo);
+    }
+}
+
+struct TextureHandle(webgl::Texture);
+
+impl Drop for TextureHandle {
+    fn drop(&mut self) {
+        webgl::gl_delete_texture(&self.0)
+    }
+}
+
+struct SamplerHandle(webgl::Sampler);
+
+impl Drop for SamplerHandle {
+    fn drop(&mut self) {
+        webgl::gl_delete_sampler(&self.0)
+    }
+}
+
 #[derive(Clone)]
-pub struct Texture(webgl::Texture);
+pub struct Sampler {
+    handle: Rc<SamplerHandle>,
+}
+
+// Clones share the same GL texture, which is deleted once the last clone is dropped
+#[derive(Clone)]
+pub struct Texture {
+    handle: Rc<TextureHandle>,
+    size: (u32, u32),
+    format: TextureFormat,
+    compressed: Option<CompressedFormat>,
+    mipmaps: bool,
+}
+
+// creates a texture with its filtering and wrapping set, and leaves it bound
+fn create_texture_handle(
+    filtering: Option<(GLenum, GLenum)>,
+    wrap: Option<GLenum>,
+) -> webgl::Texture {
+    let (min_filter, mag_filter) = filtering.unwrap_or((webgl::LINEAR, webgl::LINEAR));
+    let handle = webgl::gl_create_texture();
+    webgl::gl_bind_texture(webgl::TEXTURE_2D, &handle);
+    webgl::gl_tex_parameter_i(
+        webgl::TEXTURE_2D,
+        webgl::TEXTURE_MIN_FILTER,
+        min_filter as GLint,
+    );
+    webgl::gl_tex_parameter_i(
+        webgl::TEXTURE_2D,
+        webgl::TEXTURE_MAG_FILTER,
+        mag_filter as GLint,
+    );
+    webgl::gl_tex_parameter_i(
+        webgl::TEXTURE_2D,
+        webgl::TEXTURE_WRAP_S,
+        wrap.unwrap_or(webgl::CLAMP_TO_EDGE) as GLint,
+    );
+    webgl::gl_tex_parameter_i(
+        webgl::TEXTURE_2D,
+        webgl::TEXTURE_WRAP_T,
+        wrap.unwrap_or(webgl::CLAMP_TO_EDGE) as GLint,
+    );
+    handle
+}
+
+impl PartialEq for Texture {
+    fn eq(&self, other: &Texture) -> bool {
+        Rc::ptr_eq(&self.handle, &other.handle)
+    }
+}
 
 impl Texture {
-    fn new(size: (u32, u32), filtering: Option<GLenum>) -> Texture {
-        let handle = webgl::gl_create_texture();
-        webgl::gl_bind_texture(webgl::TEXTURE_2D, &handle);
-        webgl::gl_tex_parameter_i(
-            webgl::TEXTURE_2D,
-            webgl::TEXTURE_MIN_FILTER,
-            filtering.unwrap_or(webgl::LINEAR) as GLint,
-        );
-        webgl::gl_tex_parameter_i(
-            webgl::TEXTURE_2D,
-            webgl::TEXTURE_MAG_FILTER,
-            filtering.unwrap_or(webgl::LINEAR) as GLint,
-        );
+    fn new(
+        size: (u32, u32),
+        format: TextureFormat,
+        filtering: Option<(GLenum, GLenum)>,
+        wrap: Option<GLenum>,
+        mipmaps: bool,
+        data: Option<&[u8]>,
+    ) -> Texture {
+        let (internal_format, pixel_format) = gl_texture_format(format);
+        let handle = create_texture_handle(filtering, wrap);
 
-        webgl::gl_tex_image_2d_empty(
+        // rows of R8 and RGB8 data are not 4 byte aligned
+        webgl::gl_pixel_store_i(webgl::UNPACK_ALIGNMENT, 1);
+        match data {
+            Some(data) => webgl::gl_tex_image_2d_u8(
+                webgl::TEXTURE_2D,
+                0,
+                internal_format,
+                size.0 as GLsizei,
+                size.1 as GLsizei,
+                0 as GLint,
+                pixel_format,
+                webgl::UNSIGNED_BYTE,
+                data,
+            ),
+            None => webgl::gl_tex_image_2d_empty(
+                webgl::TEXTURE_2D,
+                0,
+                internal_format,
+                size.0 as GLsizei,
+                size.1 as GLsizei,
+                0 as GLint,
+                pixel_format,
+                webgl::UNSIGNED_BYTE,
+            ),
+        }
+        if mipmaps {
+            webgl::gl_generate_mipmap(webgl::TEXTURE_2D);
+        }
+        Texture {
+            handle: Rc::new(TextureHandle(handle)),
+            size,
+            format,
+            compressed: None,
+            mipmaps,
+        }
+    }
+    fn new_compressed(
+        size: (u32, u32),
+        format: CompressedFormat,
+        filtering: Option<(GLenum, GLenum)>,
+        wrap: Option<GLenum>,
+        levels: &[&[u8]],
+    ) -> Texture {
+        let handle = create_texture_handle(filtering, wrap);
+        for (level, data) in levels.iter().enumerate() {
+            webgl::gl_compressed_tex_image_2d(
+                webgl::TEXTURE_2D,
+                level as GLint,
+                gl_compressed_format(format),
+                (size.0 >> level).max(1) as GLsizei,
+                (size.1 >> level).max(1) as GLsizei,
+                0 as GLint,
+                data,
+            );
+        }
+        // the texture is complete without the levels below the last one given
+        webgl::gl_tex_parameter_i(
             webgl::TEXTURE_2D,
-            0,
-            webgl::RGBA,
-            size.0 as GLsizei,
-            size.1 as GLsizei,
-            0 as GLint,
-            webgl::RGBA,
-            webgl::UNSIGNED_BYTE,
+            webgl::TEXTURE_MAX_LEVEL,
+            levels.len() as GLint - 1,
         );
-        Texture(handle)
+        // mipmaps are only generated for uncompressed textures
+        Texture {
+            handle: Rc::new(TextureHandle(handle)),
+            size,
+            format: format.texture_format(),
+            compressed: Some(format),
+            mipmaps: false,
+        }
     }
     fn handle<'a>(&'a self) -> &'a webgl::Texture {
-        &self.0
+        &self.handle.0
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    pub fn compressed_format(&self) -> Option<CompressedFormat> {
+        self.compressed
+    }
+
+    pub fn set_region(&self, image: &Image, offset: (u32, u32)) -> Result<(), Error> {
+        self.set_regions(iter::once((image, offset)))
     }
 
-    pub fn set_region(&self, image: &Image, offset: (u32, u32)) {
+    // binds once and regenerates the mipmaps once for all of the regions
+    pub fn set_regions<'a, I: IntoIterator<Item = (&'a Image, (u32, u32))>>(
+        &self,
+        updates: I,
+    ) -> Result<(), Error> {
+        let (_, pixel_format) = gl_texture_format(self.format);
         webgl::gl_bind_texture(webgl::TEXTURE_2D, self.handle());
-        webgl::gl_tex_sub_image_2d_u8(
+        webgl::gl_pixel_store_i(webgl::UNPACK_ALIGNMENT, 1);
+        for (image, offset) in updates {
+            webgl::gl_tex_sub_image_2d_u8(
+                webgl::TEXTURE_2D,
+                0,
+                offset.0 as GLint,
+                offset.1 as GLint,
+                image.width as GLsizei,
+                image.height as GLsizei,
+                pixel_format,
+                webgl::UNSIGNED_BYTE,
+                &image.data,
+            );
+        }
+        if self.mipmaps {
+            webgl::gl_generate_mipmap(webgl::TEXTURE_2D);
+        }
+        check_gl_error("set_regions")
+    }
+
+    pub fn set_label(&self, _label: &str) {}
+
+    // WebGL has no getTexImage, the texture is attached to a temporary
+    // framebuffer and read from there
+    pub fn read_pixels(&self) -> Result<Vec<u8>, Error> {
+        let (width, height) = self.size;
+        let mut data = vec![0u8; (width * height * 4) as usize];
+        let previous = webgl::gl_get_framebuffer_binding();
+        let fbo = webgl::gl_create_framebuffer();
+        webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &fbo);
+        webgl::gl_framebuffer_texture_2d(
+            webgl::FRAMEBUFFER,
+            webgl::COLOR_ATTACHMENT0,
             webgl::TEXTURE_2D,
+            self.handle(),
             0,
-            offset.0 as GLint,
-            offset.1 as GLint,
-            image.width as GLsizei,
-            image.height as GLsizei,
-            webgl::RGBA,
-            webgl::UNSIGNED_BYTE,
-            &image.data,
         );
+        let status = webgl::gl_check_framebuffer_status(webgl::FRAMEBUFFER);
+        if status == webgl::FRAMEBUFFER_COMPLETE {
+            webgl::gl_read_pixels(
+                0,
+                0,
+                width as GLsizei,
+                height as GLsizei,
+                webgl::RGBA,
+                webgl::UNSIGNED_BYTE,
+                &mut data,
+            );
+        }
+        webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &previous);
+        webgl::gl_delete_framebuffer(&fbo);
+        if status != webgl::FRAMEBUFFER_COMPLETE {
+            return Err(format_err!(
+                "texture can't be read back, its format is not renderable (status 0x{:X})",
+                status
+            ));
+        }
+        check_gl_error("read_pixels")?;
+
+        Ok(data)
     }
-}
 
-impl Drop for Texture {
-    fn drop(&mut self) {
-        webgl::gl_delete_texture(self.handle())
+    pub fn set_anisotropy(&self, level: f32) -> f32 {
+        // getExtension also enables the extension's enums
+        if !webgl::gl_get_extension("EXT_texture_filter_anisotropic") {
+            return 1.0;
+        }
+        let max = webgl::gl_get_parameter_f(webgl::MAX_TEXTURE_MAX_ANISOTROPY);
+        let level = level.max(1.0).min(max);
+        webgl::gl_bind_texture(webgl::TEXTURE_2D, self.handle());
+        webgl::gl_tex_parameter_f(webgl::TEXTURE_2D, webgl::TEXTURE_MAX_ANISOTROPY, level);
+        level
     }
 }
 
 pub fn screen_size() -> (i32, i32) {
-    let width = webgl::gl_drawing_buffer_width();
-    let height = webgl::gl_drawing_buffer_height();
-    (width, height)
+    let (_, _, width, height) = viewport();
+    (width as i32, height as i32)
+}
+pub fn viewport() -> (i32, i32, u32, u32) {
+    let mut rect = [0; 4];
+    webgl::gl_get_viewport(&mut rect);
+    (rect[0], rect[1], rect[2] as u32, rect[3] as u32)
 }
-pub fn create_vertex_buffer() -> Result<VertexBuffer, Error> {
-    let vbo = VertexBuffer::new(webgl::gl_create_buffer());
+pub fn create_vertex_buffer(usage: BufferUsage) -> Result<VertexBuffer, Error> {
+    let vbo = VertexBuffer::new(
+        webgl::gl_create_buffer(),
+        webgl::gl_create_buffer(),
+        webgl::gl_create_buffer(),
+        gl_buffer_usage(usage),
+    );
 
     Ok(vbo)
 }
-pub fn create_program(vs: &str, fs: &str) -> Result<Program, Error> {
-    let vs = VertexShader::new(vs)?;
-    let fs = FragmentShader::new(fs)?;
+/// Whether timer queries work, and if so whether disjoint events have to be
+/// checked for
+pub fn gpu_timer_support() -> Option<bool> {
+    // getExtension also enables the extension's enums
+    if webgl::gl_get_extension("EXT_disjoint_timer_query_webgl2") {
+        Some(true)
+    } else {
+        None
+    }
+}
+pub fn gpu_timer_disjoint() -> bool {
+    webgl::gl_get_parameter_b(webgl::GPU_DISJOINT)
+}
+pub fn create_timer_query() -> TimerQuery {
+    TimerQuery(webgl::gl_create_query())
+}
+pub fn create_uniform_buffer(usage: BufferUsage) -> Result<UniformBuffer, Error> {
+    Ok(UniformBuffer {
+        ubo: webgl::gl_create_buffer(),
+        usage: gl_buffer_usage(usage),
+    })
+}
+fn gl_buffer_usage(usage: BufferUsage) -> GLenum {
+    match usage {
+        BufferUsage::Static => webgl::STATIC_DRAW,
+        BufferUsage::Dynamic => webgl::DYNAMIC_DRAW,
+        BufferUsage::Stream => webgl::STREAM_DRAW,
+    }
+}
+// queried on the first uniform buffer bind and cached with the context
+pub fn max_uniform_buffer_bindings() -> u32 {
+    webgl::gl_get_limit(webgl::MAX_UNIFORM_BUFFER_BINDINGS) as u32
+}
+
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        max_texture_size: max_texture_size(),
+        max_texture_units: webgl::gl_get_parameter_i(webgl::MAX_TEXTURE_IMAGE_UNITS) as u32,
+        max_samples: max_samples(),
+        max_color_attachments: max_color_attachments(),
+        max_uniform_buffer_bindings: max_uniform_buffer_bindings(),
+        version: webgl::gl_get_parameter_string(webgl::VERSION),
+        renderer: webgl::gl_get_parameter_string(webgl::RENDERER),
+        extensions: webgl::gl_get_supported_extensions()
+            .split(' ')
+            .filter(|extension| !extension.is_empty())
+            .map(String::from)
+            .collect(),
+    }
+}
+pub fn enable_debug_output(_: DebugSeverity, _: Box<FnMut(&DebugMessage)>) -> Result<(), Error> {
+    Err(format_err!("WebGL has no debug output"))
+}
+// a draw writes to at most MAX_DRAW_BUFFERS of the attachments
+pub fn max_color_attachments() -> u32 {
+    let max_attachments = webgl::gl_get_parameter_i(webgl::MAX_COLOR_ATTACHMENTS);
+    let max_draw_buffers = webgl::gl_get_parameter_i(webgl::MAX_DRAW_BUFFERS);
+    max_attachments.min(max_draw_buffers) as u32
+}
+pub fn max_samples() -> u32 {
+    webgl::gl_get_parameter_i(webgl::MAX_SAMPLES) as u32
+}
+pub fn max_texture_size() -> u32 {
+    webgl::gl_get_parameter_i(webgl::MAX_TEXTURE_SIZE) as u32
+}
+// multisampled framebuffers have a single color attachment
+pub fn create_framebuffer(
+    textures: &[&Texture],
+    size: (u32, u32),
+    samples: u32,
+) -> Result<Framebuffer, Error> {
+    let previous = webgl::gl_get_framebuffer_binding();
+
+    let mut resolve = None;
+    let fbo = if samples > 1 {
+        let resolve_fbo = webgl::gl_create_framebuffer();
+        webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &resolve_fbo);
+        webgl::gl_framebuffer_texture_2d(
+            webgl::FRAMEBUFFER,
+            webgl::COLOR_ATTACHMENT0,
+            webgl::TEXTURE_2D,
+            &textures[0].handle.0,
+            0,
+        );
 
-    Ok(Program::new(vs, fs)?)
+        let fbo = webgl::gl_create_framebuffer();
+        webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &fbo);
+        let color = webgl::gl_create_renderbuffer();
+        webgl::gl_bind_renderbuffer(webgl::RENDERBUFFER, &color);
+        webgl::gl_renderbuffer_storage_multisample(
+            webgl::RENDERBUFFER,
+            samples as GLsizei,
+            webgl::RGBA8,
+            size.0 as GLsizei,
+            size.1 as GLsizei,
+        );
+        webgl::gl_framebuffer_renderbuffer(
+            webgl::FRAMEBUFFER,
+            webgl::COLOR_ATTACHMENT0,
+            webgl::RENDERBUFFER,
+            &color,
+        );
+        resolve = Some(Resolve {
+            fbo: resolve_fbo,
+            color,
+        });
+        fbo
+    } else {
+        let fbo = webgl::gl_create_framebuffer();
+        webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &fbo);
+        let attachments: Vec<GLenum> = (0..textures.len() as GLenum)
+            .map(|i| webgl::COLOR_ATTACHMENT0 + i)
+            .collect();
+        for (texture, &attachment) in textures.iter().zip(attachments.iter()) {
+            webgl::gl_framebuffer_texture_2d(
+                webgl::FRAMEBUFFER,
+                attachment,
+                webgl::TEXTURE_2D,
+                &texture.handle.0,
+                0,
+            );
+        }
+        // only the first attachment is drawn to unless told otherwise, output N
+        // of the fragment shader goes to attachment N
+        if attachments.len() > 1 {
+            webgl::gl_draw_buffers(&attachments);
+        }
+        fbo
+    };
+
+    let depth = webgl::gl_create_renderbuffer();
+    webgl::gl_bind_renderbuffer(webgl::RENDERBUFFER, &depth);
+    webgl::gl_renderbuffer_storage_multisample(
+        webgl::RENDERBUFFER,
+        if samples > 1 { samples as GLsizei } else { 0 },
+        webgl::DEPTH24_STENCIL8,
+        size.0 as GLsizei,
+        size.1 as GLsizei,
+    );
+    webgl::gl_framebuffer_renderbuffer(
+        webgl::FRAMEBUFFER,
+        webgl::DEPTH_STENCIL_ATTACHMENT,
+        webgl::RENDERBUFFER,
+        &depth,
+    );
+
+    let status = webgl::gl_check_framebuffer_status(webgl::FRAMEBUFFER);
+    webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &previous);
+    let framebuffer = Framebuffer {
+        fbo,
+        depth,
+        size,
+        resolve,
+    };
+
+    if status != webgl::FRAMEBUFFER_COMPLETE {
+        return Err(RenderError::IncompleteFramebuffer(status).into());
+    }
+    check_gl_error("create_framebuffer")?;
+
+    Ok(framebuffer)
+}
+pub fn resolve_framebuffer(framebuffer: &Framebuffer) -> Result<(), Error> {
+    let resolve = match framebuffer.resolve {
+        Some(ref resolve) => resolve,
+        None => return Ok(()),
+    };
+    let (width, height) = (framebuffer.size.0 as GLint, framebuffer.size.1 as GLint);
+    let previous = webgl::gl_get_framebuffer_binding();
+    // the scissor rectangle also clips blits
+    let scissor = webgl::gl_is_enabled(webgl::SCISSOR_TEST);
+    webgl::gl_disable(webgl::SCISSOR_TEST);
+
+    webgl::gl_bind_framebuffer(webgl::READ_FRAMEBUFFER, &framebuffer.fbo);
+    webgl::gl_bind_framebuffer(webgl::DRAW_FRAMEBUFFER, &resolve.fbo);
+    webgl::gl_blit_framebuffer(
+        0,
+        0,
+        width,
+        height,
+        0,
+        0,
+        width,
+        height,
+        webgl::COLOR_BUFFER_BIT,
+        webgl::NEAREST,
+    );
+
+    webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &previous);
+    if scissor {
+        webgl::gl_enable(webgl::SCISSOR_TEST);
+    }
+    check_gl_error("resolve_framebuffer")
+}
+/// Unbinding restores the viewport from before the first bind, or covers the
+/// whole canvas when that was lost to a context switch
+pub fn bind_framebuffer(framebuffer: Option<&Framebuffer>) {
+    match framebuffer {
+        Some(framebuffer) => {
+            webgl::gl_save_screen_viewport();
+            webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &framebuffer.fbo);
+            webgl::gl_viewport(
+                0,
+                0,
+                framebuffer.size.0 as GLsizei,
+                framebuffer.size.1 as GLsizei,
+            );
+        }
+        None => {
+            webgl::gl_bind_framebuffer(webgl::FRAMEBUFFER, &webgl::Framebuffer::null());
+            webgl::gl_restore_screen_viewport();
+        }
+    }
+}
+pub fn set_viewport(x: i32, y: i32, width: u32, height: u32) {
+    webgl::gl_viewport(x, y, width as GLsizei, height as GLsizei);
+}
+pub fn create_program(label: &str, vs: &str, fs: &str) -> Result<Program, RenderError> {
+    let vs = VertexShader::new(label, vs)?;
+    let fs = FragmentShader::new(label, fs)?;
+
+    Program::new(label, vs, fs)
 }
 pub fn create_texture(
     size: (u32, u32),
+    format: TextureFormat,
+    filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
+) -> Result<Texture, RenderError> {
+    new_texture(size, format, filtering, wrap, None)
+}
+pub fn create_texture_from_image(
+    image: &Image,
+    filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
+) -> Result<Texture, RenderError> {
+    new_texture(
+        (image.width, image.height),
+        TextureFormat::Rgba8,
+        filtering,
+        wrap,
+        Some(&image.data),
+    )
+}
+fn new_texture(
+    size: (u32, u32),
+    format: TextureFormat,
+    filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
+    data: Option<&[u8]>,
+) -> Result<Texture, RenderError> {
+    let mipmaps = match filtering {
+        Some(TextureFiltering::Trilinear) => true,
+        _ => false,
+    };
+    let filtering = filtering.map(gl_filtering);
+    let wrap = wrap.map(gl_wrap);
+    let texture = Texture::new(size, format, filtering, wrap, mipmaps, data);
+    gl_errors("create_texture")?;
+    Ok(texture)
+}
+pub fn create_compressed_texture(
+    format: CompressedFormat,
+    size: (u32, u32),
+    levels: &[&[u8]],
     filtering: Option<TextureFiltering>,
+    wrap: Option<TextureWrap>,
 ) -> Result<Texture, Error> {
-    let filtering = filtering.map(|f| match f {
-        TextureFiltering::Linear => webgl::LINEAR,
-        TextureFiltering::Nearest => webgl::NEAREST,
-    });
-    Ok(Texture::new(size, filtering))
+    let filtering = filtering.map(gl_filtering);
+    let wrap = wrap.map(gl_wrap);
+    let texture = Texture::new_compressed(size, format, filtering, wrap, levels);
+    check_gl_error("create_compressed_texture")?;
+    Ok(texture)
+}
+// none of them are core in WebGL 2, getExtension also enables the formats
+pub fn compressed_format_supported(format: CompressedFormat) -> bool {
+    let extension = match format {
+        CompressedFormat::Dxt1 | CompressedFormat::Dxt5 => "WEBGL_compressed_texture_s3tc",
+        CompressedFormat::Etc2Rgb8 | CompressedFormat::Etc2Rgba8 => "WEBGL_compressed_texture_etc",
+        CompressedFormat::Astc4x4 => "WEBGL_compressed_texture_astc",
+    };
+    webgl::gl_get_extension(extension)
+}
+// minification and magnification filter
+fn gl_filtering(filtering: TextureFiltering) -> (GLenum, GLenum) {
+    match filtering {
+        TextureFiltering::Linear => (webgl::LINEAR, webgl::LINEAR),
+        TextureFiltering::Nearest => (webgl::NEAREST, webgl::NEAREST),
+        TextureFiltering::Trilinear => (webgl::LINEAR_MIPMAP_LINEAR, webgl::LINEAR),
+    }
+}
+fn gl_wrap(wrap: TextureWrap) -> GLenum {
+    match wrap {
+        TextureWrap::ClampToEdge => webgl::CLAMP_TO_EDGE,
+        TextureWrap::Repeat => webgl::REPEAT,
+        TextureWrap::MirroredRepeat => webgl::MIRRORED_REPEAT,
+    }
+}
+pub fn create_sampler(filtering: TextureFiltering, wrap: TextureWrap) -> Result<Sampler, Error> {
+    let (min_filter, mag_filter) = gl_filtering(filtering);
+    let wrap = gl_wrap(wrap);
+    let sampler = webgl::gl_create_sampler();
+    webgl::gl_sampler_parameter_i(&sampler, webgl::TEXTURE_MIN_FILTER, min_filter as GLint);
+    webgl::gl_sampler_parameter_i(&sampler, webgl::TEXTURE_MAG_FILTER, mag_filter as GLint);
+    webgl::gl_sampler_parameter_i(&sampler, webgl::TEXTURE_WRAP_S, wrap as GLint);
+    webgl::gl_sampler_parameter_i(&sampler, webgl::TEXTURE_WRAP_T, wrap as GLint);
+    let sampler = Sampler {
+        handle: Rc::new(SamplerHandle(sampler)),
+    };
+    check_gl_error("create_sampler")?;
+    Ok(sampler)
 }
 
 pub fn render_vertices<V: Vertex>(
+    primitive: Primitive,
     vertex_buffer: &VertexBuffer,
     program: &Program,
-    vertices: &Vec<V>,
+    vertices: &[V],
 ) -> Result<(), Error> {
-    webgl::gl_blend_func(webgl::SRC_ALPHA, webgl::ONE_MINUS_SRC_ALPHA);
-    webgl::gl_enable(webgl::BLEND);
+    setup_vertices(vertex_buffer, program, vertices)?;
+    webgl::gl_draw_arrays(gl_primitive(primitive), 0, vertices.len() as GLsizei);
+
+    check_gl_error("render_vertices")
+}
+
+pub fn render_indexed<V: Vertex, I: Index>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    vertices: &[V],
+    indices: &[I],
+) -> Result<(), Error> {
+    setup_vertices(vertex_buffer, program, vertices)?;
+
+    webgl::gl_bind_buffer(webgl::ELEMENT_ARRAY_BUFFER, vertex_buffer.element_handle());
+    unsafe {
+        let data = ::std::slice::from_raw_parts(
+            indices.as_ptr() as *const u8,
+            indices.len() * ::std::mem::size_of::<I>(),
+        );
+        upload_buffer(webgl::ELEMENT_ARRAY_BUFFER, vertex_buffer.usage, data);
+        vertex_buffer.index_bytes.set(data.len());
+    }
+
+    webgl::gl_draw_elements(
+        gl_primitive(primitive),
+        indices.len() as GLsizei,
+        gl_index_type(I::index_type()),
+        0,
+    );
+
+    check_gl_error("render_indexed")
+}
+
+pub fn draw_arrays_raw<V: Vertex>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    first: u32,
+    count: u32,
+) -> Result<(), Error> {
+    webgl::gl_bind_buffer(webgl::ARRAY_BUFFER, vertex_buffer.handle());
+    setup_attributes::<V>(program, 0)?;
+    webgl::gl_draw_arrays(gl_primitive(primitive), first as GLint, count as GLsizei);
+
+    check_gl_error("draw_arrays_raw")
+}
+
+pub fn draw_elements_raw<V: Vertex>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    index_type: IndexType,
+    first: u32,
+    count: u32,
+) -> Result<(), Error> {
+    webgl::gl_bind_buffer(webgl::ARRAY_BUFFER, vertex_buffer.handle());
+    setup_attributes::<V>(program, 0)?;
+    webgl::gl_bind_buffer(webgl::ELEMENT_ARRAY_BUFFER, vertex_buffer.element_handle());
+    webgl::gl_draw_elements(
+        gl_primitive(primitive),
+        count as GLsizei,
+        gl_index_type(index_type),
+        (first as usize * index_type.size()) as GLintptr,
+    );
+
+    check_gl_error("draw_elements_raw")
+}
+
+/// Forgets the state the JS side caches for the current context
+pub fn reset_bound_state() {
+    webgl::gl_reset_bound_state();
+}
+
+pub fn render_instanced<V: Vertex, I: Vertex>(
+    primitive: Primitive,
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    vertices: &[V],
+    instances: &[I],
+) -> Result<(), Error> {
+    setup_vertices(vertex_buffer, program, vertices)?;
+
+    webgl::gl_bind_buffer(webgl::ARRAY_BUFFER, &vertex_buffer.instance_vbo);
+    unsafe {
+        let data = ::std::slice::from_raw_parts(
+            instances.as_ptr() as *const u8,
+            instances.len() * I::stride(),
+        );
+        upload_buffer(webgl::ARRAY_BUFFER, vertex_buffer.usage, data);
+    }
+    setup_attributes::<I>(program, 1)?;
+
+    webgl::gl_draw_arrays_instanced(
+        gl_primitive(primitive),
+        0,
+        vertices.len() as GLsizei,
+        instances.len() as GLsizei,
+    );
+
+    check_gl_error("render_instanced")
+}
+
+fn upload_buffer(target: GLenum, usage: GLenum, data: &[u8]) {
+    if usage == webgl::STATIC_DRAW {
+        webgl::gl_buffer_data(target, data, usage);
+    } else {
+        // orphan the old storage so the driver can hand out a fresh block
+        // instead of synchronizing with draws still using it
+        webgl::gl_buffer_data_size(target, data.len() as GLsizeiptr, usage);
+        webgl::gl_buffer_sub_data(target, 0, data);
+    }
+}
+
+// queried on the first draw and cached with the context
+fn max_texture_units() -> u32 {
+    webgl::gl_get_limit(webgl::MAX_TEXTURE_IMAGE_UNITS) as u32
+}
+
+fn gl_index_type(index_type: IndexType) -> GLenum {
+    match index_type {
+        IndexType::U16 => webgl::UNSIGNED_SHORT,
+        IndexType::U32 => webgl::UNSIGNED_INT,
+    }
+}
+
+fn gl_primitive(primitive: Primitive) -> GLenum {
+    match primitive {
+        Primitive::Triangles => webgl::TRIANGLES,
+        Primitive::TriangleStrip => webgl::TRIANGLE_STRIP,
+        Primitive::Lines => webgl::LINES,
+        Primitive::LineStrip => webgl::LINE_STRIP,
+        Primitive::Points => webgl::POINTS,
+    }
+}
 
+fn setup_vertices<V: Vertex>(
+    vertex_buffer: &VertexBuffer,
+    program: &Program,
+    vertices: &[V],
+) -> Result<(), Error> {
     // push vertex data
     webgl::gl_bind_buffer(webgl::ARRAY_BUFFER, vertex_buffer.handle());
     unsafe {
@@ -199,30 +1046,66 @@ pub fn render_vertices<V: Vertex>(
             vertices.as_ptr() as *const u8,
             vertices.len() * V::stride(),
         );
-        webgl::gl_buffer_data(webgl::ARRAY_BUFFER, data, webgl::STATIC_DRAW);
+        upload_buffer(webgl::ARRAY_BUFFER, vertex_buffer.usage, data);
+        vertex_buffer.vertex_bytes.set(data.len());
     }
 
     webgl::gl_use_program(program.handle());
 
     // set uniforms
+    let max_texture_units = max_texture_units();
     let mut texture_index = 0;
     for &(ref name, ref uniform) in program.uniforms() {
-        let attr = webgl::gl_get_uniform_location(program.handle(), name);
+        let attr = match program.uniform_location(name) {
+            Some(attr) => attr,
+            None => continue,
+        };
         match uniform {
+            &Uniform::Float(v) => webgl::gl_uniform1f(&attr, v),
+            &Uniform::Int(v) => webgl::gl_uniform1i(&attr, v),
             &Uniform::Vec2(gl_vec2) => webgl::gl_uniform2f(&attr, gl_vec2.0, gl_vec2.1),
+            &Uniform::Vec3(gl_vec3) => webgl::gl_uniform3f(&attr, gl_vec3.0, gl_vec3.1, gl_vec3.2),
+            &Uniform::Vec4(gl_vec4) => {
+                webgl::gl_uniform4f(&attr, gl_vec4.0, gl_vec4.1, gl_vec4.2, gl_vec4.3)
+            }
+            &Uniform::Mat3(ref gl_mat3) => {
+                webgl::gl_uniform_matrix3fv(&attr, webgl::FALSE, gl_mat3)
+            }
+            &Uniform::Mat4(ref gl_mat4) => {
+                webgl::gl_uniform_matrix4fv(&attr, webgl::FALSE, gl_mat4)
+            }
             &Uniform::Texture(ref gl_texture) => {
+                check_limit("textures per draw", texture_index + 1, max_texture_units)?;
+                webgl::gl_active_texture(webgl::TEXTURE0 + texture_index);
+                webgl::gl_bind_texture(webgl::TEXTURE_2D, gl_texture.handle());
+                // null unbinds a sampler left on the unit by an earlier draw
+                webgl::gl_bind_sampler(texture_index, &webgl::Sampler::null());
+                webgl::gl_uniform1i(&attr, texture_index as GLint);
+                texture_index += 1;
+            }
+            &Uniform::SampledTexture(ref gl_texture, ref sampler) => {
+                check_limit("textures per draw", texture_index + 1, max_texture_units)?;
                 webgl::gl_active_texture(webgl::TEXTURE0 + texture_index);
                 webgl::gl_bind_texture(webgl::TEXTURE_2D, gl_texture.handle());
+                webgl::gl_bind_sampler(texture_index, &sampler.handle.0);
                 webgl::gl_uniform1i(&attr, texture_index as GLint);
                 texture_index += 1;
             }
         }
     }
 
-    // define vertex format
+    setup_attributes::<V>(program, 0)?;
+
+    check_gl_error("vertex setup")
+}
+
+// Points the attributes of `V` at the bound array buffer, advancing once per
+// vertex with a divisor of 0 or once per instance with 1
+fn setup_attributes<V: Vertex>(program: &Program, divisor: u32) -> Result<(), Error> {
+    check_vertex_layout::<V>()?;
     let mut step = 0;
     for (attr_name, attr_count, attr_type) in V::attributes() {
-        let attr = webgl::gl_get_attrib_location(program.handle(), &attr_name);
+        let attr = program.attrib_location(&attr_name);
         if attr < 0 {
             return Err(format_err!(
                 "could not find location of attribute {}",
@@ -231,23 +1114,31 @@ pub fn render_vertices<V: Vertex>(
         }
         let attr = attr as u32;
         webgl::gl_enable_vertex_attrib_array(attr as u32);
+        webgl::gl_vertex_attrib_divisor(attr, divisor);
+        let (gl_type, normalized) = match attr_type {
+            VertexAttributeType::Float => (webgl::FLOAT, false),
+            VertexAttributeType::Unsigned => (webgl::UNSIGNED_INT, false),
+            VertexAttributeType::Int => (webgl::INT, false),
+            VertexAttributeType::UnsignedByteNormalized => (webgl::UNSIGNED_BYTE, true),
+            VertexAttributeType::ShortNormalized => (webgl::SHORT, true),
+        };
         match attr_type {
-            VertexAttributeType::Float => {
-                webgl::gl_vertex_attrib_pointer(
+            // vertexAttribPointer would convert these to floats
+            VertexAttributeType::Unsigned | VertexAttributeType::Int => {
+                webgl::gl_vertex_attrib_i_pointer(
                     attr,
                     attr_count as GLsizei,
-                    webgl::FLOAT,
-                    false,
+                    gl_type,
                     V::stride() as GLsizei,
                     step,
                 );
             }
-            VertexAttributeType::Unsigned => {
+            _ => {
                 webgl::gl_vertex_attrib_pointer(
                     attr,
                     attr_count as GLsizei,
-                    webgl::UNSIGNED_INT,
-                    false,
+                    gl_type,
+                    normalized,
                     V::stride() as GLsizei,
                     step,
                 );
@@ -257,18 +1148,307 @@ pub fn render_vertices<V: Vertex>(
         step += (attr_count * attr_type.size()) as GLsizei;
     }
 
-    webgl::gl_draw_arrays(webgl::TRIANGLES, 0, vertices.len() as GLsizei);
+    Ok(())
+}
+
+pub fn set_blend_mode(mode: BlendMode) {
+    let (src, dst) = match blend_factors(mode) {
+        Some(factors) => factors,
+        None => {
+            webgl::gl_disable(webgl::BLEND);
+            return;
+        }
+    };
+    webgl::gl_enable(webgl::BLEND);
+    webgl::gl_blend_equation(webgl::FUNC_ADD);
+    webgl::gl_blend_func(src, dst);
+}
 
+pub fn set_framebuffer_srgb(enabled: bool) -> Result<(), Error> {
+    if enabled {
+        return Err(format_err!(
+            "WebGL can't write sRGB to the default framebuffer"
+        ));
+    }
     Ok(())
 }
 
-pub fn clear(color: Option<(f32, f32, f32, f32)>) {
-    let (r, g, b, a) = color.unwrap_or((0.0, 0.0, 0.0, 1.0));
-    webgl::gl_clear_color(r, g, b, a);
-    webgl::gl_clear(webgl::COLOR_BUFFER_BIT);
+pub fn set_line_width(width: f32) -> Result<(), Error> {
+    // out of range widths are an error on some drivers and clamped on others
+    let (min, max) = line_width_range();
+    if width.is_nan() || width < min || width > max {
+        return Err(format_err!(
+            "line width {} is outside the supported range of {} to {}",
+            width,
+            min,
+            max
+        ));
+    }
+    webgl::gl_line_width(width);
+    check_gl_error("set_line_width")
+}
+
+pub fn line_width_range() -> (f32, f32) {
+    let mut range = [0.0; 2];
+    webgl::gl_get_parameter_fv(webgl::ALIASED_LINE_WIDTH_RANGE, &mut range);
+    (range[0], range[1])
+}
+
+pub fn point_size_range() -> (f32, f32) {
+    let mut range = [0.0; 2];
+    webgl::gl_get_parameter_fv(webgl::ALIASED_POINT_SIZE_RANGE, &mut range);
+    (range[0], range[1])
+}
+
+pub fn set_stencil_test(enabled: bool) {
+    if enabled {
+        webgl::gl_enable(webgl::STENCIL_TEST);
+    } else {
+        webgl::gl_disable(webgl::STENCIL_TEST);
+    }
+}
+
+pub fn set_stencil_func(func: StencilFunc, reference: u8, mask: u8) {
+    let func = match func {
+        StencilFunc::Never => webgl::NEVER,
+        StencilFunc::Less => webgl::LESS,
+        StencilFunc::Equal => webgl::EQUAL,
+        StencilFunc::LessEqual => webgl::LEQUAL,
+        StencilFunc::Greater => webgl::GREATER,
+        StencilFunc::NotEqual => webgl::NOTEQUAL,
+        StencilFunc::GreaterEqual => webgl::GEQUAL,
+        StencilFunc::Always => webgl::ALWAYS,
+    };
+    webgl::gl_stencil_func(func, reference as GLint, mask as GLuint);
+}
+
+fn gl_stencil_op(op: StencilOp) -> GLenum {
+    match op {
+        StencilOp::Keep => webgl::KEEP,
+        StencilOp::Zero => webgl::ZERO,
+        StencilOp::Replace => webgl::REPLACE,
+        StencilOp::Increment => webgl::INCR,
+        StencilOp::IncrementWrap => webgl::INCR_WRAP,
+        StencilOp::Decrement => webgl::DECR,
+        StencilOp::DecrementWrap => webgl::DECR_WRAP,
+        StencilOp::Invert => webgl::INVERT,
+    }
+}
+
+pub fn set_stencil_op(fail: StencilOp, depth_fail: StencilOp, pass: StencilOp) {
+    webgl::gl_stencil_op(
+        gl_stencil_op(fail),
+        gl_stencil_op(depth_fail),
+        gl_stencil_op(pass),
+    );
+}
+
+pub fn set_stencil_write_mask(mask: u8) {
+    webgl::gl_stencil_mask(GLuint::from(mask));
+}
+
+pub fn set_color_mask(red: bool, green: bool, blue: bool, alpha: bool) {
+    webgl::gl_color_mask(red, green, blue, alpha);
+}
+
+/// The scissor rectangle has a bottom left origin like `glScissor`
+pub fn render_state() -> RenderState {
+    let mut viewport = [0; 4];
+    let mut scissor = [0; 4];
+    let mut color_mask = [0; 4];
+    webgl::gl_get_viewport(&mut viewport);
+    webgl::gl_get_parameter_iv(webgl::SCISSOR_BOX, &mut scissor);
+    webgl::gl_get_parameter_iv(webgl::COLOR_WRITEMASK, &mut color_mask);
+    let src = webgl::gl_get_parameter_i(webgl::BLEND_SRC_RGB) as GLenum;
+    let dst = webgl::gl_get_parameter_i(webgl::BLEND_DST_RGB) as GLenum;
+
+    let blend_mode = if webgl::gl_is_enabled(webgl::BLEND) {
+        blend_mode_from_factors(src, dst)
+    } else {
+        Some(BlendMode::None)
+    };
+    RenderState {
+        blend_mode,
+        depth_test: webgl::gl_is_enabled(webgl::DEPTH_TEST),
+        stencil_test: webgl::gl_is_enabled(webgl::STENCIL_TEST),
+        viewport: (
+            viewport[0],
+            viewport[1],
+            viewport[2] as u32,
+            viewport[3] as u32,
+        ),
+        scissor: if webgl::gl_is_enabled(webgl::SCISSOR_TEST) {
+            Some((scissor[0], scissor[1], scissor[2], scissor[3]))
+        } else {
+            None
+        },
+        color_mask: (
+            color_mask[0] != 0,
+            color_mask[1] != 0,
+            color_mask[2] != 0,
+            color_mask[3] != 0,
+        ),
+    }
+}
+
+pub fn set_depth_test(enabled: bool) {
+    if enabled {
+        webgl::gl_enable(webgl::DEPTH_TEST);
+    } else {
+        webgl::gl_disable(webgl::DEPTH_TEST);
+    }
+}
+
+/// Takes the rectangle with a bottom left origin like `glScissor`
+pub fn set_scissor(rect: Option<(i32, i32, i32, i32)>) {
+    match rect {
+        Some((x, y, width, height)) => {
+            webgl::gl_enable(webgl::SCISSOR_TEST);
+            webgl::gl_scissor(x, y, width, height);
+        }
+        None => webgl::gl_disable(webgl::SCISSOR_TEST),
+    }
+}
+
+/// Reads RGBA pixels from the bound framebuffer, rows are ordered bottom to top
+pub fn read_pixels(x: i32, y: i32, width: u32, height: u32) -> Result<Vec<u8>, Error> {
+    let mut data = vec![0u8; (width * height * 4) as usize];
+    webgl::gl_read_pixels(
+        x as GLint,
+        y as GLint,
+        width as GLsizei,
+        height as GLsizei,
+        webgl::RGBA,
+        webgl::UNSIGNED_BYTE,
+        &mut data,
+    );
+    check_gl_error("read_pixels")?;
+
+    Ok(data)
+}
+
+// Clears the buffers given a value and leaves the others untouched
+pub fn clear(color: Option<(f32, f32, f32, f32)>, depth: Option<f32>, stencil: Option<u8>) {
+    let mut mask = 0;
+    if let Some((r, g, b, a)) = color {
+        webgl::gl_clear_color(r, g, b, a);
+        mask |= webgl::COLOR_BUFFER_BIT;
+    }
+    if let Some(depth) = depth {
+        webgl::gl_clear_depth(depth);
+        mask |= webgl::DEPTH_BUFFER_BIT;
+    }
+    if let Some(stencil) = stencil {
+        webgl::gl_clear_stencil(stencil as GLint);
+        mask |= webgl::STENCIL_BUFFER_BIT;
+    }
+    if mask != 0 {
+        webgl::gl_clear(mask);
+    }
+}
+
+// the renderer of every canvas, WebGL state lives in the current context
+// rather than here
+pub struct Backend;
+
+impl PlatformRenderer for Backend {
+    type Texture = Texture;
+    type Program = Program;
+    type VertexBuffer = VertexBuffer;
+
+    fn create_texture(
+        &self,
+        size: (u32, u32),
+        format: TextureFormat,
+        filtering: Option<TextureFiltering>,
+        wrap: Option<TextureWrap>,
+    ) -> Result<Texture, RenderError> {
+        create_texture(size, format, filtering, wrap)
+    }
+    fn create_program(&self, label: &str, vs: &str, fs: &str) -> Result<Program, RenderError> {
+        create_program(label, vs, fs)
+    }
+    fn create_vertex_buffer(&self, usage: BufferUsage) -> Result<VertexBuffer, Error> {
+        create_vertex_buffer(usage)
+    }
+    fn render_vertices<V: Vertex>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &VertexBuffer,
+        program: &Program,
+        vertices: &[V],
+    ) -> Result<(), Error> {
+        render_vertices(primitive, vertex_buffer, program, vertices)
+    }
+    fn render_indexed<V: Vertex, I: Index>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &VertexBuffer,
+        program: &Program,
+        vertices: &[V],
+        indices: &[I],
+    ) -> Result<(), Error> {
+        render_indexed(primitive, vertex_buffer, program, vertices, indices)
+    }
+    fn set_blend_mode(&self, mode: BlendMode) {
+        set_blend_mode(mode)
+    }
+    fn viewport(&self) -> (i32, i32, u32, u32) {
+        viewport()
+    }
+    fn set_viewport(&self, x: i32, y: i32, width: u32, height: u32) {
+        set_viewport(x, y, width, height)
+    }
+    fn read_pixels(&self, x: i32, y: i32, width: u32, height: u32) -> Result<Vec<u8>, Error> {
+        read_pixels(x, y, width, height)
+    }
+    fn clear(&self, color: Option<(f32, f32, f32, f32)>, depth: Option<f32>, stencil: Option<u8>) {
+        clear(color, depth, stencil)
+    }
+}
+
+// Reports any pending GL errors, only checked in debug builds since glGetError
+// stalls the pipeline
+fn check_gl_error(context: &str) -> Result<(), Error> {
+    Ok(gl_errors(context)?)
+}
+
+// check_gl_error for the functions that return a RenderError
+fn gl_errors(context: &str) -> Result<(), RenderError> {
+    if !cfg!(debug_assertions) {
+        return Ok(());
+    }
+
+    let mut errors = Vec::new();
+    loop {
+        let error = webgl::gl_get_error();
+        // bail out after a few errors in case of a lost context, which may
+        // keep reporting errors
+        if error == webgl::NO_ERROR || errors.len() >= 8 {
+            break;
+        }
+        errors.push(error);
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(RenderError::GlError {
+            context: context.into(),
+            codes: errors,
+        })
+    }
 }
 
-fn compile_shader(src: &str, t: GLenum) -> Result<webgl::Shader, Error> {
+fn shader_stage(t: GLenum) -> ShaderStage {
+    if t == webgl::VERTEX_SHADER {
+        ShaderStage::Vertex
+    } else {
+        ShaderStage::Fragment
+    }
+}
+
+fn compile_shader(label: &str, src: &str, t: GLenum) -> Result<webgl::Shader, RenderError> {
     let shader;
     shader = webgl::gl_create_shader(t);
     webgl::gl_shader_source(&shader, src);
@@ -277,12 +1457,22 @@ fn compile_shader(src: &str, t: GLenum) -> Result<webgl::Shader, Error> {
     let status = webgl::gl_get_shader_parameter(&shader, webgl::COMPILE_STATUS);
     if status != (webgl::TRUE as GLint) {
         let log = webgl::gl_get_shader_info_log(&shader);
-        return Err(format_err!("Error compiling shader: {}", log));
+        webgl::gl_delete_shader(&shader);
+        return Err(RenderError::ShaderCompile {
+            label: label.into(),
+            stage: shader_stage(t),
+            log,
+            source: src.into(),
+        });
     }
     Ok(shader)
 }
 
-fn link_program(vs: &VertexShader, fs: &FragmentShader) -> Result<webgl::Program, Error> {
+fn link_program(
+    label: &str,
+    vs: &VertexShader,
+    fs: &FragmentShader,
+) -> Result<webgl::Program, RenderError> {
     let program = webgl::gl_create_program();
     webgl::gl_attach_shader(&program, vs.handle());
     webgl::gl_attach_shader(&program, fs.handle());
@@ -291,7 +1481,16 @@ fn link_program(vs: &VertexShader, fs: &FragmentShader) -> Result<webgl::Program
     let status = webgl::gl_get_program_parameter(&program, webgl::LINK_STATUS);
     if status != (webgl::TRUE as GLint) {
         let log = webgl::gl_get_program_info_log(&program);
-        return Err(format_err!("Error linking program: {}", log));
+        webgl::gl_delete_program(&program);
+        return Err(RenderError::ProgramLink {
+            label: label.into(),
+            log,
+        });
     }
+
+    // the shaders are no longer needed once linked, detach them so they are
+    // freed when dropped
+    webgl::gl_detach_shader(&program, vs.handle());
+    webgl::gl_detach_shader(&program, fs.handle());
     Ok(program)
 }
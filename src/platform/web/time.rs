@@ -0,0 +1,23 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js;
+use js::window::AnimationLoop;
+
+/// Seconds since the page was loaded
+pub fn now() -> f64 {
+    js::window::performance_now() / 1000.0
+}
+
+pub fn run_loop<F: FnMut(f64) -> bool + 'static>(mut f: F) {
+    let frame_loop = Rc::new(RefCell::new(None::<AnimationLoop>));
+    let stop = Rc::clone(&frame_loop);
+    // the loop is only stopped from inside the callback, by which point it is set
+    *frame_loop.borrow_mut() = Some(js::window::run_loop(move |timestamp| {
+        if !f(timestamp / 1000.0) {
+            if let Some(ref frame_loop) = *stop.borrow() {
+                frame_loop.stop();
+            }
+        }
+    }));
+}
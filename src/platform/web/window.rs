@@ -1,15 +1,16 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use failure::Error;
 
-use input::InputEvent;
+use input::{InputEvent, Modifiers, TouchId};
 use js;
 use js::window::{CanvasWindow, InputHandler as JsInputHandler};
-use window::WindowSettings;
+use window::{CursorStyle, WindowSettings};
 
-use super::input::{to_key, to_mouse_button};
+use super::input::{button_mask, to_key, to_modifiers, to_mouse_button};
 use super::Context;
+use platform::Window as PlatformWindow;
 
 pub struct GLContext(js::window::GLContext);
 
@@ -18,19 +19,35 @@ type InputEvents = Rc<RefCell<Vec<InputEvent>>>;
 pub struct Window {
     js_window: CanvasWindow,
     input_events: InputEvents,
+    cursor_visible: Cell<bool>,
+    cursor_style: Cell<CursorStyle>,
 }
 
 impl Window {
     pub fn new(_: &mut Context, settings: WindowSettings) -> Result<Window, Error> {
         let input_events = Rc::new(RefCell::new(Vec::new()));
         let handler = input_handler(&input_events);
-        let WindowSettings { canvas_id, .. } = settings;
+        let WindowSettings {
+            title,
+            canvas_id,
+            high_dpi,
+            ..
+        } = settings;
 
         let canvas_id =
             canvas_id.ok_or_else(|| format_err!("missing canvas id in WindowSettings"))?;
+        if let Some(title) = title {
+            js::window::set_window_title(&title);
+        }
+        let js_window = js::window::create_canvas_window(&canvas_id, handler);
+        if high_dpi {
+            js::window::set_canvas_high_dpi(&js_window, true);
+        }
         Ok(Window {
-            js_window: js::window::create_canvas_window(&canvas_id, handler),
+            js_window,
             input_events,
+            cursor_visible: Cell::new(true),
+            cursor_style: Cell::new(CursorStyle::Default),
         })
     }
 
@@ -40,48 +57,259 @@ impl Window {
         events.into_iter()
     }
 
-    pub fn gl_create_context(&self) -> GLContext {
-        GLContext(js::window::get_window_context(&self.js_window))
+    fn update_cursor(&self) {
+        let css = match self.cursor_style.get() {
+            _ if !self.cursor_visible.get() => "none",
+            CursorStyle::Default => "default",
+            CursorStyle::Pointer => "pointer",
+            CursorStyle::Crosshair => "crosshair",
+            CursorStyle::Move => "move",
+            CursorStyle::None => "none",
+        };
+        js::window::set_canvas_cursor(&self.js_window, css);
+    }
+}
+
+impl PlatformWindow for Window {
+    type GLContext = GLContext;
+
+    fn size(&self) -> (u32, u32) {
+        let mut size = [0; 2];
+        js::window::get_canvas_size(&self.js_window, &mut size);
+        (size[0], size[1])
+    }
+
+    fn logical_size(&self) -> (f32, f32) {
+        let mut size = [0.0; 2];
+        js::window::get_canvas_css_size(&self.js_window, &mut size);
+        (size[0] as f32, size[1] as f32)
+    }
+
+    fn set_title(&self, title: &str) -> Result<(), Error> {
+        js::window::set_window_title(title);
+        Ok(())
+    }
+
+    fn set_cursor_visible(&self, visible: bool) -> Result<(), Error> {
+        self.cursor_visible.set(visible);
+        self.update_cursor();
+        Ok(())
+    }
+
+    fn set_cursor_style(&self, style: CursorStyle) -> Result<(), Error> {
+        self.cursor_style.set(style);
+        self.update_cursor();
+        Ok(())
+    }
+
+    fn set_fullscreen(&self, fullscreen: bool) -> Result<(), Error> {
+        if fullscreen {
+            js::window::request_fullscreen(&self.js_window);
+        } else {
+            js::window::exit_fullscreen();
+        }
+        Ok(())
+    }
+
+    fn set_pointer_lock(&self, locked: bool) {
+        if locked {
+            js::window::request_pointer_lock(&self.js_window);
+        } else {
+            js::window::exit_pointer_lock();
+        }
+    }
+
+    fn set_context_lost(&self, lost: bool) {
+        js::window::set_canvas_context_lost(&self.js_window, lost);
+    }
+
+    fn gl_create_context(&self) -> Result<GLContext, Error> {
+        let context = js::window::get_window_context(&self.js_window);
+        // getContext returns null when the browser or its GPU can't do WebGL 2
+        if context.is_null() {
+            return Err(format_err!("could not create a WebGL 2 context"));
+        }
+        Ok(GLContext(context))
     }
 
-    pub fn gl_set_current(&self, gl_context: &GLContext) {
+    fn gl_set_current(&self, gl_context: &GLContext) {
         js::window::gl_set_current_context(&gl_context.0);
     }
 }
 
 fn input_handler(input_events: &Rc<RefCell<Vec<InputEvent>>>) -> JsInputHandler {
     let mut handler = JsInputHandler::new();
+    let modifiers = Rc::new(Cell::new(Modifiers::default()));
+    // buttons pressed on the canvas, in the DOM `buttons` mask layout
+    let buttons_down = Rc::new(Cell::new(0));
 
     let events = Rc::clone(input_events);
-    handler.set_mouse_move(move |x, y| {
-        events.borrow_mut().push(InputEvent::MouseMove(x, y));
+    let down = Rc::clone(&buttons_down);
+    handler.set_mouse_move_buttons(move |x, y, buttons| {
+        let mut events = events.borrow_mut();
+        events.push(InputEvent::MouseMove(x, y));
+        // buttons released outside the canvas never get a mouseup there
+        for button in 0..3 {
+            let mask = button_mask(button);
+            if down.get() & mask != 0 && buttons & mask == 0 {
+                down.set(down.get() & !mask);
+                events.push(InputEvent::MouseUp {
+                    button: to_mouse_button(button),
+                    position: (x, y),
+                });
+            }
+        }
     });
 
     let events = Rc::clone(input_events);
-    handler.set_mouse_down(move |button, x, y| {
-        events.borrow_mut().push(InputEvent::MouseDown {
+    handler.set_mouse_delta(move |dx, dy| {
+        events.borrow_mut().push(InputEvent::MouseDelta(dx, dy));
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_pointer_lock_change(move |locked| {
+        events
+            .borrow_mut()
+            .push(InputEvent::PointerLockChanged(locked));
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_mouse_enter(move || {
+        events.borrow_mut().push(InputEvent::MouseEnter);
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_mouse_leave(move || {
+        events.borrow_mut().push(InputEvent::MouseLeave);
+    });
+
+    let events = Rc::clone(input_events);
+    let current = Rc::clone(&modifiers);
+    let down = Rc::clone(&buttons_down);
+    handler.set_mouse_down_mod(move |button, x, y, new| {
+        down.set(down.get() | button_mask(button));
+        let mut events = events.borrow_mut();
+        update_modifiers(&mut events, &current, to_modifiers(new));
+        events.push(InputEvent::MouseDown {
             button: to_mouse_button(button),
             position: (x, y),
         });
     });
 
     let events = Rc::clone(input_events);
-    handler.set_mouse_up(move |button, x, y| {
-        events.borrow_mut().push(InputEvent::MouseUp {
+    let current = Rc::clone(&modifiers);
+    let down = Rc::clone(&buttons_down);
+    handler.set_mouse_up_mod(move |button, x, y, new| {
+        down.set(down.get() & !button_mask(button));
+        let mut events = events.borrow_mut();
+        update_modifiers(&mut events, &current, to_modifiers(new));
+        events.push(InputEvent::MouseUp {
             button: to_mouse_button(button),
             position: (x, y),
         });
     });
 
     let events = Rc::clone(input_events);
-    handler.set_key_down(move |key| {
-        events.borrow_mut().push(InputEvent::KeyDown(to_key(key)));
+    handler.set_mouse_wheel(move |dx, dy| {
+        events.borrow_mut().push(InputEvent::MouseWheel(dx, dy));
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_text_input(move |text| {
+        events.borrow_mut().push(InputEvent::TextInput(text));
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_touch_start(move |id, x, y| {
+        events.borrow_mut().push(InputEvent::TouchStart {
+            id: id as TouchId,
+            position: (x, y),
+        });
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_touch_move(move |id, x, y| {
+        events.borrow_mut().push(InputEvent::TouchMove {
+            id: id as TouchId,
+            position: (x, y),
+        });
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_touch_end(move |id, x, y| {
+        events.borrow_mut().push(InputEvent::TouchEnd {
+            id: id as TouchId,
+            position: (x, y),
+        });
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_gamepad_connected(move |index| {
+        events
+            .borrow_mut()
+            .push(InputEvent::GamepadConnected(index));
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_gamepad_disconnected(move |index| {
+        events
+            .borrow_mut()
+            .push(InputEvent::GamepadDisconnected(index));
     });
 
     let events = Rc::clone(input_events);
-    handler.set_key_up(move |key| {
-        events.borrow_mut().push(InputEvent::KeyUp(to_key(key)));
+    handler.set_fullscreen_change(move |fullscreen| {
+        events
+            .borrow_mut()
+            .push(InputEvent::FullscreenChanged(fullscreen));
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_context_lost(move |lost| {
+        events.borrow_mut().push(if lost {
+            InputEvent::ContextLost
+        } else {
+            InputEvent::ContextRestored
+        });
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_focus(move |focused| {
+        events.borrow_mut().push(InputEvent::Focus(focused));
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_resize(move |width, height| {
+        events.borrow_mut().push(InputEvent::Resize(width, height));
+    });
+
+    let events = Rc::clone(input_events);
+    let current = Rc::clone(&modifiers);
+    handler.set_key_down_mod(move |key, new| {
+        let mut events = events.borrow_mut();
+        update_modifiers(&mut events, &current, to_modifiers(new));
+        events.push(InputEvent::KeyDown(to_key(key)));
+    });
+
+    let events = Rc::clone(input_events);
+    handler.set_key_repeat(move |key| {
+        events.borrow_mut().push(InputEvent::KeyRepeat(to_key(key)));
+    });
+
+    let events = Rc::clone(input_events);
+    let current = Rc::clone(&modifiers);
+    handler.set_key_up_mod(move |key, new| {
+        let mut events = events.borrow_mut();
+        update_modifiers(&mut events, &current, to_modifiers(new));
+        events.push(InputEvent::KeyUp(to_key(key)));
     });
 
     handler
 }
+
+fn update_modifiers(events: &mut Vec<InputEvent>, current: &Cell<Modifiers>, new: Modifiers) {
+    if current.get() != new {
+        current.set(new);
+        events.push(InputEvent::ModifiersChanged(new));
+    }
+}
@@ -1,27 +1,255 @@
+use std::collections::VecDeque;
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
 use std::marker::PhantomData;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::{Path, PathBuf};
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
 
 use failure::Error;
 
-use assets::Image;
+use assets::{CompressedImage, Image};
 use platform::rendering as render_impl;
+use platform::{check_limit, numbered_source, Renderer as PlatformRenderer};
+
+pub use embla_derive::Vertex;
+
+/// Failures of the rendering functions that are worth telling apart, such as
+/// a shader that doesn't compile on this GPU. Creating programs and textures
+/// returns them directly, the other functions return them inside the
+/// `failure::Error`, get them back with `error.downcast_ref::<RenderError>()`.
+#[derive(Debug)]
+pub enum RenderError {
+    ShaderCompile {
+        label: String,
+        stage: ShaderStage,
+        log: String,
+        source: String,
+    },
+    ProgramLink {
+        label: String,
+        log: String,
+    },
+    /// A texture size, sample count or other amount past the GPU's limit for it,
+    /// see `Capabilities`
+    LimitExceeded {
+        what: &'static str,
+        value: u32,
+        max: u32,
+    },
+    /// An image written at `offset` does not fit inside the texture
+    RegionOutOfBounds {
+        offset: (u32, u32),
+        region: (u32, u32),
+        texture: (u32, u32),
+    },
+    /// The framebuffer status the driver reported
+    IncompleteFramebuffer(u32),
+    /// The GL error codes raised by `context`, only checked in debug builds
+    GlError {
+        context: String,
+        codes: Vec<u32>,
+    },
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RenderError::ShaderCompile {
+                ref label,
+                stage,
+                ref log,
+                ref source,
+            } => write!(
+                f,
+                "Error compiling {} shader of {}: {}\n{}",
+                match stage {
+                    ShaderStage::Vertex => "vertex",
+                    ShaderStage::Fragment => "fragment",
+                },
+                label,
+                log,
+                numbered_source(source)
+            ),
+            RenderError::ProgramLink { ref label, ref log } => {
+                write!(f, "Error linking program {}: {}", label, log)
+            }
+            RenderError::LimitExceeded { what, value, max } => write!(
+                f,
+                "{}: {} requested but the GPU supports at most {}",
+                what, value, max
+            ),
+            RenderError::RegionOutOfBounds {
+                offset,
+                region,
+                texture,
+            } => write!(
+                f,
+                "{}x{} region at ({}, {}) is outside of the {}x{} texture",
+                region.0, region.1, offset.0, offset.1, texture.0, texture.1
+            ),
+            RenderError::IncompleteFramebuffer(status) => {
+                write!(f, "framebuffer is incomplete (status 0x{:X})", status)
+            }
+            RenderError::GlError {
+                ref context,
+                ref codes,
+            } => {
+                let names: Vec<_> = codes.iter().map(|&code| gl_error_name(code)).collect();
+                write!(f, "GL error in {}: {}", context, names.join(", "))
+            }
+        }
+    }
+}
+
+// failure implements Fail for every std error, so these convert into its Error
+impl ::std::error::Error for RenderError {}
 
 #[derive(Debug, Clone, Copy)]
 pub enum TextureFiltering {
     Nearest,
     Linear,
+    /// Linear filtering between mipmap levels, mipmaps are generated automatically
+    Trilinear,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TextureWrap {
+    ClampToEdge,
+    Repeat,
+    MirroredRepeat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    None,
+    Alpha,
+    Additive,
+    Multiply,
+    PremultipliedAlpha,
+}
+
+/// Comparison of the stencil reference value against the stored value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StencilFunc {
+    Never,
+    Less,
+    Equal,
+    LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always,
+}
+
+/// What happens to the stored stencil value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StencilOp {
+    Keep,
+    Zero,
+    /// Stores the reference value of `set_stencil_func`
+    Replace,
+    Increment,
+    IncrementWrap,
+    Decrement,
+    DecrementWrap,
+    Invert,
+}
+
+impl Default for BlendMode {
+    fn default() -> BlendMode {
+        BlendMode::Alpha
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Triangles,
+    TriangleStrip,
+    Lines,
+    LineStrip,
+    Points,
+}
+
+/// Size of the indices of an indexed draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexType {
+    /// Half the size, for meshes of up to 65536 vertices
+    U16,
+    U32,
+}
+
+impl IndexType {
+    /// Size of one index in bytes
+    pub fn size(self) -> usize {
+        match self {
+            IndexType::U16 => 2,
+            IndexType::U32 => 4,
+        }
+    }
+}
+
+mod sealed {
+    // keeps Index to the types GL can read indices as
+    pub trait Sealed {}
+
+    impl Sealed for u16 {}
+    impl Sealed for u32 {}
+}
+
+/// Integer types `render_indexed` takes indices as, it can't be implemented
+/// outside of embla
+pub trait Index: Copy + sealed::Sealed {
+    fn index_type() -> IndexType;
+    fn to_u32(self) -> u32;
+}
+
+impl Index for u16 {
+    fn index_type() -> IndexType {
+        IndexType::U16
+    }
+
+    fn to_u32(self) -> u32 {
+        u32::from(self)
+    }
 }
 
-#[derive(Clone, Copy)]
+impl Index for u32 {
+    fn index_type() -> IndexType {
+        IndexType::U32
+    }
+
+    fn to_u32(self) -> u32 {
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VertexAttributeType {
     Float,
+    /// `u32` components that reach the shader as integers, declared as `in uint`
+    /// or `in uvec*` which needs `#version 300 es`
     Unsigned,
+    /// `i32` components declared as `in int` or `in ivec*`
+    Int,
+    /// `u8` components read as floats from 0.0 to 1.0, e.g. colors
+    UnsignedByteNormalized,
+    /// `i16` components read as floats from -1.0 to 1.0, e.g. normals
+    ShortNormalized,
 }
 
 impl VertexAttributeType {
+    /// Size of one component in bytes. Attributes have to start at a multiple of
+    /// it, so pad byte attributes to keep the ones after them aligned
     pub fn size(self) -> usize {
         match self {
             VertexAttributeType::Float => 4,
             VertexAttributeType::Unsigned => 4,
+            VertexAttributeType::Int => 4,
+            VertexAttributeType::UnsignedByteNormalized => 1,
+            VertexAttributeType::ShortNormalized => 2,
         }
     }
 }
@@ -36,15 +264,35 @@ pub trait Vertex {
 }
 
 pub enum Uniform {
+    Float(f32),
+    Int(i32),
     Vec2((f32, f32)),
+    Vec3((f32, f32, f32)),
+    Vec4((f32, f32, f32, f32)),
+    /// 3x3 matrix in column-major order, as expected by OpenGL
+    Mat3([f32; 9]),
+    /// 4x4 matrix in column-major order, as expected by OpenGL
+    Mat4([f32; 16]),
     Texture(Texture),
+    /// A texture read with the filtering and wrapping of the sampler instead
+    /// of its own
+    SampledTexture(Texture, Sampler),
 }
 
 impl From<render_impl::Uniform> for Uniform {
     fn from(uniform: render_impl::Uniform) -> Self {
         match uniform {
+            render_impl::Uniform::Float(v) => Uniform::Float(v),
+            render_impl::Uniform::Int(v) => Uniform::Int(v),
             render_impl::Uniform::Vec2(v) => Uniform::Vec2(v),
+            render_impl::Uniform::Vec3(v) => Uniform::Vec3(v),
+            render_impl::Uniform::Vec4(v) => Uniform::Vec4(v),
+            render_impl::Uniform::Mat3(m) => Uniform::Mat3(m),
+            render_impl::Uniform::Mat4(m) => Uniform::Mat4(m),
             render_impl::Uniform::Texture(t) => Uniform::Texture(Texture(t)),
+            render_impl::Uniform::SampledTexture(t, s) => {
+                Uniform::SampledTexture(Texture(t), Sampler(s))
+            }
         }
     }
 }
@@ -52,23 +300,339 @@ impl From<render_impl::Uniform> for Uniform {
 impl Into<render_impl::Uniform> for Uniform {
     fn into(self) -> render_impl::Uniform {
         match self {
+            Uniform::Float(v) => render_impl::Uniform::Float(v),
+            Uniform::Int(v) => render_impl::Uniform::Int(v),
             Uniform::Vec2(v) => render_impl::Uniform::Vec2(v),
+            Uniform::Vec3(v) => render_impl::Uniform::Vec3(v),
+            Uniform::Vec4(v) => render_impl::Uniform::Vec4(v),
+            Uniform::Mat3(m) => render_impl::Uniform::Mat3(m),
+            Uniform::Mat4(m) => render_impl::Uniform::Mat4(m),
             Uniform::Texture(t) => render_impl::Uniform::Texture(t.0),
+            Uniform::SampledTexture(t, s) => render_impl::Uniform::SampledTexture(t.0, s.0),
         }
     }
 }
 
+/// Hint for how often the contents of a vertex buffer are replaced. Non-static
+/// buffers orphan their storage on every upload so the driver does not have to
+/// wait for in-flight draws.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BufferUsage {
+    /// Uploaded once and drawn many times
+    Static,
+    /// Replaced every few frames
+    Dynamic,
+    /// Replaced every frame
+    Stream,
+}
+
+impl Default for BufferUsage {
+    fn default() -> BufferUsage {
+        BufferUsage::Dynamic
+    }
+}
+
 pub struct VertexBuffer(render_impl::VertexBuffer);
 
+impl VertexBuffer {
+    /// Names the buffers in GPU debuggers such as RenderDoc, which needs
+    /// `GL_KHR_debug` and does nothing without it or on the web
+    pub fn set_label(&self, label: &str) {
+        self.0.set_label(label)
+    }
+}
+
+/// Uniform values in GPU memory that any number of programs read through a
+/// uniform block, so data such as the camera is uploaded once per frame instead
+/// of once per program
+pub struct UniformBuffer(render_impl::UniformBuffer);
+
+impl UniformBuffer {
+    /// Uploads `data`, a `#[repr(C)]` struct whose fields follow the std140 layout
+    /// of the block: `vec4` and matrix columns are 16 byte aligned, and `vec3`
+    /// takes the room of a `vec4`
+    pub fn set_data<T: Copy>(&self, data: &T) {
+        let bytes = unsafe {
+            ::std::slice::from_raw_parts(data as *const T as *const u8, ::std::mem::size_of::<T>())
+        };
+        self.0.set_data(bytes)
+    }
+
+    /// Makes the buffer the source of every uniform block bound to `binding`,
+    /// see `Program::set_uniform_block_binding`
+    pub fn bind(&self, binding: u32) -> Result<(), Error> {
+        check_uniform_binding(binding)?;
+
+        self.0.bind(binding);
+        Ok(())
+    }
+
+    /// Names the buffer in GPU debuggers, see `VertexBuffer::set_label`
+    pub fn set_label(&self, label: &str) {
+        self.0.set_label(label)
+    }
+}
+
 pub struct Program<V: Vertex> {
     inner: render_impl::Program,
     vertex_format: PhantomData<V>,
+    #[cfg(not(target_arch = "wasm32"))]
+    files: Option<ShaderFiles>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+/// GLSL dialect that shaders without a `#version` directive are compiled as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShaderProfile {
+    /// `#version 100`, the dialect of WebGL 1 and GLES 2, which is also accepted
+    /// by the WebGL 2 and GLES 3 contexts both backends create
+    Gles2,
+    /// `#version 300 es`
+    Gles3,
+    /// `#version 330 core`
+    Desktop,
+}
+
+impl ShaderProfile {
+    fn version_directive(self) -> &'static str {
+        match self {
+            ShaderProfile::Gles2 => "#version 100",
+            ShaderProfile::Gles3 => "#version 300 es",
+            ShaderProfile::Desktop => "#version 330 core",
+        }
+    }
+
+    fn is_es(self) -> bool {
+        self != ShaderProfile::Desktop
+    }
+}
+
+/// Which buffers `Renderer::clear_buffers` clears and to what, buffers without a
+/// value are left untouched
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClearOptions {
+    pub color: Option<(f32, f32, f32, f32)>,
+    /// Usually 1.0, or 0.0 for reverse depth where nearer fragments have larger depth
+    pub depth: Option<f32>,
+    pub stencil: Option<u8>,
+}
+
+/// A rectangle of the bound target in pixels with a bottom left origin, like the
+/// arguments of `set_viewport`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Viewport {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Divides the viewport into `count` columns from left to right, the last
+    /// one takes up the pixels that don't divide evenly
+    pub fn split_columns(&self, count: u32) -> Vec<Viewport> {
+        let count = count.max(1);
+        let width = self.width / count;
+        (0..count)
+            .map(|i| {
+                let x = self.x + (i * width) as i32;
+                let last = i + 1 == count;
+                let width = if last { self.width - i * width } else { width };
+                Viewport::new(x, self.y, width, self.height)
+            })
+            .collect()
+    }
+
+    /// Divides the viewport into `count` rows from top to bottom, the last one
+    /// takes up the pixels that don't divide evenly
+    pub fn split_rows(&self, count: u32) -> Vec<Viewport> {
+        let count = count.max(1);
+        let height = self.height / count;
+        (0..count)
+            .map(|i| {
+                let last = i + 1 == count;
+                let row_height = if last {
+                    self.height - i * height
+                } else {
+                    height
+                };
+                let y = self.y + (self.height - i * height - row_height) as i32;
+                Viewport::new(self.x, y, self.width, row_height)
+            })
+            .collect()
+    }
+}
+
+/// The state an overlay pass usually changes, captured so that it can be put
+/// back exactly afterwards
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderState {
+    /// `None` when blending was set up outside of `set_blend_mode`, which is
+    /// then left alone by `apply`
+    pub blend_mode: Option<BlendMode>,
+    pub depth_test: bool,
+    pub stencil_test: bool,
+    /// `(x, y, width, height)` as passed to `set_viewport`
+    pub viewport: (i32, i32, u32, u32),
+    /// The rectangle of `set_scissor`, with a top left origin
+    pub scissor: Option<(i32, i32, i32, i32)>,
+    pub color_mask: (bool, bool, bool, bool),
+}
+
+impl RenderState {
+    /// Reads the current state of the renderer's target
+    pub fn capture(renderer: &Renderer) -> RenderState {
+        renderer.target.make_current();
+
+        let mut state = render_impl::render_state();
+        let viewport = state.viewport;
+        state.scissor = state.scissor.map(|rect| from_target_rect(rect, viewport));
+        state
+    }
+
+    pub fn apply(&self, renderer: &Renderer) {
+        if let Some(mode) = self.blend_mode {
+            renderer.set_blend_mode(mode);
+        }
+        renderer.set_depth_test(self.depth_test);
+        renderer.set_stencil_test(self.stencil_test);
+        let (x, y, width, height) = self.viewport;
+        renderer.set_viewport(x, y, width, height);
+        // after the viewport, which the scissor rectangle is relative to
+        renderer.set_scissor(self.scissor);
+        let (red, green, blue, alpha) = self.color_mask;
+        renderer.set_color_mask(red, green, blue, alpha);
+    }
+}
+
+/// Fills in what shader sources leave out, so that the same source compiles
+/// on every backend
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderOptions {
+    pub profile: ShaderProfile,
+    /// Adds `precision mediump float;` to GLES fragment shaders that don't
+    /// declare a default precision, which GLES requires
+    pub default_precision: bool,
+}
+
+impl Default for ShaderOptions {
+    fn default() -> ShaderOptions {
+        ShaderOptions {
+            profile: ShaderProfile::Gles2,
+            default_precision: true,
+        }
+    }
+}
+
+impl ShaderOptions {
+    /// Returns `src` with a `#version` directive and default precision added
+    /// where they are missing. Sources that specify both are returned as is.
+    pub fn preprocess(&self, src: &str, stage: ShaderStage) -> String {
+        let starts_with = |prefix: &str| src.lines().any(|l| l.trim_start().starts_with(prefix));
+        let add_version = !starts_with("#version");
+        let add_precision = self.default_precision
+            && self.profile.is_es()
+            && stage == ShaderStage::Fragment
+            && !src.lines().any(is_float_precision);
+        if !add_version && !add_precision {
+            return src.to_string();
+        }
+
+        // the precision statement has to follow directives like #extension
+        let lines: Vec<&str> = src.lines().collect();
+        let header_len = lines
+            .iter()
+            .take_while(|l| {
+                let l = l.trim();
+                l.is_empty() || l.starts_with('#') || l.starts_with("//")
+            })
+            .count();
+
+        let mut out = String::with_capacity(src.len() + 64);
+        if add_version {
+            out.push_str(self.profile.version_directive());
+            out.push('\n');
+        }
+        for line in &lines[..header_len] {
+            out.push_str(line);
+            out.push('\n');
+        }
+        if add_precision {
+            out.push_str("precision mediump float;\n");
+        }
+        for line in &lines[header_len..] {
+            out.push_str(line);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+// Shader sources of a program created with `create_program_from_files`, along
+// with the last modification time that was loaded
+#[cfg(not(target_arch = "wasm32"))]
+struct ShaderFiles {
+    vertex: PathBuf,
+    fragment: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ShaderFiles {
+    fn last_modified(&self) -> Option<SystemTime> {
+        let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+        modified(&self.vertex).max(modified(&self.fragment))
+    }
+
+    fn label(&self) -> String {
+        format!("{} and {}", self.vertex.display(), self.fragment.display())
+    }
+
+    fn read(&self) -> Result<(String, String), Error> {
+        let read = |path: &Path| {
+            fs::read_to_string(path)
+                .map_err(|e| format_err!("could not read shader {}: {}", path.display(), e))
+        };
+        Ok((read(&self.vertex)?, read(&self.fragment)?))
+    }
 }
 
 impl<V: Vertex> Program<V> {
+    /// Names the program in GPU debuggers, see `VertexBuffer::set_label`
+    pub fn set_label(&self, label: &str) {
+        self.inner.set_label(label)
+    }
+    /// Reads the uniform block named `block` from the `UniformBuffer` bound to
+    /// `binding`, kept when the program is reloaded
+    pub fn set_uniform_block_binding(&mut self, block: &str, binding: u32) -> Result<(), Error> {
+        check_uniform_binding(binding)?;
+
+        self.inner.set_uniform_block_binding(block, binding)
+    }
+    /// Sets the value uploaded with every draw, replacing an earlier value of
+    /// the same uniform
     pub fn set_uniform(&mut self, name: &str, uniform: Uniform) {
         self.inner.set_uniform(name, uniform.into())
     }
+    /// Removes every uniform value, for rebuilding them from scratch. Uniforms
+    /// not set again keep the value of the last draw in the GL program
+    pub fn clear_uniforms(&mut self) {
+        self.inner.clear_uniforms()
+    }
     pub fn uniforms<'a>(&'a self) -> impl Iterator<Item = (String, Uniform)> + 'a {
         self.inner
             .uniforms()
@@ -76,6 +640,84 @@ impl<V: Vertex> Program<V> {
     }
 }
 
+/// Pixel layout of a texture. Image data uploaded to a texture must use the
+/// same layout, tightly packed without row padding.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextureFormat {
+    R8,
+    Rg8,
+    Rgb8,
+    Rgba8,
+    /// RGBA with the color channels sRGB encoded, they are converted to linear
+    /// values when sampled. Alpha is always stored linearly.
+    Srgba8,
+}
+
+impl TextureFormat {
+    pub fn bytes_per_pixel(&self) -> u32 {
+        match *self {
+            TextureFormat::R8 => 1,
+            TextureFormat::Rg8 => 2,
+            TextureFormat::Rgb8 => 3,
+            TextureFormat::Rgba8 => 4,
+            TextureFormat::Srgba8 => 4,
+        }
+    }
+}
+
+/// GPU compressed pixel data made of 4x4 pixel blocks, uploaded as is from a KTX
+/// or DDS payload with `Renderer::create_compressed_texture`. It takes 4 to 8
+/// times less memory than RGBA8. DXT is common on desktop GPUs and ETC2 and ASTC
+/// on mobile ones, check `Renderer::compressed_format_supported` first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompressedFormat {
+    /// BC1, RGB in 8 bytes per block
+    Dxt1,
+    /// BC3, RGBA in 16 bytes per block
+    Dxt5,
+    /// RGB in 8 bytes per block
+    Etc2Rgb8,
+    /// ETC2 color with EAC alpha in 16 bytes per block
+    Etc2Rgba8,
+    /// RGBA in 16 bytes per block
+    Astc4x4,
+}
+
+impl CompressedFormat {
+    pub fn bytes_per_block(self) -> u32 {
+        match self {
+            CompressedFormat::Dxt1 | CompressedFormat::Etc2Rgb8 => 8,
+            CompressedFormat::Dxt5 | CompressedFormat::Etc2Rgba8 | CompressedFormat::Astc4x4 => 16,
+        }
+    }
+
+    /// Bytes of a `size` image, the blocks at the right and bottom edges are
+    /// stored whole even when the size is not a multiple of 4. `None` when that
+    /// doesn't fit in a `usize`.
+    pub fn data_size(self, size: (u32, u32)) -> Option<usize> {
+        let blocks = ((u64::from(size.0) + 3) / 4) * ((u64::from(size.1) + 3) / 4);
+        let bytes = blocks.checked_mul(u64::from(self.bytes_per_block()))?;
+        if bytes > usize::max_value() as u64 {
+            return None;
+        }
+        Some(bytes as usize)
+    }
+
+    /// The layout textures of this format are sampled as
+    pub fn texture_format(self) -> TextureFormat {
+        match self {
+            CompressedFormat::Dxt1 | CompressedFormat::Etc2Rgb8 => TextureFormat::Rgb8,
+            _ => TextureFormat::Rgba8,
+        }
+    }
+}
+
+impl Default for TextureFormat {
+    fn default() -> TextureFormat {
+        TextureFormat::Rgba8
+    }
+}
+
 #[derive(Clone)]
 pub struct Texture(render_impl::Texture);
 
@@ -85,10 +727,217 @@ impl Into<render_impl::Texture> for Texture {
     }
 }
 
+impl PartialEq for Texture {
+    fn eq(&self, other: &Texture) -> bool {
+        self.0 == other.0
+    }
+}
+
 impl Texture {
-    pub fn set_region(&self, image: &Image, offset: (u32, u32)) {
+    /// Width and height the texture was created with
+    pub fn size(&self) -> (u32, u32) {
+        self.0.size()
+    }
+
+    /// The layout the texture is sampled as, for compressed textures that is the
+    /// layout they decompress to
+    pub fn format(&self) -> TextureFormat {
+        self.0.format()
+    }
+
+    /// The format of textures made with `Renderer::create_compressed_texture`
+    pub fn compressed_format(&self) -> Option<CompressedFormat> {
+        self.0.compressed_format()
+    }
+
+    /// Fails on compressed textures, which can only be replaced as a whole
+    pub fn set_region(&self, image: &Image, offset: (u32, u32)) -> Result<(), Error> {
+        check_uncompressed(self)?;
+        check_image_size(image, self.format())?;
+        check_region_bounds(image, offset, self.size())?;
+
         self.0.set_region(image, offset)
     }
+
+    /// Uploads every image at its offset with a single texture bind, for updating
+    /// many small parts of a texture at once such as a glyph cache. Nothing is
+    /// uploaded if any of the regions is invalid.
+    pub fn set_regions(&self, updates: &[(Image, (u32, u32))]) -> Result<(), Error> {
+        check_uncompressed(self)?;
+        for &(ref image, offset) in updates {
+            check_image_size(image, self.format())?;
+            check_region_bounds(image, offset, self.size())?;
+        }
+
+        self.0
+            .set_regions(updates.iter().map(|&(ref image, offset)| (image, offset)))
+    }
+
+    /// Replaces the whole texture with `image`, which has to be the size of the
+    /// texture
+    pub fn update(&self, image: &Image) -> Result<(), Error> {
+        check_image_dimensions(image, self.size())?;
+        self.set_region(image, (0, 0))
+    }
+
+    /// Samples the texture up to `level` times along the view direction, which
+    /// keeps surfaces seen at grazing angles sharp. The level is clamped to what
+    /// the GPU supports and the applied value is returned, 1.0 when
+    /// `EXT_texture_filter_anisotropic` is not available and the texture keeps
+    /// its plain filtering.
+    pub fn set_anisotropy(&self, level: f32) -> f32 {
+        self.0.set_anisotropy(level)
+    }
+
+    /// Reads the texture back as an RGBA8 image with the rows in upload order,
+    /// for checking what was uploaded. Formats with fewer channels read 0 for the
+    /// missing colors and 255 for alpha.
+    pub fn read_pixels(&self) -> Result<Image, Error> {
+        let (width, height) = self.size();
+
+        Ok(Image {
+            data: self.0.read_pixels()?,
+            width,
+            height,
+        })
+    }
+
+    /// Names the texture in GPU debuggers, see `VertexBuffer::set_label`
+    pub fn set_label(&self, label: &str) {
+        self.0.set_label(label)
+    }
+}
+
+/// Filtering and wrapping that override those of a texture while it is sampled
+/// through `Uniform::SampledTexture`, so the same texture can be read with
+/// nearest filtering in one pass and linear in another. Clones share the sampler.
+/// `Trilinear` only works on textures created with it, the others lack mipmaps.
+#[derive(Clone)]
+pub struct Sampler(render_impl::Sampler);
+
+/// An offscreen render target with a color texture and a depth buffer. Bind it
+/// with `Renderer::bind_framebuffer` to draw into it, then sample `texture()` in a
+/// later pass.
+pub struct Framebuffer {
+    inner: render_impl::Framebuffer,
+    textures: Vec<Texture>,
+}
+
+impl Framebuffer {
+    /// The first color attachment
+    pub fn texture(&self) -> &Texture {
+        &self.textures[0]
+    }
+
+    /// Every color attachment, in the order of the fragment shader outputs
+    pub fn textures(&self) -> &[Texture] {
+        &self.textures
+    }
+}
+
+// results arrive a few frames late, begin skips measuring while all are in flight
+const GPU_TIMER_QUERIES: usize = 4;
+
+/// Measures how long the GPU takes for the draws between `begin` and `end`.
+/// Results are collected without waiting on the GPU, so `elapsed_ms` lags a few
+/// frames behind. Only one timer can measure at a time.
+pub struct GpuTimer {
+    free: Vec<render_impl::TimerQuery>,
+    pending: VecDeque<render_impl::TimerQuery>,
+    active: Option<render_impl::TimerQuery>,
+    check_disjoint: bool,
+    elapsed_ms: Option<f64>,
+}
+
+impl GpuTimer {
+    pub fn begin(&mut self) {
+        self.collect();
+        if self.active.is_some() {
+            return;
+        }
+        if let Some(query) = self.free.pop() {
+            query.begin();
+            self.active = Some(query);
+        }
+    }
+
+    pub fn end(&mut self) {
+        if let Some(query) = self.active.take() {
+            query.end();
+            self.pending.push_back(query);
+        }
+        self.collect();
+    }
+
+    /// GPU time of the latest range whose result is in, `None` until the first
+    /// one is
+    pub fn elapsed_ms(&self) -> Option<f64> {
+        self.elapsed_ms
+    }
+
+    fn collect(&mut self) {
+        // results measured across a disjoint event such as a GPU clock change are
+        // garbage, the queries are still recycled
+        let disjoint = self.check_disjoint && render_impl::gpu_timer_disjoint();
+        while self
+            .pending
+            .front()
+            .map_or(false, |query| query.is_available())
+        {
+            let query = self.pending.pop_front().unwrap();
+            if !disjoint {
+                self.elapsed_ms = Some(query.result_ns() as f64 / 1_000_000.0);
+            }
+            self.free.push(query);
+        }
+    }
+}
+
+/// Limits and identification of the GL context, for picking texture sizes and
+/// features that the hardware can handle
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    /// Largest width and height of a texture
+    pub max_texture_size: u32,
+    /// Number of textures a single draw can sample
+    pub max_texture_units: u32,
+    /// Largest sample count for `create_multisampled_framebuffer`
+    pub max_samples: u32,
+    /// Most color attachments of `create_framebuffer_with_attachments`
+    pub max_color_attachments: u32,
+    /// Number of binding points for `UniformBuffer::bind`
+    pub max_uniform_buffer_bindings: u32,
+    pub version: String,
+    pub renderer: String,
+    pub extensions: Vec<String>,
+}
+
+impl Capabilities {
+    pub fn has_extension(&self, name: &str) -> bool {
+        self.extensions.iter().any(|extension| extension == name)
+    }
+}
+
+/// Importance of a `DebugMessage`, ordered from least to most severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DebugSeverity {
+    Notification,
+    Low,
+    Medium,
+    /// Errors and undefined behavior
+    High,
+}
+
+/// Message reported by the driver through the GL debug output
+#[derive(Clone, Debug)]
+pub struct DebugMessage {
+    /// Part of the GL that produced the message, such as "api" or "shader compiler"
+    pub source: &'static str,
+    /// Kind of the message, such as "error" or "performance"
+    pub kind: &'static str,
+    pub severity: DebugSeverity,
+    pub id: u32,
+    pub message: String,
 }
 
 pub trait RenderTarget {
@@ -104,52 +953,2162 @@ impl<'a> Renderer<'a> {
         Renderer { target }
     }
 
+    pub fn capabilities(&self) -> Capabilities {
+        self.target.make_current();
+
+        render_impl::capabilities()
+    }
+
+    /// Prints driver messages of at least `min_severity` to stderr with
+    /// `eprintln!`, meant for debugging. `enable_debug_output_with` passes them to
+    /// a callback instead, e.g. to send them to a log.
+    pub fn enable_debug_output(&self, min_severity: DebugSeverity) -> Result<(), Error> {
+        self.enable_debug_output_with(min_severity, |message| {
+            eprintln!(
+                "GL {} {} {:?} ({}): {}",
+                message.source, message.kind, message.severity, message.id, message.message
+            )
+        })
+    }
+
+    /// Calls `callback` for every driver message of at least `min_severity`,
+    /// replacing the previous callback. Messages are reported during the GL call
+    /// that caused them. Warnings of embla itself, like uniforms a program doesn't
+    /// use, are reported as "application" messages. Needs `KHR_debug`, which
+    /// drivers usually only offer in windows created with `WindowSettings::gl_debug`,
+    /// and fails when it is missing or on the web
+    pub fn enable_debug_output_with<F: FnMut(&DebugMessage) + 'static>(
+        &self,
+        min_severity: DebugSeverity,
+        callback: F,
+    ) -> Result<(), Error> {
+        self.target.make_current();
+
+        render_impl::enable_debug_output(min_severity, Box::new(callback))
+    }
+
+    /// Size of the viewport, which follows `set_viewport` and `bind_framebuffer`
     pub fn screen_size(&self) -> (i32, i32) {
         self.target.make_current();
 
         render_impl::screen_size()
     }
 
-    pub fn create_vertex_buffer(&self) -> Result<VertexBuffer, Error> {
+    pub fn create_vertex_buffer(&self, usage: BufferUsage) -> Result<VertexBuffer, Error> {
+        self.target.make_current();
+
+        Ok(VertexBuffer(
+            render_impl::Backend.create_vertex_buffer(usage)?,
+        ))
+    }
+
+    pub fn create_uniform_buffer(&self, usage: BufferUsage) -> Result<UniformBuffer, Error> {
         self.target.make_current();
 
-        Ok(VertexBuffer(render_impl::create_vertex_buffer()?))
+        Ok(UniformBuffer(render_impl::create_uniform_buffer(usage)?))
     }
 
-    pub fn create_program<V: Vertex>(&self, vs: &str, fs: &str) -> Result<Program<V>, Error> {
+    /// Fails when the GPU has no timer queries, which on the web and in GLES
+    /// need `EXT_disjoint_timer_query`
+    pub fn create_gpu_timer(&self) -> Result<GpuTimer, Error> {
         self.target.make_current();
 
+        let check_disjoint = render_impl::gpu_timer_support()
+            .ok_or_else(|| format_err!("GPU timer queries are not supported"))?;
+        Ok(GpuTimer {
+            free: (0..GPU_TIMER_QUERIES)
+                .map(|_| render_impl::create_timer_query())
+                .collect(),
+            pending: VecDeque::new(),
+            active: None,
+            check_disjoint,
+            elapsed_ms: None,
+        })
+    }
+
+    pub fn create_program<V: Vertex>(&self, vs: &str, fs: &str) -> Result<Program<V>, RenderError> {
+        self.create_labeled_program("unnamed program", vs, fs)
+    }
+
+    /// Like `create_program`, `label` is included in compile and link errors to
+    /// tell which program failed
+    pub fn create_labeled_program<V: Vertex>(
+        &self,
+        label: &str,
+        vs: &str,
+        fs: &str,
+    ) -> Result<Program<V>, RenderError> {
+        self.create_program_with_options(label, vs, fs, &ShaderOptions::default())
+    }
+
+    /// Creates a program from sources preprocessed with `options`, see
+    /// `ShaderOptions::preprocess`
+    pub fn create_program_with_options<V: Vertex>(
+        &self,
+        label: &str,
+        vs: &str,
+        fs: &str,
+        options: &ShaderOptions,
+    ) -> Result<Program<V>, RenderError> {
+        self.target.make_current();
+
+        let vs = options.preprocess(vs, ShaderStage::Vertex);
+        let fs = options.preprocess(fs, ShaderStage::Fragment);
         Ok(Program {
-            inner: render_impl::create_program(vs, fs)?,
+            inner: render_impl::Backend.create_program(label, &vs, &fs)?,
             vertex_format: PhantomData,
+            #[cfg(not(target_arch = "wasm32"))]
+            files: None,
         })
     }
 
+    /// Creates a program from shader files, which `reload_program` checks for
+    /// changes while the game is running
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn create_program_from_files<V: Vertex, P: AsRef<Path>>(
+        &self,
+        vs_path: P,
+        fs_path: P,
+    ) -> Result<Program<V>, Error> {
+        let mut files = ShaderFiles {
+            vertex: vs_path.as_ref().to_path_buf(),
+            fragment: fs_path.as_ref().to_path_buf(),
+            modified: None,
+        };
+        files.modified = files.last_modified();
+        let (vs, fs) = files.read()?;
+
+        let mut program = self.create_labeled_program(&files.label(), &vs, &fs)?;
+        program.files = Some(files);
+        Ok(program)
+    }
+
+    /// Recompiles a program created from files if any of them changed since it
+    /// was last loaded, keeping its uniforms. Returns whether it was reloaded. If
+    /// the new shaders fail to compile the error is returned and the program
+    /// keeps working with the previous shaders until the files change again.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn reload_program<V: Vertex>(&self, program: &mut Program<V>) -> Result<bool, Error> {
+        let (label, (vs, fs)) = match program.files {
+            Some(ref mut files) => {
+                let modified = files.last_modified();
+                if modified == files.modified {
+                    return Ok(false);
+                }
+                files.modified = modified;
+                (files.label(), files.read()?)
+            }
+            None => return Ok(false),
+        };
+
+        self.target.make_current();
+
+        let options = ShaderOptions::default();
+        program.inner.relink(
+            &label,
+            &options.preprocess(&vs, ShaderStage::Vertex),
+            &options.preprocess(&fs, ShaderStage::Fragment),
+        )?;
+        Ok(true)
+    }
+
     pub fn create_texture(
         &self,
         size: (u32, u32),
+        format: TextureFormat,
         filtering: Option<TextureFiltering>,
-    ) -> Result<Texture, Error> {
+        wrap: Option<TextureWrap>,
+    ) -> Result<Texture, RenderError> {
         self.target.make_current();
 
-        Ok(Texture(render_impl::create_texture(size, filtering)?))
+        check_texture_size(size)?;
+        Ok(Texture(
+            render_impl::Backend.create_texture(size, format, filtering, wrap)?,
+        ))
     }
 
-    pub fn render_vertices<V: Vertex>(
+    pub fn create_texture_from_image(
         &self,
-        vertex_buffer: &VertexBuffer,
-        program: &Program<V>,
-        vertices: &Vec<V>,
-    ) -> Result<(), Error> {
+        image: &Image,
+        filtering: Option<TextureFiltering>,
+        wrap: Option<TextureWrap>,
+    ) -> Result<Texture, Error> {
+        check_image_size(image, TextureFormat::Rgba8)?;
+
         self.target.make_current();
 
-        render_impl::render_vertices(&vertex_buffer.0, &program.inner, vertices)?;
-        Ok(())
+        check_texture_size((image.width, image.height))?;
+        Ok(Texture(render_impl::create_texture_from_image(
+            image, filtering, wrap,
+        )?))
     }
 
-    pub fn clear(&self, color: Option<(f32, f32, f32, f32)>) {
+    pub fn compressed_format_supported(&self, format: CompressedFormat) -> bool {
         self.target.make_current();
 
-        render_impl::clear(color);
+        render_impl::compressed_format_supported(format)
     }
+
+    /// Creates a texture from pre-compressed `data` without mipmaps, so
+    /// `Trilinear` filtering is not available. Use
+    /// `create_texture_from_compressed_image` to upload the mip levels of a file.
+    pub fn create_compressed_texture(
+        &self,
+        format: CompressedFormat,
+        size: (u32, u32),
+        data: &[u8],
+        filtering: Option<TextureFiltering>,
+        wrap: Option<TextureWrap>,
+    ) -> Result<Texture, Error> {
+        self.create_compressed_levels(format, size, &[data], filtering, wrap)
+    }
+
+    /// Creates a texture with every mip level of `image`, `Trilinear` filtering
+    /// blends between the levels it has
+    pub fn create_texture_from_compressed_image(
+        &self,
+        image: &CompressedImage,
+        filtering: Option<TextureFiltering>,
+        wrap: Option<TextureWrap>,
+    ) -> Result<Texture, Error> {
+        let levels: Vec<&[u8]> = image.levels.iter().map(|level| &level[..]).collect();
+        self.create_compressed_levels(
+            image.format,
+            (image.width, image.height),
+            &levels,
+            filtering,
+            wrap,
+        )
+    }
+
+    fn create_compressed_levels(
+        &self,
+        format: CompressedFormat,
+        size: (u32, u32),
+        levels: &[&[u8]],
+        filtering: Option<TextureFiltering>,
+        wrap: Option<TextureWrap>,
+    ) -> Result<Texture, Error> {
+        check_compressed_levels(format, size, levels, filtering)?;
+
+        self.target.make_current();
+
+        if !render_impl::compressed_format_supported(format) {
+            return Err(format_err!(
+                "{:?} textures are not supported by the GPU",
+                format
+            ));
+        }
+        check_texture_size(size)?;
+        Ok(Texture(render_impl::create_compressed_texture(
+            format, size, levels, filtering, wrap,
+        )?))
+    }
+
+    pub fn create_sampler(
+        &self,
+        filtering: TextureFiltering,
+        wrap: TextureWrap,
+    ) -> Result<Sampler, Error> {
+        self.target.make_current();
+
+        Ok(Sampler(render_impl::create_sampler(filtering, wrap)?))
+    }
+
+    pub fn create_framebuffer(
+        &self,
+        size: (u32, u32),
+        filtering: Option<TextureFiltering>,
+    ) -> Result<Framebuffer, Error> {
+        let texture = self.create_texture(
+            size,
+            TextureFormat::Rgba8,
+            filtering,
+            Some(TextureWrap::ClampToEdge),
+        )?;
+
+        Ok(Framebuffer {
+            inner: render_impl::create_framebuffer(&[&texture.0], size, 1)?,
+            textures: vec![texture],
+        })
+    }
+
+    /// Creates a framebuffer with a color texture of each format, so one draw can
+    /// write several outputs such as albedo and normals. The fragment shader
+    /// output at `layout(location = N)` is written to the Nth texture.
+    pub fn create_framebuffer_with_attachments(
+        &self,
+        size: (u32, u32),
+        formats: &[TextureFormat],
+        filtering: Option<TextureFiltering>,
+    ) -> Result<Framebuffer, Error> {
+        self.target.make_current();
+
+        if formats.is_empty() {
+            return Err(format_err!(
+                "framebuffer needs at least one color attachment"
+            ));
+        }
+        check_limit(
+            "color attachments",
+            formats.len() as u32,
+            render_impl::max_color_attachments(),
+        )?;
+        let textures = formats
+            .iter()
+            .map(|&format| {
+                self.create_texture(size, format, filtering, Some(TextureWrap::ClampToEdge))
+            })
+            .collect::<Result<Vec<_>, RenderError>>()?;
+
+        let handles: Vec<_> = textures.iter().map(|texture| &texture.0).collect();
+        Ok(Framebuffer {
+            inner: render_impl::create_framebuffer(&handles, size, 1)?,
+            textures,
+        })
+    }
+
+    /// Creates a framebuffer that is drawn with `samples` (2, 4 or 8) samples per
+    /// pixel to smooth out edges. Its texture is only updated by
+    /// `resolve_framebuffer`, call that before sampling it.
+    pub fn create_multisampled_framebuffer(
+        &self,
+        size: (u32, u32),
+        samples: u32,
+        filtering: Option<TextureFiltering>,
+    ) -> Result<Framebuffer, Error> {
+        self.target.make_current();
+
+        if samples != 2 && samples != 4 && samples != 8 {
+            return Err(format_err!(
+                "{} samples requested, multisampling supports 2, 4 or 8",
+                samples
+            ));
+        }
+        check_limit("samples", samples, render_impl::max_samples())?;
+        let texture = self.create_texture(
+            size,
+            TextureFormat::Rgba8,
+            filtering,
+            Some(TextureWrap::ClampToEdge),
+        )?;
+
+        Ok(Framebuffer {
+            inner: render_impl::create_framebuffer(&[&texture.0], size, samples)?,
+            textures: vec![texture],
+        })
+    }
+
+    /// Copies the averaged samples of a multisampled framebuffer into its
+    /// texture, does nothing for other framebuffers.
+    pub fn resolve_framebuffer(&self, framebuffer: &Framebuffer) -> Result<(), Error> {
+        self.target.make_current();
+
+        render_impl::resolve_framebuffer(&framebuffer.inner)
+    }
+
+    /// Makes `framebuffer` the target of subsequent clears and draw calls and sets
+    /// the viewport to its size. `None` goes back to drawing to the screen.
+    pub fn bind_framebuffer(&self, framebuffer: Option<&Framebuffer>) {
+        self.target.make_current();
+
+        render_impl::bind_framebuffer(framebuffer.map(|f| &f.inner));
+    }
+
+    /// Sets the region of the bound target that is drawn to, usually called with
+    /// the new size after an `InputEvent::Resize`
+    pub fn set_viewport(&self, x: i32, y: i32, width: u32, height: u32) {
+        self.target.make_current();
+
+        render_impl::Backend.set_viewport(x, y, width, height);
+    }
+
+    /// The viewport of the bound target, see `set_viewport`
+    pub fn viewport(&self) -> Viewport {
+        self.target.make_current();
+
+        let (x, y, width, height) = render_impl::Backend.viewport();
+        Viewport::new(x, y, width, height)
+    }
+
+    /// Runs `draw` with the viewport and scissor set to `view`, for drawing one of
+    /// the views of a split screen. Clears and draws stay inside the view and
+    /// `screen_size` is its size. The previous viewport and scissor are restored
+    /// afterwards, also when `draw` fails.
+    pub fn render_view<F>(&self, view: Viewport, draw: F) -> Result<(), Error>
+    where
+        F: FnOnce(&Renderer) -> Result<(), Error>,
+    {
+        let state = RenderState::capture(self);
+        self.set_viewport(view.x, view.y, view.width, view.height);
+        self.set_scissor(Some((0, 0, view.width as i32, view.height as i32)));
+
+        let result = draw(self);
+
+        state.apply(self);
+        result
+    }
+
+    pub fn render_vertices<V: Vertex>(
+        &self,
+        vertex_buffer: &VertexBuffer,
+        program: &Program<V>,
+        vertices: &[V],
+    ) -> Result<(), Error> {
+        self.target.make_current();
+
+        self.render_vertices_with(Primitive::Triangles, vertex_buffer, program, vertices)
+    }
+
+    pub fn render_vertices_with<V: Vertex>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &VertexBuffer,
+        program: &Program<V>,
+        vertices: &[V],
+    ) -> Result<(), Error> {
+        self.target.make_current();
+
+        render_impl::Backend.render_vertices(
+            primitive,
+            &vertex_buffer.0,
+            &program.inner,
+            vertices,
+        )?;
+        Ok(())
+    }
+
+    /// Draws the triangles of `indices` into `vertices`, which can be `u16` or
+    /// `u32`. Debug builds return an error for indices past the end of
+    /// `vertices`, release builds leave it to the driver.
+    pub fn render_indexed<V: Vertex, I: Index>(
+        &self,
+        vertex_buffer: &VertexBuffer,
+        program: &Program<V>,
+        vertices: &[V],
+        indices: &[I],
+    ) -> Result<(), Error> {
+        self.target.make_current();
+
+        if cfg!(debug_assertions) {
+            check_indices(indices, vertices.len())?;
+        }
+
+        render_impl::Backend.render_indexed(
+            Primitive::Triangles,
+            &vertex_buffer.0,
+            &program.inner,
+            vertices,
+            indices,
+        )?;
+        Ok(())
+    }
+
+    /// Draws `vertices` once for every element of `instances`. The attributes of
+    /// `I` advance once per instance instead of once per vertex, so the program
+    /// has to declare the attributes of both `V` and `I`.
+    pub fn render_instanced<V: Vertex, I: Vertex>(
+        &self,
+        vertex_buffer: &VertexBuffer,
+        program: &Program<V>,
+        vertices: &[V],
+        instances: &[I],
+    ) -> Result<(), Error> {
+        self.target.make_current();
+
+        render_impl::render_instanced(
+            Primitive::Triangles,
+            &vertex_buffer.0,
+            &program.inner,
+            vertices,
+            instances,
+        )?;
+        Ok(())
+    }
+
+    /// Draws `count` of the vertices last uploaded to `vertex_buffer` starting at
+    /// `first`. Only the attributes of `V` are pointed at them, the program,
+    /// uniforms and other state are whatever is bound: that of the last `render_*`
+    /// call or of the caller's own GL calls. `program` has to be the bound
+    /// program, the attribute locations are taken from it.
+    pub fn draw_arrays_raw<V: Vertex>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &VertexBuffer,
+        program: &Program<V>,
+        first: u32,
+        count: u32,
+    ) -> Result<(), Error> {
+        self.target.make_current();
+
+        check_draw_range(
+            "vertices",
+            first,
+            count,
+            vertex_buffer.0.vertex_bytes() / V::stride().max(1),
+        )?;
+        render_impl::draw_arrays_raw::<V>(primitive, &vertex_buffer.0, &program.inner, first, count)
+    }
+
+    /// Like `draw_arrays_raw`, with `count` of the indices last uploaded to
+    /// `vertex_buffer` starting at `first`, read as `index_type`
+    pub fn draw_elements_raw<V: Vertex>(
+        &self,
+        primitive: Primitive,
+        vertex_buffer: &VertexBuffer,
+        program: &Program<V>,
+        index_type: IndexType,
+        first: u32,
+        count: u32,
+    ) -> Result<(), Error> {
+        self.target.make_current();
+
+        check_draw_range(
+            "indices",
+            first,
+            count,
+            vertex_buffer.0.index_bytes() / index_type.size(),
+        )?;
+        render_impl::draw_elements_raw::<V>(
+            primitive,
+            &vertex_buffer.0,
+            &program.inner,
+            index_type,
+            first,
+            count,
+        )
+    }
+
+    /// Forgets the program, vertex array and other state embla remembers to skip
+    /// redundant GL calls, needed after binding things with GL calls of your own
+    pub fn reset_state_cache(&self) {
+        self.target.make_current();
+
+        render_impl::reset_bound_state();
+    }
+
+    /// Sets the blend mode used by subsequent draw calls, windows start out with
+    /// `BlendMode::Alpha`
+    pub fn set_blend_mode(&self, mode: BlendMode) {
+        self.target.make_current();
+
+        render_impl::Backend.set_blend_mode(mode);
+    }
+
+    /// Encodes the colors written to the screen as sRGB, so that lighting and
+    /// blending can be done on linear values, e.g. sampled from `Srgba8`
+    /// textures. Alpha is written unchanged. Only available natively and on
+    /// GLES drivers only with `EXT_sRGB_write_control`, elsewhere an error is
+    /// returned. WebGL has no way to turn the conversion on for the default
+    /// framebuffer.
+    pub fn set_framebuffer_srgb(&self, enabled: bool) -> Result<(), Error> {
+        self.target.make_current();
+
+        render_impl::set_framebuffer_srgb(enabled)
+    }
+
+    /// Width in pixels of `Lines` and `LineStrip` primitives, widths outside of
+    /// `line_width_range` are an error. The range is often just 1.0.
+    pub fn set_line_width(&self, width: f32) -> Result<(), Error> {
+        self.target.make_current();
+
+        render_impl::set_line_width(width)
+    }
+
+    /// Smallest and largest supported line width
+    pub fn line_width_range(&self) -> (f32, f32) {
+        self.target.make_current();
+
+        render_impl::line_width_range()
+    }
+
+    /// Smallest and largest supported point size. GLES and WebGL have no
+    /// `glPointSize`, the vertex shader writes the size of `Points` primitives to
+    /// `gl_PointSize` instead
+    pub fn point_size_range(&self) -> (f32, f32) {
+        self.target.make_current();
+
+        render_impl::point_size_range()
+    }
+
+    /// Restricts drawing to the pixels whose stencil value passes
+    /// `set_stencil_func`. Screens and framebuffers have an 8 bit stencil buffer
+    pub fn set_stencil_test(&self, enabled: bool) {
+        self.target.make_current();
+
+        render_impl::set_stencil_test(enabled);
+    }
+
+    /// A pixel passes when `reference & mask` compares with `func` against the
+    /// stored value masked the same way
+    pub fn set_stencil_func(&self, func: StencilFunc, reference: u8, mask: u8) {
+        self.target.make_current();
+
+        render_impl::set_stencil_func(func, reference, mask);
+    }
+
+    /// How the stored value changes when the stencil test fails, when it
+    /// passes but the depth test fails, and when both pass
+    pub fn set_stencil_op(&self, fail: StencilOp, depth_fail: StencilOp, pass: StencilOp) {
+        self.target.make_current();
+
+        render_impl::set_stencil_op(fail, depth_fail, pass);
+    }
+
+    /// Chooses which stencil bits draws and `clear_stencil` write to, all of them
+    /// by default
+    pub fn set_stencil_write_mask(&self, mask: u8) {
+        self.target.make_current();
+
+        render_impl::set_stencil_write_mask(mask);
+    }
+
+    /// Turns off writing color, so that a mask can be drawn into the stencil
+    /// buffer alone, see `set_color_mask`
+    pub fn set_color_write(&self, enabled: bool) {
+        self.set_color_mask(enabled, enabled, enabled, enabled);
+    }
+
+    /// Chooses which color channels draws write to, masking all of them off makes
+    /// a cheap depth pre-pass or a stencil only pass. The mask also applies to
+    /// `clear`, which leaves masked channels untouched
+    pub fn set_color_mask(&self, red: bool, green: bool, blue: bool, alpha: bool) {
+        self.target.make_current();
+
+        render_impl::set_color_mask(red, green, blue, alpha);
+    }
+
+    pub fn set_depth_test(&self, enabled: bool) {
+        self.target.make_current();
+
+        render_impl::set_depth_test(enabled);
+    }
+
+    /// Restricts subsequent clears and draw calls to the `(x, y, width, height)`
+    /// rectangle, with `(x, y)` being its top left corner relative to the
+    /// viewport like in `read_pixels`. `None` turns clipping off again.
+    pub fn set_scissor(&self, rect: Option<(i32, i32, i32, i32)>) {
+        self.target.make_current();
+
+        let viewport = render_impl::Backend.viewport();
+        render_impl::set_scissor(rect.map(|rect| to_target_rect(rect, viewport)));
+    }
+
+    /// Reads back a region of the bound framebuffer. `(x, y)` is the top left
+    /// corner of the region relative to the viewport, and the returned image is
+    /// flipped so that its first row is the top row like any other `Image`.
+    /// Regions reaching outside the viewport are an error.
+    pub fn read_pixels(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Image, Error> {
+        self.target.make_current();
+
+        let viewport = render_impl::Backend.viewport();
+        let (_, _, viewport_width, viewport_height) = viewport;
+        let right = x.checked_add(width);
+        let bottom = y.checked_add(height);
+        if right.map_or(true, |right| right > viewport_width)
+            || bottom.map_or(true, |bottom| bottom > viewport_height)
+        {
+            return Err(format_err!(
+                "can't read {}x{} pixels at ({}, {}) from a {}x{} viewport",
+                width,
+                height,
+                x,
+                y,
+                viewport_width,
+                viewport_height
+            ));
+        }
+        let (gl_x, gl_y, _, _) =
+            to_target_rect((x as i32, y as i32, width as i32, height as i32), viewport);
+        let data = render_impl::Backend.read_pixels(gl_x, gl_y, width, height)?;
+
+        Ok(Image {
+            data,
+            width,
+            height,
+        }
+        .flipped_vertical())
+    }
+
+    /// Clears the color buffer to `color`, or black when `None`
+    pub fn clear(&self, color: Option<(f32, f32, f32, f32)>) {
+        self.clear_buffers(&ClearOptions {
+            color: Some(color.unwrap_or((0.0, 0.0, 0.0, 1.0))),
+            ..ClearOptions::default()
+        });
+    }
+
+    /// Sets every stencil value of the bound target to `value`
+    pub fn clear_stencil(&self, value: u8) {
+        self.clear_buffers(&ClearOptions {
+            stencil: Some(value),
+            ..ClearOptions::default()
+        });
+    }
+
+    /// Clears the color, depth and stencil buffers, depth values become 1.0 and
+    /// stencil values 0
+    pub fn clear_all(&self, color: Option<(f32, f32, f32, f32)>) {
+        self.clear_buffers(&ClearOptions {
+            color: Some(color.unwrap_or((0.0, 0.0, 0.0, 1.0))),
+            depth: Some(1.0),
+            stencil: Some(0),
+        });
+    }
+
+    /// Clears only the buffers `options` has a value for, in a single clear. The
+    /// color clear respects `set_color_mask`
+    pub fn clear_buffers(&self, options: &ClearOptions) {
+        self.target.make_current();
+
+        render_impl::Backend.clear(options.color, options.depth, options.stencil);
+    }
+}
+
+// Converts a rectangle with a top left origin relative to `viewport` to pixels of
+// the target with a bottom left origin, which is what GL takes
+fn to_target_rect(
+    rect: (i32, i32, i32, i32),
+    viewport: (i32, i32, u32, u32),
+) -> (i32, i32, i32, i32) {
+    let (x, y, w, h) = rect;
+    let (viewport_x, viewport_y, _, viewport_height) = viewport;
+    (
+        viewport_x + x,
+        viewport_y + viewport_height as i32 - (y + h),
+        w,
+        h,
+    )
+}
+
+// The inverse of `to_target_rect`
+fn from_target_rect(
+    rect: (i32, i32, i32, i32),
+    viewport: (i32, i32, u32, u32),
+) -> (i32, i32, i32, i32) {
+    let (x, y, w, h) = rect;
+    let (viewport_x, viewport_y, _, viewport_height) = viewport;
+    (
+        x - viewport_x,
+        viewport_y + viewport_height as i32 - (y + h),
+        w,
+        h,
+    )
+}
+
+// Whether `line` is a `precision <qualifier> float;` statement, a default
+// precision for ints leaves floats without one
+fn is_float_precision(line: &str) -> bool {
+    let mut words = line
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|word| !word.is_empty());
+    match (words.next(), words.next(), words.next()) {
+        (Some("precision"), Some(qualifier), Some("float")) => {
+            qualifier == "lowp" || qualifier == "mediump" || qualifier == "highp"
+        }
+        _ => false,
+    }
+}
+
+fn check_region_bounds(image: &Image, offset: (u32, u32), size: (u32, u32)) -> Result<(), Error> {
+    let right = offset.0.checked_add(image.width);
+    let bottom = offset.1.checked_add(image.height);
+    if right.map_or(true, |right| right > size.0) || bottom.map_or(true, |bottom| bottom > size.1) {
+        return Err(RenderError::RegionOutOfBounds {
+            offset,
+            region: (image.width, image.height),
+            texture: size,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+// Errors on the first index that points past the vertices, drawing it reads
+// whatever follows them in the buffer or nothing at all depending on the driver
+fn check_indices<I: Index>(indices: &[I], vertex_count: usize) -> Result<(), Error> {
+    match indices
+        .iter()
+        .position(|index| index.to_u32() as usize >= vertex_count)
+    {
+        Some(position) => Err(format_err!(
+            "index {} at position {} is out of bounds for {} vertices",
+            indices[position].to_u32(),
+            position,
+            vertex_count
+        )),
+        None => Ok(()),
+    }
+}
+
+// the raw draws take ranges of what the last render_* call uploaded
+fn check_draw_range(what: &str, first: u32, count: u32, available: usize) -> Result<(), Error> {
+    match first.checked_add(count) {
+        Some(end) if end as usize <= available => Ok(()),
+        _ => Err(format_err!(
+            "{} {} to {} are out of bounds for {} {}",
+            what,
+            first,
+            first as u64 + count as u64,
+            available,
+            what
+        )),
+    }
+}
+
+fn check_uncompressed(texture: &Texture) -> Result<(), Error> {
+    if let Some(format) = texture.compressed_format() {
+        return Err(format_err!(
+            "{:?} compressed texture can't be updated with images",
+            format
+        ));
+    }
+    Ok(())
+}
+
+fn check_compressed_levels(
+    format: CompressedFormat,
+    size: (u32, u32),
+    levels: &[&[u8]],
+    filtering: Option<TextureFiltering>,
+) -> Result<(), Error> {
+    if levels.is_empty() {
+        return Err(format_err!("compressed texture needs at least one level"));
+    }
+    if let Some(TextureFiltering::Trilinear) = filtering {
+        if levels.len() == 1 {
+            return Err(format_err!(
+                "compressed texture has no mipmaps for trilinear filtering"
+            ));
+        }
+    }
+    for (level, data) in levels.iter().enumerate() {
+        let level_size = mip_size(size, level);
+        let expected = format.data_size(level_size).ok_or_else(|| {
+            format_err!("a {}x{} {:?} texture is too large", size.0, size.1, format)
+        })?;
+        if data.len() != expected {
+            return Err(format_err!(
+                "{} bytes of data do not match level {} of a {}x{} {:?} texture, which is {} bytes",
+                data.len(),
+                level,
+                size.0,
+                size.1,
+                format,
+                expected
+            ));
+        }
+        if level > 0 && level_size == (1, 1) && level + 1 < levels.len() {
+            return Err(format_err!("compressed texture has levels below 1x1"));
+        }
+    }
+    Ok(())
+}
+
+/// Size of mip `level` of a `size` texture, levels halve in size down to 1x1
+pub fn mip_size(size: (u32, u32), level: usize) -> (u32, u32) {
+    ((size.0 >> level).max(1), (size.1 >> level).max(1))
+}
+
+fn check_texture_size(size: (u32, u32)) -> Result<(), RenderError> {
+    check_limit(
+        "texture size",
+        size.0.max(size.1),
+        render_impl::max_texture_size(),
+    )
+}
+
+// bindings are counted from 0, binding n needs n + 1 of them
+fn check_uniform_binding(binding: u32) -> Result<(), RenderError> {
+    check_limit(
+        "uniform buffer bindings",
+        binding.saturating_add(1),
+        render_impl::max_uniform_buffer_bindings(),
+    )
+}
+
+fn check_image_dimensions(image: &Image, size: (u32, u32)) -> Result<(), Error> {
+    if (image.width, image.height) != size {
+        return Err(format_err!(
+            "{}x{} image does not match the {}x{} texture",
+            image.width,
+            image.height,
+            size.0,
+            size.1
+        ));
+    }
+    Ok(())
+}
+
+fn check_image_size(image: &Image, format: TextureFormat) -> Result<(), Error> {
+    let expected = image.width * image.height * format.bytes_per_pixel();
+    if image.data.len() != expected as usize {
+        return Err(format_err!(
+            "image data of {} bytes does not match a {}x{} {:?} image",
+            image.data.len(),
+            image.width,
+            image.height,
+            format
+        ));
+    }
+    Ok(())
+}
+
+// The codes are the same in GL and WebGL
+fn gl_error_name(code: u32) -> String {
+    match code {
+        0x0500 => "INVALID_ENUM".into(),
+        0x0501 => "INVALID_VALUE".into(),
+        0x0502 => "INVALID_OPERATION".into(),
+        0x0503 => "STACK_OVERFLOW".into(),
+        0x0504 => "STACK_UNDERFLOW".into(),
+        0x0505 => "OUT_OF_MEMORY".into(),
+        0x0506 => "INVALID_FRAMEBUFFER_OPERATION".into(),
+        0x9242 => "CONTEXT_LOST_WEBGL".into(),
+        e => format!("unknown error 0x{:04X}", e),
+    }
+}
+
+#[test]
+fn vertex_stride() {
+    struct Packed;
+    impl Vertex for Packed {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![
+                ("position".into(), 2, VertexAttributeType::Float),
+                (
+                    "color".into(),
+                    4,
+                    VertexAttributeType::UnsignedByteNormalized,
+                ),
+                ("normal".into(), 2, VertexAttributeType::ShortNormalized),
+            ]
+        }
+    }
+    assert_eq!(Packed::stride(), 16);
+}
+
+#[test]
+fn derive_vertex() {
+    #[derive(Vertex)]
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct Derived {
+        #[attribute(name = "position", ty = Float)]
+        position: [f32; 2],
+        #[attribute(name = "color", ty = UnsignedByteNormalized)]
+        color: [u8; 4],
+        #[attribute(name = "layer", ty = Float, count = 1)]
+        layer: f32,
+    }
+    struct Manual;
+    impl Vertex for Manual {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![
+                ("position".into(), 2, VertexAttributeType::Float),
+                (
+                    "color".into(),
+                    4,
+                    VertexAttributeType::UnsignedByteNormalized,
+                ),
+                ("layer".into(), 1, VertexAttributeType::Float),
+            ]
+        }
+    }
+
+    assert_eq!(Derived::attributes(), Manual::attributes());
+    assert_eq!(Derived::stride(), Manual::stride());
+}
+
+#[test]
+#[should_panic(expected = "not tightly packed")]
+fn derive_vertex_padding() {
+    #[derive(Vertex)]
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct Padded {
+        #[attribute(name = "flag", ty = UnsignedByteNormalized)]
+        flag: u8,
+        #[attribute(name = "position", ty = Float)]
+        position: [f32; 2],
+    }
+    Padded::attributes();
+}
+
+#[test]
+fn texture_update_size() {
+    let image = |width, height| Image {
+        data: vec![0; (width * height * 4) as usize],
+        width,
+        height,
+    };
+    assert!(check_image_dimensions(&image(4, 2), (4, 2)).is_ok());
+    assert!(check_image_dimensions(&image(2, 4), (4, 2)).is_err());
+    assert!(check_image_dimensions(&image(4, 4), (4, 2)).is_err());
+
+    assert!(check_region_bounds(&image(2, 2), (2, 0), (4, 2)).is_ok());
+    assert!(check_region_bounds(&image(2, 2), (3, 0), (4, 2)).is_err());
+    assert!(check_region_bounds(&image(2, 2), (0, 1), (4, 2)).is_err());
+    assert!(check_region_bounds(&image(2, 2), (u32::max_value(), 0), (4, 2)).is_err());
+    assert!(check_region_bounds(&image(2, 2), (0, u32::max_value() - 1), (4, 2)).is_err());
+}
+
+#[test]
+fn compressed_data_sizes() {
+    assert_eq!(CompressedFormat::Dxt1.data_size((4, 4)), Some(8));
+    assert_eq!(CompressedFormat::Dxt5.data_size((8, 4)), Some(32));
+    // partial blocks take up a whole block
+    assert_eq!(CompressedFormat::Etc2Rgb8.data_size((5, 1)), Some(16));
+    // u32 sizes are not clamped, the block count alone overflows a u32
+    let max = u32::max_value();
+    assert_eq!(
+        CompressedFormat::Dxt1
+            .data_size((max, max))
+            .map(|size| size as u64),
+        if cfg!(target_pointer_width = "64") {
+            Some(0x4000_0000 * 0x4000_0000 * 8)
+        } else {
+            None
+        }
+    );
+    assert_eq!(CompressedFormat::Dxt5.data_size((max, max)), None);
+
+    // a single white DXT1 block
+    let block = [0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0];
+    let format = CompressedFormat::Dxt1;
+    assert!(check_compressed_levels(format, (4, 4), &[&block], None).is_ok());
+    assert!(check_compressed_levels(format, (8, 4), &[&block], None).is_err());
+    let trilinear = Some(TextureFiltering::Trilinear);
+    assert!(check_compressed_levels(format, (4, 4), &[&block], trilinear).is_err());
+
+    // 8x8, 4x4, 2x2 and 1x1 levels each fit in one block after the first
+    let mips = [&[0; 32][..], &block, &block, &block];
+    assert!(check_compressed_levels(format, (8, 8), &mips, trilinear).is_ok());
+    assert!(check_compressed_levels(format, (8, 8), &[&block, &block], None).is_err());
+    let below_1x1 = [&[0; 32][..], &block, &block, &block, &block];
+    assert!(check_compressed_levels(format, (8, 8), &below_1x1, None).is_err());
+    assert_eq!(mip_size((8, 2), 2), (2, 1));
+}
+
+#[test]
+fn render_error_messages() {
+    let err: Error = RenderError::ShaderCompile {
+        label: "sprite".into(),
+        stage: ShaderStage::Fragment,
+        log: "0:2: syntax error".into(),
+        source: "void main()\n{".into(),
+    }
+    .into();
+    assert_eq!(
+        err.to_string(),
+        "Error compiling fragment shader of sprite: 0:2: syntax error\n   1 | void main()\n   2 | {\n"
+    );
+
+    let err = RenderError::GlError {
+        context: "render_vertices".into(),
+        codes: vec![0x0502, 0x0505, 0x1234],
+    };
+    assert_eq!(
+        err.to_string(),
+        "GL error in render_vertices: INVALID_OPERATION, OUT_OF_MEMORY, unknown error 0x1234"
+    );
+}
+
+#[test]
+fn flipped_rects() {
+    let screen = (0, 0, 640, 480);
+    assert_eq!(to_target_rect((10, 0, 20, 30), screen), (10, 450, 20, 30));
+    // the right half of a split screen
+    let view = (320, 0, 320, 480);
+    assert_eq!(to_target_rect((10, 0, 20, 30), view), (330, 450, 20, 30));
+    let view = (5, 40, 100, 50);
+    assert_eq!(to_target_rect((0, 0, 100, 50), view), (5, 40, 100, 50));
+    assert_eq!(
+        from_target_rect(to_target_rect((5, 7, 11, 13), view), view),
+        (5, 7, 11, 13)
+    );
+}
+
+#[test]
+fn index_types() {
+    assert_eq!(u16::index_type(), IndexType::U16);
+    assert_eq!(u32::index_type(), IndexType::U32);
+    assert_eq!(IndexType::U16.size(), ::std::mem::size_of::<u16>());
+    assert_eq!(IndexType::U32.size(), ::std::mem::size_of::<u32>());
+}
+
+#[test]
+fn index_bounds() {
+    assert!(check_indices(&[0u16, 1, 2, 2, 3, 0], 4).is_ok());
+    assert!(check_indices::<u32>(&[], 0).is_ok());
+    assert!(check_indices(&[0u16, 1, 4], 4).is_err());
+    assert!(check_indices(&[0u32], 0).is_err());
+    assert!(check_indices(&[u32::max_value()], 4).is_err());
+}
+
+#[test]
+fn draw_ranges() {
+    assert!(check_draw_range("vertices", 0, 6, 6).is_ok());
+    assert!(check_draw_range("vertices", 6, 0, 6).is_ok());
+    assert!(check_draw_range("vertices", 3, 4, 6).is_err());
+    assert!(check_draw_range("indices", 0, 1, 0).is_err());
+    assert!(check_draw_range("indices", u32::max_value(), 2, 6).is_err());
+}
+
+#[test]
+fn shader_preprocessing() {
+    let fs = "uniform vec4 color;\nvoid main()\n{\n    gl_FragColor = color;\n}\n";
+
+    let gles = ShaderOptions::default().preprocess(fs, ShaderStage::Fragment);
+    assert!(gles.starts_with("#version 100\nprecision mediump float;\nuniform vec4 color;\n"));
+    let vertex = ShaderOptions::default().preprocess("void main() {}", ShaderStage::Vertex);
+    assert_eq!(vertex, "#version 100\nvoid main() {}\n");
+
+    let desktop = ShaderOptions {
+        profile: ShaderProfile::Desktop,
+        default_precision: true,
+    };
+    assert!(desktop
+        .preprocess(fs, ShaderStage::Fragment)
+        .starts_with("#version 330 core\nuniform vec4 color;\n"));
+
+    // precision goes after the directives, existing headers are left alone
+    let fs = "#version 300 es\n#extension GL_OES_standard_derivatives : enable\nvoid main() {}";
+    assert_eq!(
+        ShaderOptions::default().preprocess(fs, ShaderStage::Fragment),
+        "#version 300 es\n#extension GL_OES_standard_derivatives : enable\n\
+         precision mediump float;\nvoid main() {}\n"
+    );
+    let fs = "#version 100\nprecision highp float;\nvoid main() {}";
+    assert_eq!(
+        ShaderOptions::default().preprocess(fs, ShaderStage::Fragment),
+        fs
+    );
+    let fs = "#version 100\n  precision  lowp float ;\nvoid main() {}";
+    assert_eq!(
+        ShaderOptions::default().preprocess(fs, ShaderStage::Fragment),
+        fs
+    );
+    // only ints get a default precision
+    let fs = "#version 300 es\nprecision highp int;\nvoid main() {}";
+    assert_eq!(
+        ShaderOptions::default().preprocess(fs, ShaderStage::Fragment),
+        "#version 300 es\nprecision mediump float;\nprecision highp int;\nvoid main() {}\n"
+    );
+}
+
+#[test]
+fn viewport_splits() {
+    let screen = Viewport::new(0, 0, 801, 600);
+    assert_eq!(
+        screen.split_columns(2),
+        vec![
+            Viewport::new(0, 0, 400, 600),
+            Viewport::new(400, 0, 401, 600)
+        ]
+    );
+    // rows go from top to bottom while y points up
+    assert_eq!(
+        Viewport::new(10, 20, 100, 101).split_rows(2),
+        vec![
+            Viewport::new(10, 71, 100, 50),
+            Viewport::new(10, 20, 100, 51)
+        ]
+    );
+    assert_eq!(screen.split_columns(0), vec![screen]);
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn texture_read_back() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let mut image = Image::solid(4, 2, [255, 0, 0, 255]);
+        image.data[0..4].copy_from_slice(&[0, 0, 255, 128]);
+        let texture = renderer
+            .create_texture_from_image(&image, None, None)
+            .unwrap();
+        assert_eq!(texture.read_pixels().unwrap().data, image.data);
+
+        renderer.clear(Some((0.0, 1.0, 0.0, 1.0)));
+        let pixels = renderer.read_pixels(0, 0, 2, 2).unwrap();
+        assert_eq!(&pixels.data[0..4], &[0, 255, 0, 255]);
+    });
+}
+
+// exists for its vertex layout, most tests only hand the fields to the GPU
+#[cfg(test)]
+#[allow(dead_code)]
+struct Position(f32, f32);
+
+#[cfg(test)]
+impl Vertex for Position {
+    fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+        vec![("position".to_string(), 2, VertexAttributeType::Float)]
+    }
+}
+
+// two triangles covering the full height of the screen from `left` to `right`
+#[cfg(test)]
+fn quad(left: f32, right: f32) -> Vec<Position> {
+    vec![
+        Position(left, -1.0),
+        Position(right, -1.0),
+        Position(right, 1.0),
+        Position(left, -1.0),
+        Position(right, 1.0),
+        Position(left, 1.0),
+    ]
+}
+
+// draws every fragment in `color`
+#[cfg(test)]
+fn color_program(renderer: &Renderer, color: (f32, f32, f32, f32)) -> Program<Position> {
+    let vs = "attribute vec2 position;\n\
+              void main() { gl_Position = vec4(position, 0.0, 1.0); }";
+    let fs = "uniform vec4 color;\nvoid main() { gl_FragColor = color; }";
+    let mut program = renderer.create_program(vs, fs).unwrap();
+    program.set_uniform("color", Uniform::Vec4(color));
+    program
+}
+
+#[cfg(test)]
+fn assert_color(renderer: &Renderer, rect: (u32, u32, u32, u32), rgba: [u8; 4]) {
+    let (x, y, width, height) = rect;
+    let pixels = renderer.read_pixels(x, y, width, height).unwrap();
+    assert_eq!(
+        pixels.data,
+        Image::solid(width, height, rgba).data,
+        "region {:?}",
+        rect
+    );
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn framebuffer_read_back() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let framebuffer = renderer.create_framebuffer((16, 8), None).unwrap();
+        let program = color_program(renderer, (1.0, 0.0, 0.0, 1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        renderer.clear(Some((0.0, 1.0, 0.0, 1.0)));
+
+        renderer.set_viewport(32, 0, 32, 64);
+        renderer.bind_framebuffer(Some(&framebuffer));
+        assert_eq!(renderer.viewport(), Viewport::new(0, 0, 16, 8));
+        renderer.clear(Some((0.0, 0.0, 1.0, 1.0)));
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        renderer.bind_framebuffer(None);
+
+        assert_eq!(renderer.viewport(), Viewport::new(32, 0, 32, 64));
+        assert_eq!(
+            framebuffer.texture().read_pixels().unwrap().data,
+            Image::solid(16, 8, [255, 0, 0, 255]).data
+        );
+        assert_color(renderer, (0, 0, 32, 64), [0, 255, 0, 255]);
+
+        // a context switch forgets the saved viewport, the whole window is used
+        renderer.bind_framebuffer(Some(&framebuffer));
+        renderer.reset_state_cache();
+        renderer.bind_framebuffer(None);
+        assert_eq!(renderer.viewport(), Viewport::new(0, 0, 64, 64));
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn viewport_read_back() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        renderer.clear(Some((1.0, 0.0, 0.0, 1.0)));
+        // the bottom right quadrant of the 64x64 window
+        render_impl::set_scissor(Some((32, 0, 32, 32)));
+        renderer.clear(Some((0.0, 1.0, 0.0, 1.0)));
+        render_impl::set_scissor(None);
+
+        renderer.set_viewport(32, 0, 32, 32);
+        assert_color(renderer, (0, 0, 32, 32), [0, 255, 0, 255]);
+        assert!(renderer.read_pixels(0, 0, 33, 1).is_err());
+        assert!(renderer.read_pixels(1, 31, 1, 2).is_err());
+        assert!(renderer.read_pixels(u32::max_value(), 0, 2, 1).is_err());
+
+        renderer.set_viewport(0, 0, 64, 64);
+        assert_color(renderer, (32, 32, 32, 32), [0, 255, 0, 255]);
+        assert_color(renderer, (0, 0, 64, 32), [255, 0, 0, 255]);
+        assert_color(renderer, (0, 32, 32, 32), [255, 0, 0, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn scissor_in_viewport() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        renderer.clear(Some((1.0, 0.0, 0.0, 1.0)));
+        renderer.set_viewport(32, 0, 32, 32);
+        renderer.set_scissor(Some((0, 0, 16, 16)));
+        assert_eq!(RenderState::capture(renderer).scissor, Some((0, 0, 16, 16)));
+        renderer.clear(Some((0.0, 1.0, 0.0, 1.0)));
+        renderer.set_scissor(None);
+
+        renderer.set_viewport(0, 0, 64, 64);
+        assert_color(renderer, (32, 32, 16, 16), [0, 255, 0, 255]);
+        assert_color(renderer, (48, 32, 16, 32), [255, 0, 0, 255]);
+        assert_color(renderer, (32, 48, 16, 16), [255, 0, 0, 255]);
+        assert_color(renderer, (0, 0, 32, 64), [255, 0, 0, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn render_view_clips() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        renderer.clear(Some((1.0, 0.0, 0.0, 1.0)));
+        let views = renderer.viewport().split_columns(2);
+        renderer
+            .render_view(views[1], |renderer| {
+                assert_eq!(renderer.screen_size(), (32, 64));
+                renderer.clear(Some((0.0, 1.0, 0.0, 1.0)));
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(renderer.viewport(), Viewport::new(0, 0, 64, 64));
+        assert_eq!(RenderState::capture(renderer).scissor, None);
+        assert_color(renderer, (32, 0, 32, 64), [0, 255, 0, 255]);
+        assert_color(renderer, (0, 0, 32, 64), [255, 0, 0, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn raw_draw_parity() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let vs = "attribute vec2 position;\n\
+                  void main() { gl_Position = vec4(position, 0.0, 1.0); }";
+        let fs = "uniform vec4 color;\nvoid main() { gl_FragColor = color; }";
+        let mut program = renderer.create_program::<Position>(vs, fs).unwrap();
+        program.set_uniform("color", Uniform::Vec4((0.0, 1.0, 0.0, 1.0)));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        let mut vertices = quad(-1.0, 0.0);
+        vertices.extend(quad(0.0, 1.0));
+
+        renderer.clear(Some((1.0, 0.0, 0.0, 1.0)));
+        renderer
+            .render_vertices(&buffer, &program, &quad(0.0, 1.0))
+            .unwrap();
+        assert_color(renderer, (32, 0, 32, 64), [0, 255, 0, 255]);
+        let expected = renderer.read_pixels(0, 0, 64, 64).unwrap();
+
+        renderer
+            .render_vertices(&buffer, &program, &vertices)
+            .unwrap();
+        renderer.clear(Some((1.0, 0.0, 0.0, 1.0)));
+        renderer
+            .draw_arrays_raw(Primitive::Triangles, &buffer, &program, 6, 6)
+            .unwrap();
+        assert_eq!(
+            renderer.read_pixels(0, 0, 64, 64).unwrap().data,
+            expected.data
+        );
+
+        let indices: Vec<u16> = (0..12).collect();
+        renderer
+            .render_indexed(&buffer, &program, &vertices, &indices)
+            .unwrap();
+        renderer.clear(Some((1.0, 0.0, 0.0, 1.0)));
+        renderer
+            .draw_elements_raw(
+                Primitive::Triangles,
+                &buffer,
+                &program,
+                IndexType::U16,
+                6,
+                6,
+            )
+            .unwrap();
+        assert_eq!(
+            renderer.read_pixels(0, 0, 64, 64).unwrap().data,
+            expected.data
+        );
+
+        assert!(renderer
+            .draw_arrays_raw(Primitive::Triangles, &buffer, &program, 6, 7)
+            .is_err());
+        assert!(renderer
+            .draw_elements_raw(
+                Primitive::Triangles,
+                &buffer,
+                &program,
+                IndexType::U32,
+                0,
+                12,
+            )
+            .is_err());
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn shader_compile_error() {
+    use platform::with_test_renderer;
+
+    struct NoAttributes;
+    impl Vertex for NoAttributes {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            Vec::new()
+        }
+    }
+
+    with_test_renderer(|renderer| {
+        let vs = "void main() { gl_Position = vec4(0.0); }";
+        let fs = "void main() { gl_FragColor = undeclared; }";
+        match renderer.create_labeled_program::<NoAttributes>("broken", vs, fs) {
+            Err(RenderError::ShaderCompile {
+                label,
+                stage: ShaderStage::Fragment,
+                source,
+                ..
+            }) => {
+                assert_eq!(label, "broken");
+                assert!(source.contains("undeclared"));
+            }
+            Err(e) => panic!("expected a fragment shader compile error, got {}", e),
+            Ok(_) => panic!("expected a fragment shader compile error"),
+        }
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn dxt1_sampling() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let format = CompressedFormat::Dxt1;
+        if !renderer.compressed_format_supported(format) {
+            return;
+        }
+        // every texel is the first color, pure red in RGB565
+        let block = [0x00, 0xF8, 0x00, 0x00, 0, 0, 0, 0];
+        let texture = renderer
+            .create_compressed_texture(
+                format,
+                (4, 4),
+                &block,
+                Some(TextureFiltering::Nearest),
+                None,
+            )
+            .unwrap();
+
+        let vs = "attribute vec2 position;\nvarying vec2 uv;\n\
+                  void main() {\n\
+                  uv = position * 0.5 + 0.5;\n\
+                  gl_Position = vec4(position, 0.0, 1.0);\n\
+                  }";
+        let fs = "uniform sampler2D texture;\nvarying vec2 uv;\n\
+                  void main() { gl_FragColor = texture2D(texture, uv); }";
+        let mut program = renderer.create_program::<Position>(vs, fs).unwrap();
+        program.set_uniform("texture", Uniform::Texture(texture));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+
+        renderer.clear(Some((0.0, 0.0, 1.0, 1.0)));
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        assert_color(renderer, (0, 0, 64, 64), [255, 0, 0, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn render_state_round_trip() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let original = RenderState::capture(renderer);
+        assert_eq!(original.viewport, (0, 0, 64, 64));
+
+        renderer.set_blend_mode(BlendMode::Additive);
+        renderer.set_depth_test(true);
+        renderer.set_stencil_test(true);
+        renderer.set_viewport(8, 16, 24, 40);
+        renderer.set_scissor(Some((2, 3, 10, 12)));
+        renderer.set_color_mask(true, false, true, false);
+        let changed = RenderState::capture(renderer);
+        assert_eq!(
+            changed,
+            RenderState {
+                blend_mode: Some(BlendMode::Additive),
+                depth_test: true,
+                stencil_test: true,
+                viewport: (8, 16, 24, 40),
+                scissor: Some((2, 3, 10, 12)),
+                color_mask: (true, false, true, false),
+            }
+        );
+
+        original.apply(renderer);
+        assert_eq!(RenderState::capture(renderer), original);
+        changed.apply(renderer);
+        assert_eq!(RenderState::capture(renderer), changed);
+        original.apply(renderer);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn viewport_screen_size() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        assert_eq!(renderer.screen_size(), (64, 64));
+        renderer.set_viewport(8, 16, 24, 40);
+        assert_eq!(renderer.screen_size(), (24, 40));
+        assert_eq!(renderer.viewport(), Viewport::new(8, 16, 24, 40));
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn framebuffer_srgb_support() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let capabilities = renderer.capabilities();
+        let supported = !capabilities.version.starts_with("OpenGL ES")
+            || capabilities
+                .extensions
+                .iter()
+                .any(|extension| extension == "GL_EXT_sRGB_write_control");
+        assert_eq!(renderer.set_framebuffer_srgb(true).is_ok(), supported);
+        assert_eq!(renderer.set_framebuffer_srgb(false).is_ok(), supported);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn line_width_validation() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let (min, max) = renderer.line_width_range();
+        assert!(renderer.set_line_width(min).is_ok());
+        assert!(renderer.set_line_width(max).is_ok());
+        assert!(renderer.set_line_width(max + 1.0).is_err());
+        assert!(renderer.set_line_width(0.0).is_err());
+        assert!(renderer.set_line_width(f32::NAN).is_err());
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn debug_callback_panics() {
+    use platform::with_test_renderer;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    with_test_renderer(|renderer| {
+        let messages = Rc::new(Cell::new(0));
+        let counted = Rc::clone(&messages);
+        let enabled = renderer.enable_debug_output_with(DebugSeverity::Notification, move |_| {
+            counted.set(counted.get() + 1);
+            panic!("debug callback");
+        });
+        if enabled.is_err() {
+            // no KHR_debug
+            return;
+        }
+        // an invalid enum, reported through the callback
+        unsafe {
+            ::gl::Enable(0xffff);
+            ::gl::Enable(0xffff);
+            ::gl::GetError();
+        }
+        // both reach the callback, the first panic didn't take it down
+        assert!(messages.get() >= 2);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn vec4_uniform_render() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let mut program = color_program(renderer, (1.0, 0.0, 1.0, 1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        assert_color(renderer, (0, 0, 64, 64), [255, 0, 255, 255]);
+
+        // a changed value reaches the next draw
+        program.set_uniform("color", Uniform::Vec4((0.0, 1.0, 0.0, 1.0)));
+        renderer
+            .render_vertices(&buffer, &program, &quad(0.0, 1.0))
+            .unwrap();
+        assert_color(renderer, (32, 0, 32, 64), [0, 255, 0, 255]);
+        assert_color(renderer, (0, 0, 32, 64), [255, 0, 255, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn additive_blending() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let program = color_program(renderer, (0.25, 0.25, 0.25, 1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        let mut vertices = quad(-1.0, 0.5);
+        vertices.extend(quad(-0.5, 1.0));
+
+        renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+        renderer.set_blend_mode(BlendMode::Additive);
+        renderer
+            .render_vertices(&buffer, &program, &vertices)
+            .unwrap();
+        renderer.set_blend_mode(BlendMode::None);
+
+        assert_color(renderer, (0, 0, 16, 64), [64, 64, 64, 255]);
+        assert_color(renderer, (48, 0, 16, 64), [64, 64, 64, 255]);
+        let overlap = renderer.read_pixels(16, 0, 32, 64).unwrap();
+        for pixel in overlap.data.chunks(4) {
+            assert!(
+                pixel[..3].iter().all(|&channel| channel > 64),
+                "{:?}",
+                pixel
+            );
+        }
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn depth_test_hides() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let vs = "attribute vec2 position;\nuniform float depth;\n\
+                  void main() { gl_Position = vec4(position, depth, 1.0); }";
+        let fs = "uniform vec4 color;\nvoid main() { gl_FragColor = color; }";
+        let mut front = renderer.create_program::<Position>(vs, fs).unwrap();
+        front.set_uniform("depth", Uniform::Float(-0.5));
+        front.set_uniform("color", Uniform::Vec4((0.0, 1.0, 0.0, 1.0)));
+        let mut back = renderer.create_program::<Position>(vs, fs).unwrap();
+        back.set_uniform("depth", Uniform::Float(0.5));
+        back.set_uniform("color", Uniform::Vec4((1.0, 0.0, 0.0, 1.0)));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        let triangle = vec![
+            Position(-1.0, -1.0),
+            Position(1.0, -1.0),
+            Position(-1.0, 1.0),
+        ];
+
+        renderer.clear_all(Some((0.0, 0.0, 1.0, 1.0)));
+        renderer.set_depth_test(true);
+        renderer
+            .render_vertices(&buffer, &front, &triangle)
+            .unwrap();
+        renderer.render_vertices(&buffer, &back, &triangle).unwrap();
+        renderer.set_depth_test(false);
+
+        let pixels = renderer.read_pixels(0, 0, 64, 64).unwrap();
+        assert!(pixels.data.chunks(4).any(|pixel| pixel == [0, 255, 0, 255]));
+        assert!(pixels.data.chunks(4).all(|pixel| pixel != [255, 0, 0, 255]));
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn missing_uniform_debug_message() {
+    use platform::with_test_renderer;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    with_test_renderer(|renderer| {
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let reported = Rc::clone(&messages);
+        let enabled = renderer.enable_debug_output_with(DebugSeverity::Low, move |message| {
+            reported.borrow_mut().push(message.clone());
+        });
+        if enabled.is_err() {
+            // no KHR_debug
+            return;
+        }
+        let mut program = color_program(renderer, (0.0, 1.0, 0.0, 1.0));
+        program.set_uniform("unused", Uniform::Float(1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        for _ in 0..2 {
+            renderer
+                .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+                .unwrap();
+        }
+        assert_color(renderer, (0, 0, 64, 64), [0, 255, 0, 255]);
+
+        let messages = messages.borrow();
+        let missing: Vec<_> = messages
+            .iter()
+            .filter(|message| message.message.contains("uniform unused"))
+            .collect();
+        assert_eq!(missing.len(), 1, "{:?}", *messages);
+        assert_eq!(missing[0].source, "application");
+        assert_eq!(missing[0].severity, DebugSeverity::Low);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn nul_uniform_name() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let mut program = color_program(renderer, (0.0, 1.0, 0.0, 1.0));
+        program.set_uniform("bad\0name", Uniform::Float(1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        let error = renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap_err();
+        assert!(error.to_string().contains("NUL"), "{}", error);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn r8_odd_width_read_back() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        // rows of 3 bytes are not 4 byte aligned
+        let image = Image {
+            data: vec![10, 20, 30, 40, 50, 60],
+            width: 3,
+            height: 2,
+        };
+        let texture = renderer
+            .create_texture((3, 2), TextureFormat::R8, None, None)
+            .unwrap();
+        texture.update(&image).unwrap();
+
+        let expected: Vec<u8> = image
+            .data
+            .iter()
+            .flat_map(|&red| vec![red, 0, 0, 255])
+            .collect();
+        assert_eq!(texture.read_pixels().unwrap().data, expected);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn stencil_masking() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        // the window may have no stencil buffer, framebuffers always do
+        let framebuffer = renderer.create_framebuffer((64, 64), None).unwrap();
+        let program = color_program(renderer, (0.0, 1.0, 0.0, 1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        renderer.bind_framebuffer(Some(&framebuffer));
+        renderer.clear_all(Some((1.0, 0.0, 0.0, 1.0)));
+
+        // fill the mask with the left half of the screen
+        renderer.set_stencil_test(true);
+        renderer.set_color_write(false);
+        renderer.set_stencil_func(StencilFunc::Always, 1, 0xFF);
+        renderer.set_stencil_op(StencilOp::Keep, StencilOp::Keep, StencilOp::Replace);
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 0.0))
+            .unwrap();
+        renderer.set_color_write(true);
+        assert_color(renderer, (0, 0, 64, 64), [255, 0, 0, 255]);
+
+        // a full screen quad only reaches the masked half
+        renderer.set_stencil_func(StencilFunc::Equal, 1, 0xFF);
+        renderer.set_stencil_op(StencilOp::Keep, StencilOp::Keep, StencilOp::Keep);
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        renderer.set_stencil_test(false);
+        assert_color(renderer, (0, 0, 32, 64), [0, 255, 0, 255]);
+        assert_color(renderer, (32, 0, 32, 64), [255, 0, 0, 255]);
+        renderer.bind_framebuffer(None);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn integer_attribute_indexing() {
+    use platform::with_test_renderer;
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Tile {
+        position: (f32, f32),
+        index: u32,
+    }
+    impl Vertex for Tile {
+        fn attributes() -> Vec<(String, usize, VertexAttributeType)> {
+            vec![
+                ("position".to_string(), 2, VertexAttributeType::Float),
+                ("index".to_string(), 1, VertexAttributeType::Unsigned),
+            ]
+        }
+    }
+
+    with_test_renderer(|renderer| {
+        let vs = "#version 300 es\nin vec2 position;\nin uint index;\nflat out uint tile;\n\
+                  void main() {\n\
+                  tile = index;\n\
+                  gl_Position = vec4(position, 0.0, 1.0);\n\
+                  }";
+        let fs = "#version 300 es\nuniform vec4 colors[3];\nflat in uint tile;\n\
+                  out vec4 color;\n\
+                  void main() { color = colors[tile]; }";
+        let mut program = renderer.create_program::<Tile>(vs, fs).unwrap();
+        program.set_uniform("colors[0]", Uniform::Vec4((1.0, 0.0, 0.0, 1.0)));
+        program.set_uniform("colors[1]", Uniform::Vec4((0.0, 1.0, 0.0, 1.0)));
+        program.set_uniform("colors[2]", Uniform::Vec4((0.0, 0.0, 1.0, 1.0)));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        // a float conversion would turn the indices into tiny denormals
+        let tiles: Vec<Tile> = quad(-1.0, 0.0)
+            .into_iter()
+            .map(|Position(x, y)| Tile {
+                position: (x, y),
+                index: 2,
+            })
+            .chain(quad(0.0, 1.0).into_iter().map(|Position(x, y)| Tile {
+                position: (x, y),
+                index: 1,
+            }))
+            .collect();
+
+        renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+        renderer.render_vertices(&buffer, &program, &tiles).unwrap();
+        assert_color(renderer, (0, 0, 32, 64), [0, 0, 255, 255]);
+        assert_color(renderer, (32, 0, 32, 64), [0, 255, 0, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn depth_only_clear() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let program = color_program(renderer, (1.0, 0.0, 0.0, 1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        renderer.clear(Some((0.0, 1.0, 0.0, 1.0)));
+        renderer.clear_buffers(&ClearOptions {
+            depth: Some(0.0),
+            ..ClearOptions::default()
+        });
+        assert_color(renderer, (0, 0, 64, 64), [0, 255, 0, 255]);
+
+        // the quad at depth 0.5 is behind the cleared depth of 0.0
+        renderer.set_depth_test(true);
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        assert_color(renderer, (0, 0, 64, 64), [0, 255, 0, 255]);
+
+        renderer.clear_buffers(&ClearOptions {
+            depth: Some(1.0),
+            ..ClearOptions::default()
+        });
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        renderer.set_depth_test(false);
+        assert_color(renderer, (0, 0, 64, 64), [255, 0, 0, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn texture_size() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let texture = renderer
+            .create_texture((64, 32), TextureFormat::Rgba8, None, None)
+            .unwrap();
+        assert_eq!(texture.size(), (64, 32));
+        let image = Image::solid(3, 5, [0, 0, 0, 255]);
+        let texture = renderer
+            .create_texture_from_image(&image, None, None)
+            .unwrap();
+        assert_eq!(texture.size(), (3, 5));
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn set_regions_read_back() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let texture = renderer
+            .create_texture_from_image(&Image::solid(8, 4, [0, 0, 0, 255]), None, None)
+            .unwrap();
+        let red = Image::solid(2, 2, [255, 0, 0, 255]);
+        let green = Image::solid(3, 1, [0, 255, 0, 255]);
+        texture
+            .set_regions(&[(red, (1, 1)), (green, (5, 3))])
+            .unwrap();
+
+        let pixels = texture.read_pixels().unwrap();
+        for y in 0..4 {
+            for x in 0..8 {
+                let expected = if (1..3).contains(&x) && (1..3).contains(&y) {
+                    [255, 0, 0, 255]
+                } else if x >= 5 && y == 3 {
+                    [0, 255, 0, 255]
+                } else {
+                    [0, 0, 0, 255]
+                };
+                let i = ((y * 8 + x) * 4) as usize;
+                assert_eq!(&pixels.data[i..i + 4], &expected, "texel {}, {}", x, y);
+            }
+        }
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn rgb8_odd_width_read_back() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        // rows of 9 bytes, with the default unpack alignment of 4 the second row
+        // would start 3 bytes late
+        let image = Image {
+            data: vec![
+                255, 0, 0, 0, 255, 0, 0, 0, 255, 10, 20, 30, 40, 50, 60, 70, 80, 90,
+            ],
+            width: 3,
+            height: 2,
+        };
+        let texture = renderer
+            .create_texture((3, 2), TextureFormat::Rgb8, None, None)
+            .unwrap();
+        texture.update(&image).unwrap();
+
+        let pixels = texture.read_pixels().unwrap();
+        for (row, (read, uploaded)) in pixels
+            .data
+            .chunks(3 * 4)
+            .zip(image.data.chunks(3 * 3))
+            .enumerate()
+        {
+            let expected: Vec<u8> = uploaded
+                .chunks(3)
+                .flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255])
+                .collect();
+            assert_eq!(read, &expected[..], "row {}", row);
+        }
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn uniform_buffer_render() {
+    use platform::with_test_renderer;
+
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    struct Tint {
+        color: (f32, f32, f32, f32),
+    }
+
+    with_test_renderer(|renderer| {
+        let vs = "#version 300 es\nin vec2 position;\n\
+                  void main() { gl_Position = vec4(position, 0.0, 1.0); }";
+        let fs = "#version 300 es\nprecision mediump float;\n\
+                  uniform Tint { vec4 tint; };\nout vec4 color;\n\
+                  void main() { color = tint; }";
+        let mut program = renderer.create_program::<Position>(vs, fs).unwrap();
+        program.set_uniform_block_binding("Tint", 1).unwrap();
+        let uniforms = renderer
+            .create_uniform_buffer(BufferUsage::Dynamic)
+            .unwrap();
+        uniforms.set_data(&Tint {
+            color: (1.0, 1.0, 0.0, 1.0),
+        });
+        uniforms.bind(1).unwrap();
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+
+        renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        assert_color(renderer, (0, 0, 64, 64), [255, 255, 0, 255]);
+
+        // new data reaches the next draw without touching the program
+        uniforms.set_data(&Tint {
+            color: (0.0, 0.0, 1.0, 1.0),
+        });
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        assert_color(renderer, (0, 0, 64, 64), [0, 0, 255, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn color_mask_render() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let program = color_program(renderer, (1.0, 1.0, 1.0, 1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+
+        renderer.set_color_mask(true, false, true, true);
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        renderer.set_color_mask(true, true, true, true);
+        assert_color(renderer, (0, 0, 64, 64), [255, 0, 255, 255]);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn multiple_attachments_render() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let framebuffer = renderer
+            .create_framebuffer_with_attachments(
+                (8, 8),
+                &[TextureFormat::Rgba8, TextureFormat::Rgba8],
+                None,
+            )
+            .unwrap();
+        let vs = "#version 300 es\nin vec2 position;\n\
+                  void main() { gl_Position = vec4(position, 0.0, 1.0); }";
+        let fs = "#version 300 es\nprecision mediump float;\n\
+                  layout(location = 0) out vec4 albedo;\n\
+                  layout(location = 1) out vec4 normal;\n\
+                  void main() {\n\
+                  albedo = vec4(1.0, 0.0, 0.0, 1.0);\n\
+                  normal = vec4(0.0, 0.0, 1.0, 1.0);\n\
+                  }";
+        let program = renderer.create_program::<Position>(vs, fs).unwrap();
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+
+        renderer.bind_framebuffer(Some(&framebuffer));
+        renderer
+            .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+            .unwrap();
+        renderer.bind_framebuffer(None);
+
+        let textures = framebuffer.textures();
+        assert_eq!(textures.len(), 2);
+        assert_eq!(
+            textures[0].read_pixels().unwrap().data,
+            Image::solid(8, 8, [255, 0, 0, 255]).data
+        );
+        assert_eq!(
+            textures[1].read_pixels().unwrap().data,
+            Image::solid(8, 8, [0, 0, 255, 255]).data
+        );
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn sampler_filtering_render() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        // a black and a white texel stretched across the screen
+        let mut image = Image::solid(2, 1, [255, 255, 255, 255]);
+        image.data[0..4].copy_from_slice(&[0, 0, 0, 255]);
+        let texture = renderer
+            .create_texture_from_image(&image, Some(TextureFiltering::Nearest), None)
+            .unwrap();
+        let vs = "attribute vec2 position;\nvarying vec2 uv;\n\
+                  void main() {\n\
+                  uv = position * 0.5 + 0.5;\n\
+                  gl_Position = vec4(position, 0.0, 1.0);\n\
+                  }";
+        let fs = "uniform sampler2D texture;\nvarying vec2 uv;\n\
+                  void main() { gl_FragColor = texture2D(texture, uv); }";
+        let mut program = renderer.create_program::<Position>(vs, fs).unwrap();
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+
+        let mut draw = |filtering| {
+            let sampler = renderer
+                .create_sampler(filtering, TextureWrap::ClampToEdge)
+                .unwrap();
+            program.set_uniform("texture", Uniform::SampledTexture(texture.clone(), sampler));
+            renderer.clear(Some((1.0, 0.0, 0.0, 1.0)));
+            renderer
+                .render_vertices(&buffer, &program, &quad(-1.0, 1.0))
+                .unwrap();
+            renderer.read_pixels(0, 0, 64, 1).unwrap()
+        };
+        let nearest = draw(TextureFiltering::Nearest);
+        let linear = draw(TextureFiltering::Linear);
+
+        // nearest keeps a hard edge in the middle, linear blends across it
+        assert_eq!(&nearest.data[28 * 4..28 * 4 + 4], &[0, 0, 0, 255]);
+        assert_eq!(&nearest.data[36 * 4..36 * 4 + 4], &[255, 255, 255, 255]);
+        let middle = linear.data[28 * 4];
+        assert!(middle > 0 && middle < 255, "linear texel {}", middle);
+        assert_ne!(nearest.data, linear.data);
+    });
+}
+
+#[test]
+#[ignore = "needs a GL context"]
+fn index_types_render() {
+    use platform::with_test_renderer;
+
+    with_test_renderer(|renderer| {
+        let program = color_program(renderer, (0.0, 1.0, 0.0, 1.0));
+        let buffer = renderer.create_vertex_buffer(BufferUsage::Static).unwrap();
+        // a diamond, so the edges cut through pixels at an angle
+        let vertices = vec![
+            Position(0.0, -0.8),
+            Position(0.7, 0.0),
+            Position(0.0, 0.9),
+            Position(-0.6, 0.0),
+        ];
+
+        renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+        renderer
+            .render_indexed(&buffer, &program, &vertices, &[0u16, 1, 2, 0, 2, 3])
+            .unwrap();
+        let short = renderer.read_pixels(0, 0, 64, 64).unwrap();
+
+        renderer.clear(Some((0.0, 0.0, 0.0, 1.0)));
+        renderer
+            .render_indexed(&buffer, &program, &vertices, &[0u32, 1, 2, 0, 2, 3])
+            .unwrap();
+        let long = renderer.read_pixels(0, 0, 64, 64).unwrap();
+
+        assert_color(renderer, (30, 30, 4, 4), [0, 255, 0, 255]);
+        assert_color(renderer, (0, 0, 4, 4), [0, 0, 0, 255]);
+        assert!(
+            short.data == long.data,
+            "u16 and u32 indices drew different pixels"
+        );
+    });
 }
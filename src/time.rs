@@ -0,0 +1,110 @@
+use platform::time as time_impl;
+
+/// Seconds since an unspecified point, only meaningful when compared to other
+/// calls
+pub fn now() -> f64 {
+    time_impl::now()
+}
+
+/// Calls `f` once per frame with the current time from `now` until it returns
+/// `false`. On the web frames are scheduled with `requestAnimationFrame` and
+/// this returns right away, natively it blocks until the loop is stopped.
+pub fn run_loop<F: FnMut(f64) -> bool + 'static>(f: F) {
+    time_impl::run_loop(f)
+}
+
+/// Measures time from when it was created, and between calls to `delta`
+pub struct Clock {
+    start: f64,
+    last: f64,
+}
+
+impl Clock {
+    pub fn new() -> Clock {
+        let now = now();
+        Clock {
+            start: now,
+            last: now,
+        }
+    }
+
+    /// Seconds since the clock was created
+    pub fn now(&self) -> f64 {
+        now() - self.start
+    }
+
+    /// Seconds since the last call, or since the clock was created
+    pub fn delta(&mut self) -> f64 {
+        let now = now();
+        let delta = now - self.last;
+        self.last = now;
+        delta
+    }
+}
+
+/// Splits variable frame times into steps of a fixed length, for simulations that
+/// have to behave the same regardless of the frame rate.
+///
+/// ```ignore
+/// for _ in 0..timestep.advance(dt) {
+///     world.step(timestep.step());
+/// }
+/// ```
+pub struct FixedTimestep {
+    step: f64,
+    max_steps: u32,
+    accumulator: f64,
+}
+
+impl FixedTimestep {
+    pub fn new(step: f64) -> FixedTimestep {
+        FixedTimestep {
+            step,
+            max_steps: 8,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Limits how many steps a single frame can take, time beyond that is
+    /// dropped so that a long pause doesn't have to be simulated all at once
+    pub fn max_steps(mut self, max_steps: u32) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Adds `dt` seconds and returns the number of steps to simulate
+    pub fn advance(&mut self, dt: f64) -> u32 {
+        self.accumulator += dt;
+        let steps = (self.accumulator / self.step).floor();
+        if steps > self.max_steps as f64 {
+            self.accumulator = 0.0;
+            return self.max_steps;
+        }
+        self.accumulator -= steps * self.step;
+        steps as u32
+    }
+
+    /// How far into the next step the leftover time is, between 0 and 1, for
+    /// interpolating between the last two simulated states
+    pub fn alpha(&self) -> f64 {
+        self.accumulator / self.step
+    }
+}
+
+#[test]
+fn fixed_timestep() {
+    let mut timestep = FixedTimestep::new(0.25);
+    assert_eq!(timestep.advance(0.1), 0);
+    assert_eq!(timestep.advance(0.2), 1);
+    assert!((timestep.alpha() - 0.2).abs() < 1e-9);
+    assert_eq!(timestep.advance(0.7), 3);
+    assert!(timestep.alpha().abs() < 1e-9);
+
+    let mut timestep = FixedTimestep::new(0.25).max_steps(2);
+    assert_eq!(timestep.advance(10.0), 2);
+    assert_eq!(timestep.alpha(), 0.0);
+}
@@ -4,8 +4,9 @@ use failure::Error;
 
 use math::Vec2;
 use platform::window as window_impl;
+use platform::Window as PlatformWindow;
 
-use rendering::{RenderTarget, Renderer};
+use rendering::{BlendMode, RenderTarget, Renderer};
 
 use super::PlatformContext;
 
@@ -14,6 +15,13 @@ pub struct WindowSettings {
     pub title: Option<String>,
     pub size: Option<Vec2<u32>>,
     pub canvas_id: Option<String>,
+    /// Renders at the full resolution of high DPI screens, with sizes and
+    /// positions reported in those pixels. Off by default, which scales up the
+    /// rendering and blurs it on such screens.
+    pub high_dpi: bool,
+    /// Requests a debug GL context, which makes `Renderer::enable_debug_output`
+    /// report more. Ignored on the web
+    pub gl_debug: bool,
 }
 
 impl WindowSettings {
@@ -33,6 +41,31 @@ impl WindowSettings {
         self.canvas_id = Some(id);
         self
     }
+    pub fn high_dpi(mut self, enabled: bool) -> Self {
+        self.high_dpi = enabled;
+        self
+    }
+    pub fn gl_debug(mut self, enabled: bool) -> Self {
+        self.gl_debug = enabled;
+        self
+    }
+}
+
+/// Cursor shown while the mouse is over the window
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CursorStyle {
+    Default,
+    /// The hand shown over links
+    Pointer,
+    Crosshair,
+    Move,
+    None,
+}
+
+impl Default for CursorStyle {
+    fn default() -> CursorStyle {
+        CursorStyle::Default
+    }
 }
 
 pub struct GLContext(window_impl::GLContext);
@@ -45,17 +78,93 @@ pub struct Window {
 impl Window {
     pub fn new(context: &mut PlatformContext, settings: WindowSettings) -> Result<Window, Error> {
         let window = context.0.window(settings)?;
-        let gl_context = Some(GLContext(window.gl_create_context()));
-        Ok(Window {
+        let gl_context = Some(GLContext(window.gl_create_context()?));
+        let window = Window {
             inner: window,
             gl_context,
-        })
+        };
+        window.renderer().set_blend_mode(BlendMode::default());
+        Ok(window)
     }
 
+    /// Every window has its own GL context, so textures, programs and buffers
+    /// created through the renderer of one window can't be used with another's
     pub fn renderer<'a>(&'a self) -> Renderer<'a> {
         Renderer::new(self)
     }
 
+    /// Size of the drawable area in pixels, the size `Resize` events report
+    pub fn size(&self) -> Vec2<u32> {
+        let (width, height) = self.inner.size();
+        Vec2::new(width, height)
+    }
+
+    /// Size of the window in screen points, or of the canvas in CSS pixels
+    pub fn logical_size(&self) -> Vec2<f32> {
+        let (width, height) = self.inner.logical_size();
+        Vec2::new(width, height)
+    }
+
+    /// Drawable pixels per screen point, above 1.0 on high DPI screens when the
+    /// window was created with `WindowSettings::high_dpi`
+    pub fn scale_factor(&self) -> f32 {
+        let logical = self.logical_size();
+        if logical.x <= 0.0 {
+            return 1.0;
+        }
+        self.size().x as f32 / logical.x
+    }
+
+    /// Sets the title of the window, or of the document on the web
+    pub fn set_title(&self, title: &str) -> Result<(), Error> {
+        self.inner.set_title(title)
+    }
+
+    /// Shows or hides the cursor while it is over the window, the cursor style is
+    /// kept while hidden
+    pub fn set_cursor_visible(&self, visible: bool) -> Result<(), Error> {
+        self.inner.set_cursor_visible(visible)
+    }
+
+    pub fn set_cursor_style(&self, style: CursorStyle) -> Result<(), Error> {
+        self.inner.set_cursor_style(style)
+    }
+
+    /// Makes the window cover the whole screen, a `FullscreenChanged` and a
+    /// `Resize` event are sent once it has. Browsers only allow this in response
+    /// to user input, so call it in the frame a key press or click is reported.
+    pub fn request_fullscreen(&self) -> Result<(), Error> {
+        self.inner.set_fullscreen(true)
+    }
+
+    pub fn exit_fullscreen(&self) -> Result<(), Error> {
+        self.inner.set_fullscreen(false)
+    }
+
+    /// Hides the cursor and keeps it inside the window, mouse movement is then
+    /// reported through `Input::mouse_delta`. Browsers only allow this shortly
+    /// after a click or key press.
+    pub fn request_pointer_lock(&self) {
+        self.inner.set_pointer_lock(true);
+    }
+
+    pub fn exit_pointer_lock(&self) {
+        self.inner.set_pointer_lock(false);
+    }
+
+    /// Loses the window's WebGL context on purpose through `WEBGL_lose_context`,
+    /// to test that a game recovers from `InputEvent::ContextLost`. Does nothing
+    /// natively, where contexts aren't lost.
+    pub fn simulate_context_loss(&self) {
+        self.inner.set_context_lost(true);
+    }
+
+    /// Restores a context lost with `simulate_context_loss`, which is followed by
+    /// `InputEvent::ContextRestored`
+    pub fn simulate_context_restore(&self) {
+        self.inner.set_context_lost(false);
+    }
+
     fn gl_make_current(&self) {
         self.inner
             .gl_set_current(&self.gl_context.as_ref().expect("no gl context set").0);